@@ -8,69 +8,303 @@
 
 use anyhow::Result;
 use num_bigint::BigUint;
-use num_integer::Integer;
-use num_traits::{One, ToPrimitive, Zero};
+use num_traits::{One, Zero};
 use rand::{Rng, rng};
 
-use crate::error::SsssError::{BadCharacter, InvalidShareFormat};
+use crate::{
+    encoding::{ShareEncoding, checksum, decode_index},
+    error::SsssError::{BadCharacter, InvalidShareFormat},
+};
 
 const BASE: usize = 62;
 const PREFIX: usize = 10;
-const ALPHABET: [char; BASE] = [
-    '0', '1', '2', '3', '4', '5', '6', '7', '8', '9', 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I',
-    'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q', 'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z', 'a', 'b',
-    'c', 'd', 'e', 'f', 'g', 'h', 'i', 'j', 'k', 'l', 'm', 'n', 'o', 'p', 'q', 'r', 's', 't', 'u',
-    'v', 'w', 'x', 'y', 'z',
-];
+/// Marks a block encoded string.  It is outside the base62 alphabet, so a
+/// legacy (`BigUint`) encoded string can never start with it.
+const BLOCK_TAG: char = '.';
+/// Marks a block encoded string without a nonce
+const CANONICAL_TAG: char = '~';
+/// The number of bytes in a full block
+const BLOCK_BYTES: usize = 8;
+/// The number of characters in a full block (62^11 > 2^64)
+const BLOCK_CHARS: usize = 11;
+/// The number of characters used to encode a trailing block of `n` bytes
+const TAIL_CHARS: [usize; BLOCK_BYTES] = [0, 2, 3, 5, 6, 7, 9, 10];
 
+/// Base62 shares, e.g. `.4Ax...:.Ub7...:~Q9...`.
+///
+/// The share index and the share bytes are encoded separately, followed by a
+/// checksum.  By default each is prefixed with a random nonce, so the same
+/// share encodes differently every time.  A canonical encoder omits the nonce.
+///
+/// Legacy shares, which have no checksum, are still decoded.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Base62 {
+    canonical: bool,
+}
+
+impl Base62 {
+    /// Create a base62 encoding, optionally omitting the random nonce
+    #[must_use]
+    pub fn new(canonical: bool) -> Self {
+        Self { canonical }
+    }
+}
+
+impl ShareEncoding for Base62 {
+    fn encode(&self, index: u8, share: &[u8]) -> String {
+        let encode = if self.canonical {
+            encode_canonical
+        } else {
+            encode
+        };
+        let share = format!("{}:{}", encode(&[index]), encode(share));
+        checksum::append(&share, 0, normalize, encode_canonical)
+    }
+
+    fn decode(&self, share: &str) -> Result<(u8, Vec<u8>)> {
+        let share = if share.starts_with([BLOCK_TAG, CANONICAL_TAG]) {
+            checksum::verify(share, 0, normalize, decode)?
+        } else {
+            share
+        };
+        match share.split(':').collect::<Vec<&str>>()[..] {
+            [index, share] => Ok((decode_index(&decode(index)?)?, decode(share)?)),
+            _ => Err(InvalidShareFormat.into()),
+        }
+    }
+}
+
+#[allow(clippy::unnecessary_wraps)]
+fn normalize(c: char) -> Option<char> {
+    Some(c)
+}
+
+/// Encode the given bytes as base62.
+///
+/// A random `PREFIX`-byte nonce is prepended to the bytes, which are then
+/// encoded in fixed-width blocks of 8 bytes to 11 characters.  The work done
+/// depends only on the length of the input, never on its contents.
 pub(crate) fn encode(bytes: &[u8]) -> String {
     if bytes.is_empty() {
         String::new()
     } else {
-        let mut nonce = [0u8; 10];
-        rng().fill_bytes(&mut nonce);
-        let mut input = nonce.to_vec();
-        input[0] = 1;
+        let mut input = vec![0u8; PREFIX];
+        rng().fill_bytes(&mut input);
         input.extend_from_slice(bytes);
+        encode_blocks(BLOCK_TAG, &input)
+    }
+}
 
-        let mut result = String::new();
-        let mut val = BigUint::from_bytes_be(&input);
-        let base: BigUint = (BASE.to_owned() as u64).into();
-
-        while val > BigUint::zero() {
-            let remainder = val.mod_floor(&base).to_usize().unwrap_or(0);
-            result.push(ALPHABET[remainder]);
-            val /= &base;
-        }
-
-        result
+/// Encode the given bytes as base62 without a nonce.
+///
+/// The same bytes always produce the same string, so canonical strings can be
+/// compared, deduplicated or hashed directly.
+pub(crate) fn encode_canonical(bytes: &[u8]) -> String {
+    if bytes.is_empty() {
+        String::new()
+    } else {
+        encode_blocks(CANONICAL_TAG, bytes)
     }
 }
 
+/// Decode the given base62 string.
+///
+/// The block encodings produced by [`encode`] and [`encode_canonical`] and the
+/// legacy `BigUint` encoding produced by earlier releases are all accepted, and
+/// decode to the same bytes.
 pub(crate) fn decode(input: &str) -> Result<Vec<u8>> {
     if input.is_empty() {
         Ok(vec![])
-    } else {
-        let mut val: BigUint = BigUint::zero();
-        let mut base_mul = BigUint::one();
-        let base: BigUint = (BASE.to_owned() as u64).into();
-
-        for c in input.chars() {
-            let remainder: BigUint = char_to_remainder(c)?.into();
-            val += remainder * &base_mul;
-            base_mul *= &base;
-        }
-        // A valid share always encodes a `PREFIX`-byte nonce ahead of at least
-        // one payload byte (and the nonce's leading byte is forced non-zero), so
-        // the decoded buffer is always longer than `PREFIX`. A shorter buffer
-        // means the input was not produced by `encode`; reject it rather than
-        // panicking on an out-of-range slice.
-        let bytes = val.to_bytes_be();
-        if bytes.len() < PREFIX {
+    } else if let Some(blocks) = input.strip_prefix(BLOCK_TAG) {
+        let bytes = decode_blocks(blocks)?;
+        if bytes.len() <= PREFIX {
             Err(InvalidShareFormat.into())
         } else {
             Ok(bytes[PREFIX..].to_vec())
         }
+    } else if let Some(blocks) = input.strip_prefix(CANONICAL_TAG) {
+        let bytes = decode_blocks(blocks)?;
+        if bytes.is_empty() {
+            Err(InvalidShareFormat.into())
+        } else {
+            Ok(bytes)
+        }
+    } else {
+        decode_legacy(input)
+    }
+}
+
+/// Read the index and share bytes of a damaged share, ignoring its checksum.
+///
+/// A block holding an unreadable character is erased as a whole.  Legacy
+/// shares cannot be read this way.
+pub(crate) fn read_damaged(share: &str) -> Option<Vec<Option<u8>>> {
+    match share.split(':').collect::<Vec<&str>>()[..] {
+        [index, share] | [index, share, _] => {
+            let mut bytes = read_field(index)?;
+            bytes.extend(read_field(share)?);
+            Some(bytes)
+        }
+        _ => None,
+    }
+}
+
+fn read_field(input: &str) -> Option<Vec<Option<u8>>> {
+    if let Some(blocks) = input.strip_prefix(BLOCK_TAG) {
+        read_blocks(blocks).map(|bytes| bytes.into_iter().skip(PREFIX).collect())
+    } else {
+        read_blocks(input.strip_prefix(CANONICAL_TAG)?)
+    }
+}
+
+fn read_blocks(input: &str) -> Option<Vec<Option<u8>>> {
+    let chars = input.as_bytes();
+    let tail_bytes = TAIL_CHARS
+        .iter()
+        .position(|len| *len == chars.len() % BLOCK_CHARS)?;
+
+    let mut bytes = vec![];
+    for block in chars.chunks(BLOCK_CHARS) {
+        let len = if block.len() == BLOCK_CHARS {
+            BLOCK_BYTES
+        } else {
+            tail_bytes
+        };
+        let val = block
+            .iter()
+            .try_fold(0_u128, |val, c| match char_to_digit(*c) {
+                (digit, 0) => Some(val * BASE as u128 + u128::from(digit)),
+                _ => None,
+            });
+        match val.filter(|val| val >> (8 * len) == 0) {
+            Some(val) => bytes.extend(val.to_be_bytes()[16 - len..].iter().copied().map(Some)),
+            None => bytes.extend(vec![None; len]),
+        }
+    }
+    Some(bytes)
+}
+
+fn encode_blocks(tag: char, input: &[u8]) -> String {
+    let mut result = String::with_capacity(1 + encoded_len(input.len()));
+    result.push(tag);
+    for block in input.chunks(BLOCK_BYTES) {
+        encode_block(block, &mut result);
+    }
+    result
+}
+
+fn encoded_len(len: usize) -> usize {
+    (len / BLOCK_BYTES) * BLOCK_CHARS + TAIL_CHARS[len % BLOCK_BYTES]
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn encode_block(block: &[u8], result: &mut String) {
+    let mut buf = [0u8; BLOCK_BYTES];
+    buf[BLOCK_BYTES - block.len()..].copy_from_slice(block);
+    let mut val = u64::from_be_bytes(buf);
+    let width = if block.len() == BLOCK_BYTES {
+        BLOCK_CHARS
+    } else {
+        TAIL_CHARS[block.len()]
+    };
+
+    let mut digits = [0u8; BLOCK_CHARS];
+    for digit in digits[..width].iter_mut().rev() {
+        *digit = remainder_to_char((val % BASE as u64) as u8);
+        val /= BASE as u64;
+    }
+    result.extend(digits[..width].iter().map(|d| char::from(*d)));
+}
+
+fn decode_blocks(input: &str) -> Result<Vec<u8>> {
+    let chars = input.as_bytes();
+    let tail_chars = chars.len() % BLOCK_CHARS;
+    let tail_bytes = TAIL_CHARS
+        .iter()
+        .position(|len| *len == tail_chars)
+        .ok_or(InvalidShareFormat)?;
+
+    let mut bytes = Vec::with_capacity((chars.len() / BLOCK_CHARS) * BLOCK_BYTES + tail_bytes);
+    let mut invalid = 0;
+    let mut overflow = 0;
+
+    for block in chars.chunks(BLOCK_CHARS) {
+        let len = if block.len() == BLOCK_CHARS {
+            BLOCK_BYTES
+        } else {
+            tail_bytes
+        };
+        let mut val: u128 = 0;
+        for c in block {
+            let (digit, bad) = char_to_digit(*c);
+            invalid |= bad;
+            val = val * BASE as u128 + u128::from(digit);
+        }
+        overflow |= u8::from(val >> (8 * len) != 0);
+        bytes.extend_from_slice(&val.to_be_bytes()[16 - len..]);
+    }
+
+    if invalid != 0 {
+        // Only reached for bad input, so scanning for the culprit here does
+        // not leak anything about a valid share.
+        let c = input
+            .chars()
+            .find(|c| !c.is_ascii_alphanumeric())
+            .unwrap_or_default();
+        Err(BadCharacter { c }.into())
+    } else if overflow != 0 {
+        Err(InvalidShareFormat.into())
+    } else {
+        Ok(bytes)
+    }
+}
+
+/// Map a value in `0..62` to its character without branching on the value
+fn remainder_to_char(val: u8) -> u8 {
+    let ge_10 = ge_mask(val, 10);
+    let ge_36 = ge_mask(val, 36);
+    b'0' + val + (ge_10 & 7) + (ge_36 & 6)
+}
+
+/// Map a character to its value without branching on the character.
+/// The second element of the result is non-zero if the character is invalid.
+fn char_to_digit(c: u8) -> (u8, u8) {
+    let digit = ge_mask(c, b'0') & !ge_mask(c, b'9' + 1);
+    let upper = ge_mask(c, b'A') & !ge_mask(c, b'Z' + 1);
+    let lower = ge_mask(c, b'a') & !ge_mask(c, b'z' + 1);
+    let val = (digit & c.wrapping_sub(b'0'))
+        | (upper & c.wrapping_sub(b'A' - 10))
+        | (lower & c.wrapping_sub(b'a' - 36));
+    (val, !(digit | upper | lower))
+}
+
+/// `0xff` if `val >= bound`, otherwise `0x00`
+fn ge_mask(val: u8, bound: u8) -> u8 {
+    let diff = u16::from(val).wrapping_sub(u16::from(bound));
+    ((diff >> 8) as u8).wrapping_add(1).wrapping_neg()
+}
+
+/// Decode a string produced by the original `BigUint` encoder
+fn decode_legacy(input: &str) -> Result<Vec<u8>> {
+    let mut val: BigUint = BigUint::zero();
+    let mut base_mul = BigUint::one();
+    let base: BigUint = (BASE.to_owned() as u64).into();
+
+    for c in input.chars() {
+        let remainder: BigUint = char_to_remainder(c)?.into();
+        val += remainder * &base_mul;
+        base_mul *= &base;
+    }
+    // A valid share always encodes a `PREFIX`-byte nonce ahead of at least
+    // one payload byte (and the nonce's leading byte is forced non-zero), so
+    // the decoded buffer is always longer than `PREFIX`. A shorter buffer
+    // means the input was not produced by `encode`; reject it rather than
+    // panicking on an out-of-range slice.
+    let bytes = val.to_bytes_be();
+    if bytes.len() < PREFIX {
+        Err(InvalidShareFormat.into())
+    } else {
+        Ok(bytes[PREFIX..].to_vec())
     }
 }
 
@@ -87,7 +321,10 @@ fn char_to_remainder(c: char) -> Result<u64> {
 
 #[cfg(test)]
 mod test {
-    use super::{decode, encode};
+    use super::{
+        BLOCK_CHARS, PREFIX, char_to_digit, decode, encode, encode_canonical, encoded_len,
+        remainder_to_char,
+    };
     use crate::utils::check_err_result;
     use anyhow::Result;
 
@@ -98,6 +335,75 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn encode_decode_roundtrips_all_lengths() -> Result<()> {
+        for len in 1..=40 {
+            let secret: Vec<u8> = (0..len).map(|i| 255 - i).collect();
+            let encoded = encode(&secret);
+            assert_eq!(encoded.len(), 1 + encoded_len(PREFIX + usize::from(len)));
+            assert_eq!(decode(&encoded)?, secret);
+        }
+        assert_eq!(decode(&encode(&[0; 16]))?, [0; 16]);
+        assert_eq!(decode(&encode(&[0xff; 16]))?, [0xff; 16]);
+        Ok(())
+    }
+
+    #[test]
+    fn encode_canonical_is_deterministic() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        let canonical = encode_canonical(secret);
+        assert_eq!(canonical, encode_canonical(secret));
+        assert_eq!(canonical.len(), 1 + encoded_len(secret.len()));
+        assert_eq!(decode(&canonical)?, secret);
+        assert_eq!(decode(&encode_canonical(&[0; 3]))?, [0; 3]);
+        assert!(encode_canonical(&[]).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn decode_canonical_empty_errors() -> Result<()> {
+        check_err_result(decode("~"), "Invalid share format")
+    }
+
+    #[test]
+    fn digit_mapping_roundtrips() {
+        for val in 0..62 {
+            assert_eq!(char_to_digit(remainder_to_char(val)), (val, 0));
+        }
+        for c in 0..=u8::MAX {
+            let (_, bad) = char_to_digit(c);
+            assert_eq!(bad == 0, c.is_ascii_alphanumeric());
+        }
+    }
+
+    #[test]
+    fn decode_legacy_works() -> Result<()> {
+        assert_eq!(
+            decode("PoCEPVX9LMxxGL8QxtPt7wiFKe3sND1u8q0rgXWaeYxqktGtLP")?,
+            "correct horse battery staple".as_bytes()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn decode_bad_block_length_errors() -> Result<()> {
+        check_err_result(decode(".0"), "Invalid share format")?;
+        check_err_result(decode(".0000"), "Invalid share format")
+    }
+
+    #[test]
+    fn decode_block_overflow_errors() -> Result<()> {
+        let overflow = format!(".{}", "z".repeat(BLOCK_CHARS * 2));
+        check_err_result(decode(&overflow), "Invalid share format")
+    }
+
+    #[test]
+    fn decode_block_bad_character_errors() -> Result<()> {
+        let encoded = encode("abc".as_bytes());
+        let encoded = format!("{}#", &encoded[..encoded.len() - 1]);
+        check_err_result(decode(&encoded), "Base62 docoding bad character: #")
+    }
+
     #[test]
     fn decode_empty_is_empty() -> Result<()> {
         assert!(decode("")?.is_empty());
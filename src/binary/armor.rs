@@ -0,0 +1,150 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` PEM-style ASCII armor
+
+use crate::error::SsssError::InvalidArmor;
+use anyhow::Result;
+
+const BEGIN: &str = "-----BEGIN SSSS SHARE-----";
+const END: &str = "-----END SSSS SHARE-----";
+/// The standard base64 alphabet, as used by PEM
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+const PAD: char = '=';
+/// The number of base64 characters on each line
+const LINE_CHARS: usize = 64;
+
+/// Wrap the bytes in the armor
+pub(crate) fn wrap(bytes: &[u8]) -> String {
+    let encoded = encode(bytes);
+    let mut lines = vec![BEGIN];
+    lines.extend(
+        encoded
+            .as_bytes()
+            .chunks(LINE_CHARS)
+            .filter_map(|line| std::str::from_utf8(line).ok()),
+    );
+    lines.push(END);
+    lines.join("\n") + "\n"
+}
+
+/// Unwrap the bytes from the armor.  Text before and after the armor, blank
+/// lines and `Key: value` header lines inside it are ignored.
+pub(crate) fn unwrap(armored: &str) -> Result<Vec<u8>> {
+    let body = armored
+        .split_once(BEGIN)
+        .and_then(|(_, rest)| rest.split_once(END))
+        .map(|(body, _)| body)
+        .ok_or(InvalidArmor)?;
+    let encoded: String = body
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.contains(':'))
+        .collect();
+    decode(&encoded)
+}
+
+/// Check if the share is wrapped in the armor
+pub(crate) fn is_armored(share: &str) -> bool {
+    share.trim_start().starts_with(BEGIN)
+}
+
+fn encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let mut buf = [0; 3];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let group = u32::from_be_bytes([0, buf[0], buf[1], buf[2]]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let sextet = (group >> (18 - 6 * i)) & 0x3f;
+                result.push(char::from(ALPHABET[sextet as usize]));
+            } else {
+                result.push(PAD);
+            }
+        }
+    }
+    result
+}
+
+fn decode(encoded: &str) -> Result<Vec<u8>> {
+    let data = encoded.trim_end_matches(PAD);
+    if encoded.len() % 4 != 0 || encoded.len() - data.len() > 2 {
+        return Err(InvalidArmor.into());
+    }
+    let sextets = data
+        .bytes()
+        .map(|c| ALPHABET.iter().position(|a| *a == c).ok_or(InvalidArmor))
+        .collect::<Result<Vec<usize>, _>>()?;
+
+    let mut bytes = Vec::with_capacity(sextets.len() * 3 / 4);
+    for chunk in sextets.chunks(4) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, sextet)| acc | sextet << (18 - 6 * i));
+        let len = chunk.len() - 1;
+        bytes.extend(&group.to_be_bytes()[5..5 + len]);
+    }
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode, unwrap, wrap};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+
+    #[test]
+    fn base64_works() -> Result<()> {
+        for (bytes, encoded) in [
+            (&b""[..], ""),
+            (b"f", "Zg=="),
+            (b"fo", "Zm8="),
+            (b"foo", "Zm9v"),
+            (b"foob", "Zm9vYg=="),
+            (b"\xfb\xff", "+/8="),
+        ] {
+            assert_eq!(encode(bytes), encoded);
+            assert_eq!(decode(encoded)?, bytes);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn armor_roundtrip() -> Result<()> {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let armored = wrap(&bytes);
+        assert!(armored.lines().all(|line| line.len() <= 64));
+        assert_eq!(armored.lines().count(), 8);
+        assert_eq!(unwrap(&armored)?, bytes);
+        Ok(())
+    }
+
+    #[test]
+    fn armor_in_an_email() -> Result<()> {
+        let armored = wrap(b"correct horse battery staple");
+        let email = format!(
+            "Hi,\n\nhere is my share:\n\n{}\nThanks\n",
+            armored.replacen("-----\n", "-----\nComment: share 2\n\n", 1)
+        );
+        assert_eq!(unwrap(&email)?, b"correct horse battery staple");
+        Ok(())
+    }
+
+    #[test]
+    fn bad_armor_errors() -> Result<()> {
+        check_err_result(
+            unwrap("-----BEGIN SSSS SHARE-----\nZm9v"),
+            "Invalid share armor",
+        )?;
+        check_err_result(decode("Zm9"), "Invalid share armor")?;
+        check_err_result(decode("Zm9*"), "Invalid share armor")?;
+        check_err_result(decode("Z==="), "Invalid share armor")
+    }
+}
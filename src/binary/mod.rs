@@ -0,0 +1,404 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` binary share format
+//!
+//! A binary share is laid out as follows, with multi-byte integers in
+//! big-endian order:
+//!
+//! | Size | Field                                                   |
+//! |------|---------------------------------------------------------|
+//! | 4    | The magic number, `SSSS`                                |
+//! | 1    | The format version, currently 1                         |
+//! | 1    | Flags marking the optional header fields present        |
+//! | 1    | The share index                                         |
+//! | 1    | The threshold, if flag `0x01` is set                    |
+//! | 1    | The total number of shares, if flag `0x02` is set       |
+//! | 2    | The payload length                                      |
+//! | n    | The payload, the share bytes                            |
+//! | 4    | A CRC-32 (ISO-HDLC) of all of the preceding bytes       |
+//!
+//! Unknown flags are rejected, so a later version can add header fields
+//! without being misread.
+
+mod armor;
+
+use crate::{
+    SsssConfig,
+    base62::Base62,
+    encoding::ShareEncoding,
+    error::SsssError::{
+        BinaryChecksum, BinaryFlags, BinaryLength, BinaryMagic, BinaryVersion, SecretLength,
+    },
+    shamir::decode_share,
+    uri::ShareUri,
+};
+use anyhow::Result;
+use std::{fmt, str::FromStr};
+
+/// The magic number at the start of every binary share
+const MAGIC: &[u8; 4] = b"SSSS";
+/// The current format version
+const VERSION: u8 = 1;
+/// The threshold is present in the header
+const FLAG_THRESHOLD: u8 = 0x01;
+/// The total number of shares is present in the header
+const FLAG_NUM_SHARES: u8 = 0x02;
+/// The length of the CRC-32 at the end of a binary share
+const CHECKSUM_LEN: usize = 4;
+
+/// A share in the versioned binary format, for storing shares in files or
+/// sending them over the wire.
+///
+/// The [`Display`](fmt::Display) and [`FromStr`] implementations use a
+/// PEM-style ASCII armor, so a binary share can be pasted into an email:
+///
+/// ```text
+/// -----BEGIN SSSS SHARE-----
+/// U1NTUwEDAgMFABxjb3JyZWN0IGhvcnNlIGJhdHRlcnkgc3RhcGxlN3LxAQ==
+/// -----END SSSS SHARE-----
+/// ```
+///
+/// [`unlock`](crate::unlock) accepts armored shares directly, and shares in
+/// any other format, including legacy base62 shares, can be moved to this
+/// format with [`convert`].
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{gen_shares, unlock, BinaryShare, SsssConfig};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = "correct horse battery staple".as_bytes();
+/// let config = SsssConfig::default();
+/// let shares = gen_shares(&config, &secret)?;
+///
+/// let binary = BinaryShare::new(&config, &shares[0])?;
+/// let bytes = binary.to_bytes();
+/// assert!(bytes.starts_with(b"SSSS"));
+/// assert_eq!(BinaryShare::from_bytes(&bytes)?, binary);
+///
+/// let armored: Vec<String> = shares
+///     .iter()
+///     .map(|share| Ok(BinaryShare::new(&config, share)?.to_string()))
+///     .collect::<Result<_>>()?;
+/// assert!(armored[0].starts_with("-----BEGIN SSSS SHARE-----"));
+/// assert_eq!(unlock(&armored)?, secret);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct BinaryShare {
+    index: u8,
+    threshold: Option<u8>,
+    num_shares: Option<u8>,
+    share: Vec<u8>,
+}
+
+impl BinaryShare {
+    /// Convert a share generated with the given configuration
+    ///
+    /// # Errors
+    /// * This function will generate an error if the share cannot be decoded,
+    ///   or is longer than [`u16::MAX`] bytes.
+    /// * This function will generate an error if the configuration has more
+    ///   than 255 shares.
+    pub fn new(config: &SsssConfig, share: &str) -> Result<Self> {
+        let mut binary = convert(share)?;
+        binary.threshold = Some(u8::try_from(config.threshold())?);
+        binary.num_shares = Some(u8::try_from(config.num_shares())?);
+        Ok(binary)
+    }
+
+    /// Read a share from its binary form
+    ///
+    /// # Errors
+    /// * This function will generate an error if the bytes are not a binary
+    ///   share, if the share was written by a later version of the format, or
+    ///   if its checksum does not match.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let (body, checksum) = bytes
+            .split_last_chunk::<CHECKSUM_LEN>()
+            .ok_or(BinaryLength)?;
+        let mut reader = body.iter().copied();
+        let mut next = || reader.next().ok_or(BinaryLength);
+
+        if [next()?, next()?, next()?, next()?] != *MAGIC {
+            return Err(BinaryMagic.into());
+        }
+        let version = next()?;
+        if version != VERSION {
+            return Err(BinaryVersion { version }.into());
+        }
+        if crc32(body) != u32::from_be_bytes(*checksum) {
+            return Err(BinaryChecksum.into());
+        }
+        let flags = next()?;
+        if flags & !(FLAG_THRESHOLD | FLAG_NUM_SHARES) != 0 {
+            return Err(BinaryFlags { flags }.into());
+        }
+        let index = next()?;
+        let threshold = (flags & FLAG_THRESHOLD != 0).then(&mut next).transpose()?;
+        let num_shares = (flags & FLAG_NUM_SHARES != 0).then(&mut next).transpose()?;
+        let length = usize::from(u16::from_be_bytes([next()?, next()?]));
+        let share: Vec<u8> = reader.collect();
+        if share.len() == length {
+            Ok(Self {
+                index,
+                threshold,
+                num_shares,
+                share,
+            })
+        } else {
+            Err(BinaryLength.into())
+        }
+    }
+
+    /// The share index
+    #[must_use]
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// The number of shares required to unlock the secret, if known
+    #[must_use]
+    pub fn threshold(&self) -> Option<u8> {
+        self.threshold
+    }
+
+    /// The total number of shares generated, if known
+    #[must_use]
+    pub fn num_shares(&self) -> Option<u8> {
+        self.num_shares
+    }
+
+    /// The share, in the canonical [`Base62`] encoding
+    #[must_use]
+    pub fn share(&self) -> String {
+        Base62::new(true).encode(self.index, &self.share)
+    }
+
+    /// The binary form of the share
+    #[must_use]
+    #[allow(clippy::cast_possible_truncation)]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut flags = 0;
+        if self.threshold.is_some() {
+            flags |= FLAG_THRESHOLD;
+        }
+        if self.num_shares.is_some() {
+            flags |= FLAG_NUM_SHARES;
+        }
+        // The length is checked when the share is created
+        let length = self.share.len() as u16;
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([VERSION, flags, self.index]);
+        bytes.extend(self.threshold);
+        bytes.extend(self.num_shares);
+        bytes.extend(length.to_be_bytes());
+        bytes.extend(&self.share);
+        bytes.extend(crc32(&bytes).to_be_bytes());
+        bytes
+    }
+}
+
+impl fmt::Display for BinaryShare {
+    /// The armored binary form of the share
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", armor::wrap(&self.to_bytes()))
+    }
+}
+
+impl FromStr for BinaryShare {
+    type Err = anyhow::Error;
+
+    /// Read an armored share.  Surrounding text, blank lines and header lines
+    /// inside the armor are ignored.
+    fn from_str(armored: &str) -> Result<Self> {
+        Self::from_bytes(&armor::unwrap(armored)?)
+    }
+}
+
+/// Convert a share in any format this crate reads to the binary format,
+/// without reconstructing the secret.
+///
+/// This is the migration path for legacy `index:payload` base62 shares, which
+/// carry no checksum.  The threshold and the number of shares are kept when
+/// the share is a [`ShareUri`] that records them.
+///
+/// # Errors
+/// * This function will generate an error if the share cannot be decoded,
+///   or is longer than [`u16::MAX`] bytes.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{convert, unlock};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let legacy = [
+///     "33jCjZe3IiGKzB:2n9WMPdcfwwXOTyAspUoHORa1JbGOZGv9mrB4CoNZi3y2naqzN",
+///     "dA0Ae4PlUzJsQB:qjRFCjVjSQUplJN04XPkcycRALl7lTwFVJJQfbw4OWxQMv6d5W",
+///     "N7ZIELkfewuwz8:KHHOHSMxEPFPUA0uiGsdwpHGc9TjYTioxsuTixcUUf5Wg5ICaW",
+/// ];
+/// let converted: Vec<String> = legacy
+///     .iter()
+///     .map(|share| Ok(convert(share)?.to_string()))
+///     .collect::<Result<_>>()?;
+/// assert_eq!(unlock(&converted)?, b"correct horse battery staple");
+/// # Ok(())
+/// # }
+/// ```
+pub fn convert(share: &str) -> Result<BinaryShare> {
+    let (threshold, num_shares) = share
+        .parse::<ShareUri>()
+        .map(|uri| {
+            (
+                uri.threshold().and_then(|t| u8::try_from(t).ok()),
+                uri.num_shares().and_then(|n| u8::try_from(n).ok()),
+            )
+        })
+        .unwrap_or_default();
+    let (index, share) = decode_share(share.to_string())?;
+    if share.len() > usize::from(u16::MAX) {
+        return Err(SecretLength {
+            length: share.len(),
+            max: usize::from(u16::MAX),
+        }
+        .into());
+    }
+    Ok(BinaryShare {
+        index,
+        threshold,
+        num_shares,
+        share,
+    })
+}
+
+/// Unwrap the share from an armored binary share, or return the share
+/// unchanged
+pub(crate) fn strip(share: String) -> Result<String> {
+    if armor::is_armored(&share) {
+        Ok(share.parse::<BinaryShare>()?.share())
+    } else {
+        Ok(share)
+    }
+}
+
+/// CRC-32/ISO-HDLC, as used by zip and PNG
+fn crc32(bytes: &[u8]) -> u32 {
+    !bytes.iter().fold(u32::MAX, |crc, byte| {
+        (0..8).fold(crc ^ u32::from(*byte), |crc, _| {
+            (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg())
+        })
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BinaryShare, convert, crc32};
+    use crate::{
+        Bip39, ShareUri, SsssConfig, gen_shares, reencode, unlock, utils::check_err_result,
+    };
+    use anyhow::Result;
+
+    fn binary() -> BinaryShare {
+        BinaryShare {
+            index: 2,
+            threshold: Some(3),
+            num_shares: Some(5),
+            share: b"correct horse battery staple".to_vec(),
+        }
+    }
+
+    #[test]
+    fn crc32_works() {
+        assert_eq!(crc32(b"123456789"), 0xcbf4_3926);
+    }
+
+    #[test]
+    fn binary_layout() {
+        let bytes = binary().to_bytes();
+        assert_eq!(&bytes[..15], b"SSSS\x01\x03\x02\x03\x05\x00\x1ccorr");
+        assert_eq!(bytes.len(), 4 + 1 + 1 + 1 + 2 + 2 + 28 + 4);
+
+        let mut bare = binary();
+        bare.threshold = None;
+        bare.num_shares = None;
+        assert_eq!(&bare.to_bytes()[4..9], b"\x01\x00\x02\x00\x1c");
+    }
+
+    #[test]
+    fn binary_roundtrip() -> Result<()> {
+        let binary = binary();
+        assert_eq!(BinaryShare::from_bytes(&binary.to_bytes())?, binary);
+        assert_eq!(binary.to_string().parse::<BinaryShare>()?, binary);
+        Ok(())
+    }
+
+    #[test]
+    fn bad_binary_errors() -> Result<()> {
+        let bytes = binary().to_bytes();
+        let mut corrupt = bytes.clone();
+        corrupt[20] ^= 1;
+        check_err_result(
+            BinaryShare::from_bytes(&corrupt),
+            "Invalid binary share checksum",
+        )?;
+        check_err_result(
+            BinaryShare::from_bytes(&bytes[1..]),
+            "Not an ssss binary share",
+        )?;
+        check_err_result(
+            BinaryShare::from_bytes(&bytes[..6]),
+            "The binary share is truncated",
+        )?;
+
+        let mut version = bytes.clone();
+        version[4] = 2;
+        check_err_result(
+            BinaryShare::from_bytes(&version),
+            "Unsupported binary share version 2",
+        )?;
+
+        // A flag from a later version, with a valid checksum
+        let mut flags = bytes[..bytes.len() - 4].to_vec();
+        flags[5] |= 0x80;
+        flags.extend(crc32(&flags).to_be_bytes());
+        check_err_result(
+            BinaryShare::from_bytes(&flags),
+            "Unknown binary share flags 0x83",
+        )
+    }
+
+    #[test]
+    fn convert_keeps_known_counts() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        let config = SsssConfig::default();
+        let shares = gen_shares(&config, secret)?;
+
+        let uri = ShareUri::new(&config, &shares[0]).to_string();
+        let converted = convert(&uri)?;
+        assert_eq!(converted.threshold(), Some(3));
+        assert_eq!(converted.num_shares(), Some(5));
+        assert_eq!(converted, BinaryShare::new(&config, &shares[0])?);
+
+        let converted = convert(&reencode(&shares[1], &Bip39)?)?;
+        assert_eq!(converted.index(), 2);
+        assert_eq!(converted.threshold(), None);
+
+        let mixed = vec![
+            convert(&shares[0])?.to_string(),
+            convert(&shares[1])?.share(),
+            shares[2].clone(),
+        ];
+        assert_eq!(unlock(&mixed)?, secret);
+        Ok(())
+    }
+}
@@ -0,0 +1,153 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` compact JWE, encrypted with A256GCM under a direct key
+
+use crate::{
+    encoding::base64url,
+    error::SsssError::{InvalidJwe, JweDecrypt},
+};
+use aes_gcm::{
+    Aes256Gcm, Key, Nonce,
+    aead::{Aead, KeyInit, Payload},
+};
+use anyhow::Result;
+use rand::Rng;
+use serde_json::Value;
+
+/// The length of an A256GCM key
+pub(crate) const KEY_LEN: usize = 32;
+/// The length of an A256GCM initialization vector
+const IV_LEN: usize = 12;
+/// The length of an A256GCM authentication tag
+const TAG_LEN: usize = 16;
+
+/// A compact JWE, `protected..iv.ciphertext.tag`, with no encrypted key
+#[derive(Clone, Debug)]
+pub(crate) struct Jwe {
+    protected: String,
+    header: Value,
+    iv: Vec<u8>,
+    sealed: Vec<u8>,
+}
+
+impl Jwe {
+    /// Read a compact JWE, which must use `dir` and `A256GCM`
+    pub(crate) fn parse(jwe: &str) -> Result<Self> {
+        let parts: Vec<&str> = jwe.trim().split('.').collect();
+        let [protected, "", iv, ciphertext, tag] = parts[..] else {
+            return Err(InvalidJwe.into());
+        };
+        let decode = |part: &str| base64url::decode(part).map_err(|_| InvalidJwe);
+        let header: Value = serde_json::from_slice(&decode(protected)?).map_err(|_| InvalidJwe)?;
+        let iv = decode(iv)?;
+        let mut sealed = decode(ciphertext)?;
+        let tag = decode(tag)?;
+        if header["alg"] != "dir"
+            || header["enc"] != "A256GCM"
+            || iv.len() != IV_LEN
+            || tag.len() != TAG_LEN
+        {
+            return Err(InvalidJwe.into());
+        }
+        sealed.extend(tag);
+        Ok(Jwe {
+            protected: protected.to_string(),
+            header,
+            iv,
+            sealed,
+        })
+    }
+
+    /// The protected header
+    pub(crate) fn header(&self) -> &Value {
+        &self.header
+    }
+
+    /// Decrypt the payload with the direct key
+    pub(crate) fn decrypt(&self, key: &[u8]) -> Result<Vec<u8>> {
+        let cipher = Aes256Gcm::new_from_slice(key).map_err(|_| JweDecrypt)?;
+        let payload = Payload {
+            msg: &self.sealed,
+            aad: self.protected.as_bytes(),
+        };
+        Ok(cipher
+            .decrypt(Nonce::from_slice(&self.iv), payload)
+            .map_err(|_| JweDecrypt)?)
+    }
+}
+
+/// Encrypt the payload under the direct key into a compact JWE.  `alg` and
+/// `enc` are added to the protected header, which must be an object.
+pub(crate) fn encrypt(mut header: Value, key: &[u8; KEY_LEN], payload: &[u8]) -> String {
+    header["alg"] = "dir".into();
+    header["enc"] = "A256GCM".into();
+    let protected = base64url::encode(header.to_string().as_bytes());
+    let mut iv = [0; IV_LEN];
+    rand::rng().fill_bytes(&mut iv);
+    let payload = Payload {
+        msg: payload,
+        aad: protected.as_bytes(),
+    };
+    let sealed = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key))
+        .encrypt(Nonce::from_slice(&iv), payload)
+        .expect("A256GCM can encrypt any payload that fits in memory");
+    let (ciphertext, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+    format!(
+        "{protected}..{}.{}.{}",
+        base64url::encode(&iv),
+        base64url::encode(ciphertext),
+        base64url::encode(tag)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Jwe, encrypt};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+    use serde_json::json;
+
+    #[test]
+    fn jwe_roundtrips() -> Result<()> {
+        let key = [7; 32];
+        let jwe = encrypt(json!({"kid": "test"}), &key, b"hunter2");
+        assert_eq!(jwe.split('.').nth(1), Some(""));
+        let parsed = Jwe::parse(&jwe)?;
+        assert_eq!(
+            *parsed.header(),
+            json!({"alg": "dir", "enc": "A256GCM", "kid": "test"})
+        );
+        assert_eq!(parsed.decrypt(&key)?, b"hunter2");
+        check_err_result(parsed.decrypt(&[8; 32]), "The JWE could not be decrypted")
+    }
+
+    #[test]
+    fn bad_jwes_error() -> Result<()> {
+        let jwe = encrypt(json!({}), &[7; 32], b"hunter2");
+        let parts: Vec<&str> = jwe.split('.').collect();
+        for bad in [
+            String::new(),
+            parts[..4].join("."),
+            [parts[0], "AAAA", parts[2], parts[3], parts[4]].join("."),
+            [parts[0], "", "AAAA", parts[3], parts[4]].join("."),
+            ["e30", "", parts[2], parts[3], parts[4]].join("."),
+            jwe.replace('.', "!."),
+        ] {
+            check_err_result(Jwe::parse(&bad), "Invalid JWE")?;
+        }
+
+        // The header is authenticated
+        let header = crate::encoding::base64url::encode(br#"{"enc":"A256GCM","alg":"dir"}"#);
+        let forged = [&header, "", parts[2], parts[3], parts[4]].join(".");
+        check_err_result(
+            Jwe::parse(&forged)?.decrypt(&[7; 32]),
+            "The JWE could not be decrypted",
+        )
+    }
+}
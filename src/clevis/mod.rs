@@ -0,0 +1,357 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! [Clevis](https://github.com/latchset/clevis) `sss` pin encryption.
+//!
+//! Clevis encrypts data, such as a disk unlock key, into a compact JWE bound
+//! to a policy of pins.  The `sss` pin encrypts the data with a random key,
+//! splits the key into points with Shamir's Secret Sharing over the integers
+//! modulo a fresh 256-bit prime, and encrypts each point with a nested pin.
+//! Any `t` of the nested pins recover the key.  The protected header of the
+//! JWE holds the policy,
+//!
+//! ```json
+//! {"alg":"dir","enc":"A256GCM","clevis":{"pin":"sss","sss":{"t":2,"p":"...","jwe":["...","..."]}}}
+//! ```
+//!
+//! with the prime `p` in base64url and the nested pins as compact JWEs.
+//!
+//! [`encrypt`] and [`decrypt`] work like `clevis encrypt` and
+//! `clevis decrypt`, and take the same JSON configuration, e.g.
+//! `{"t":2,"pins":{"sss":[{"t":1,"pins":{...}}],"null":[{},{}]}}`.  Only
+//! the local pins are supported: `sss` itself and the `null` pin, which
+//! keeps its key in the clear in its header and so only serves for testing.
+//! The `null` pin is configured with `{}`, or `{"fail":true}` for a pin that
+//! cannot be decrypted.  Pins that need the network or hardware, such as
+//! `tang` and `tpm2`, are not supported.
+//!
+//! # Example
+//! ```
+//! # use anyhow::Result;
+//! # use ssss::clevis::{decrypt, encrypt};
+//! #
+//! # pub fn main() -> Result<()> {
+//! let config = r#"{"t":2,"pins":{"null":[{},{"fail":true},{}]}}"#;
+//! let jwe = encrypt("sss", config, b"disk unlock key")?;
+//! assert_eq!(jwe.split('.').count(), 5);
+//!
+//! assert_eq!(decrypt(&jwe)?, b"disk unlock key");
+//! # Ok(())
+//! # }
+//! ```
+
+mod jwe;
+mod shamir;
+
+use self::{
+    jwe::{Jwe, KEY_LEN},
+    shamir::{Polynomial, is_valid_prime, recover},
+};
+use crate::{
+    encoding::base64url,
+    error::SsssError::{
+        ClevisConfig, ClevisNotEnoughPins, ClevisNullFail, ClevisPin, ClevisThreshold, InvalidJwe,
+    },
+};
+use anyhow::Result;
+use rand::Rng;
+use serde_json::{Value, json};
+
+/// The `sss` pin
+const SSS: &str = "sss";
+/// The `null` pin
+const NULL: &str = "null";
+
+/// Encrypt the data with the given pin and its JSON configuration, like
+/// `clevis encrypt`, into a compact JWE.
+///
+/// # Errors
+/// * This function will generate an error if the pin, or a pin nested in an
+///   `sss` configuration, is not `sss` or `null`.
+/// * This function will generate an error if the configuration is not valid
+///   JSON for the pin, or an `sss` threshold is 0 or larger than the number
+///   of its pins.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::clevis::{decrypt, encrypt};
+/// #
+/// # pub fn main() -> Result<()> {
+/// // Either the first pin, or both of the nested pins
+/// let config = r#"{"t":1,"pins":{"null":{},"sss":{"t":2,"pins":{"null":[{},{}]}}}}"#;
+/// let jwe = encrypt("sss", config, b"hunter2")?;
+/// assert_eq!(decrypt(&jwe)?, b"hunter2");
+/// # Ok(())
+/// # }
+/// ```
+pub fn encrypt(pin: &str, config: &str, data: &[u8]) -> Result<String> {
+    let config: Value = serde_json::from_str(config).map_err(|_| ClevisConfig)?;
+    encrypt_pin(pin, &config, data)
+}
+
+/// Decrypt a compact JWE made by [`encrypt`] or `clevis encrypt`, like
+/// `clevis decrypt`.  The nested pins of an `sss` pin are tried in order
+/// until enough are decrypted.
+///
+/// # Errors
+/// * This function will generate an error if the JWE cannot be read, was not
+///   encrypted with `dir` and `A256GCM`, or was not encrypted with an `sss`
+///   or `null` pin.
+/// * This function will generate an error if fewer nested pins than the
+///   threshold can be decrypted, or the data cannot be decrypted.
+pub fn decrypt(jwe: &str) -> Result<Vec<u8>> {
+    let jwe = Jwe::parse(jwe)?;
+    let clevis = &jwe.header()["clevis"];
+    match clevis["pin"].as_str() {
+        Some(SSS) => {
+            let sss = &clevis[SSS];
+            let threshold = sss["t"]
+                .as_u64()
+                .and_then(|t| usize::try_from(t).ok())
+                .ok_or(InvalidJwe)?;
+            let prime = sss["p"]
+                .as_str()
+                .and_then(|p| base64url::decode(p).ok())
+                .filter(|p| is_valid_prime(p))
+                .ok_or(InvalidJwe)?;
+            let pins = sss["jwe"].as_array().ok_or(InvalidJwe)?;
+            let points: Vec<Vec<u8>> = pins
+                .iter()
+                .filter_map(|pin| pin.as_str().and_then(|pin| decrypt(pin).ok()))
+                .take(threshold)
+                .collect();
+            if threshold == 0 || points.len() < threshold {
+                return Err(ClevisNotEnoughPins {
+                    threshold,
+                    found: points.len(),
+                }
+                .into());
+            }
+            jwe.decrypt(&recover(&prime, &points)?)
+        }
+        Some(NULL) => {
+            let null = &clevis[NULL];
+            if null["fail"] == true {
+                return Err(ClevisNullFail.into());
+            }
+            let key = null["jwk"]["k"]
+                .as_str()
+                .and_then(|k| base64url::decode(k).ok())
+                .ok_or(InvalidJwe)?;
+            jwe.decrypt(&key)
+        }
+        Some(pin) => Err(ClevisPin {
+            pin: pin.to_string(),
+        }
+        .into()),
+        None => Err(InvalidJwe.into()),
+    }
+}
+
+fn encrypt_pin(pin: &str, config: &Value, data: &[u8]) -> Result<String> {
+    match pin {
+        SSS => encrypt_sss(config, data),
+        NULL => encrypt_null(config, data),
+        _ => Err(ClevisPin {
+            pin: pin.to_string(),
+        }
+        .into()),
+    }
+}
+
+/// Encrypt with the `sss` pin, configured by `{"t":t,"pins":{pin:[config]}}`.
+/// A pin may be given a single configuration rather than a list, and `t`
+/// defaults to 1.
+fn encrypt_sss(config: &Value, data: &[u8]) -> Result<String> {
+    let threshold = match config.get("t") {
+        None => 1,
+        Some(t) => t.as_u64().ok_or(ClevisConfig)?,
+    };
+    let mut pins: Vec<(&str, &Value)> = Vec::new();
+    for (pin, configs) in config
+        .get("pins")
+        .and_then(Value::as_object)
+        .ok_or(ClevisConfig)?
+    {
+        match configs {
+            Value::Array(configs) => {
+                pins.extend(configs.iter().map(|config| (pin.as_str(), config)));
+            }
+            Value::Object(_) => pins.push((pin, configs)),
+            _ => return Err(ClevisConfig.into()),
+        }
+    }
+    let threshold = usize::try_from(threshold)
+        .ok()
+        .filter(|t| (1..=pins.len()).contains(t))
+        .ok_or(ClevisThreshold {
+            threshold,
+            pins: pins.len(),
+        })?;
+
+    let polynomial = Polynomial::random(threshold);
+    let jwes = pins
+        .iter()
+        .map(|(pin, config)| encrypt_pin(pin, config, &polynomial.point()))
+        .collect::<Result<Vec<String>>>()?;
+    let header = json!({
+        "clevis": {
+            "pin": SSS,
+            "sss": {
+                "t": threshold,
+                "p": base64url::encode(&polynomial.prime()),
+                "jwe": jwes,
+            },
+        },
+    });
+    Ok(jwe::encrypt(header, &polynomial.key(), data))
+}
+
+/// Encrypt with the `null` pin, configured by `{}` or `{"fail":true}`
+fn encrypt_null(config: &Value, data: &[u8]) -> Result<String> {
+    let fail = match config.get("fail") {
+        None => false,
+        Some(fail) => fail.as_bool().ok_or(ClevisConfig)?,
+    };
+    if !config.is_object() {
+        return Err(ClevisConfig.into());
+    }
+    let mut key = [0; KEY_LEN];
+    rand::rng().fill_bytes(&mut key);
+    let mut settings = json!({
+        "jwk": {"alg": "A256GCM", "k": base64url::encode(&key), "kty": "oct"},
+    });
+    if fail {
+        settings["fail"] = true.into();
+    }
+    let header = json!({"clevis": {"pin": NULL, "null": settings}});
+    Ok(jwe::encrypt(header, &key, data))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decrypt, encrypt, jwe::Jwe};
+    use crate::{encoding::base64url, utils::check_err_result};
+    use anyhow::Result;
+
+    fn header(jwe: &str) -> Result<serde_json::Value> {
+        Ok(Jwe::parse(jwe)?.header().clone())
+    }
+
+    #[test]
+    fn null_pin_roundtrips() -> Result<()> {
+        let jwe = encrypt("null", "{}", b"hunter2")?;
+        let header = header(&jwe)?;
+        assert_eq!(header["clevis"]["pin"], "null");
+        assert_eq!(header["clevis"]["null"]["jwk"]["kty"], "oct");
+        assert_eq!(decrypt(&jwe)?, b"hunter2");
+
+        let jwe = encrypt("null", r#"{"fail":true}"#, b"hunter2")?;
+        check_err_result(decrypt(&jwe), "The null pin is configured to fail")
+    }
+
+    #[test]
+    fn sss_pin_roundtrips() -> Result<()> {
+        let config = r#"{"t":2,"pins":{"null":[{},{"fail":true},{}]}}"#;
+        let jwe = encrypt("sss", config, b"correct horse battery staple")?;
+        let header = header(&jwe)?;
+        let sss = &header["clevis"]["sss"];
+        assert_eq!(header["clevis"]["pin"], "sss");
+        assert_eq!(sss["t"], 2);
+        assert!(sss.get("e").is_none());
+        let prime = base64url::decode(sss["p"].as_str().unwrap_or_default())?;
+        assert_eq!(prime.len(), 32);
+        assert!(prime[0] >= 0x80);
+        assert_eq!(sss["jwe"].as_array().map(Vec::len), Some(3));
+        assert_eq!(decrypt(&jwe)?, b"correct horse battery staple");
+
+        let config = r#"{"t":3,"pins":{"null":[{},{"fail":true},{}]}}"#;
+        let jwe = encrypt("sss", config, b"correct horse battery staple")?;
+        check_err_result(
+            decrypt(&jwe),
+            "3 pins are required, but only 2 could be decrypted",
+        )
+    }
+
+    #[test]
+    fn nested_sss_pins_roundtrip() -> Result<()> {
+        let inner_ok = r#"{"t":2,"pins":{"null":[{},{},{"fail":true}]}}"#;
+        let inner_fail = r#"{"t":2,"pins":{"null":[{"fail":true},{},{"fail":true}]}}"#;
+        let config = format!(r#"{{"t":2,"pins":{{"sss":[{inner_ok},{inner_fail}],"null":{{}}}}}}"#);
+        let jwe = encrypt("sss", &config, b"nested")?;
+        assert_eq!(decrypt(&jwe)?, b"nested");
+
+        let config = format!(r#"{{"pins":{{"sss":[{inner_fail},{inner_ok}]}}}}"#);
+        let jwe = encrypt("sss", &config, b"nested")?;
+        assert_eq!(header(&jwe)?["clevis"]["sss"]["t"], 1);
+        assert_eq!(decrypt(&jwe)?, b"nested");
+        Ok(())
+    }
+
+    #[test]
+    fn bad_configs_error() -> Result<()> {
+        check_err_result(
+            encrypt("tang", r#"{"url":"http://tang.local"}"#, b"data"),
+            "The Clevis pin 'tang' is not supported",
+        )?;
+        check_err_result(
+            encrypt("sss", r#"{"t":1,"pins":{"tpm2":{}}}"#, b"data"),
+            "The Clevis pin 'tpm2' is not supported",
+        )?;
+        for config in [
+            "not json",
+            "[]",
+            r#"{"t":1}"#,
+            r#"{"t":"1","pins":{"null":{}}}"#,
+            r#"{"t":1,"pins":{"null":true}}"#,
+            r#"{"t":1,"pins":{"null":{"fail":"yes"}}}"#,
+            r#"{"t":1,"pins":{"null":[[]]}}"#,
+        ] {
+            check_err_result(
+                encrypt("sss", config, b"data"),
+                "Invalid Clevis pin configuration",
+            )?;
+        }
+        check_err_result(
+            encrypt("sss", r#"{"t":0,"pins":{"null":{}}}"#, b"data"),
+            "The threshold 0 must be from 1 to the number of pins (1)",
+        )?;
+        check_err_result(
+            encrypt("sss", r#"{"t":3,"pins":{"null":[{},{}]}}"#, b"data"),
+            "The threshold 3 must be from 1 to the number of pins (2)",
+        )
+    }
+
+    #[test]
+    fn bad_jwes_error() -> Result<()> {
+        check_err_result(decrypt("not a jwe"), "Invalid JWE")?;
+        let jwe = encrypt("sss", r#"{"pins":{"null":{}}}"#, b"data")?;
+        let mut parts: Vec<String> = jwe.split('.').map(ToString::to_string).collect();
+        parts[3] = base64url::encode(b"forged");
+        check_err_result(decrypt(&parts.join(".")), "The JWE could not be decrypted")?;
+
+        let jwe = encrypt("sss", r#"{"t":2,"pins":{"null":[{},{}]}}"#, b"data")?;
+        let mut forged: Vec<String> = jwe.split('.').map(ToString::to_string).collect();
+        let mut two = [0; 32];
+        two[31] = 2;
+        for prime in [&[][..], &[1], &[2], &[0; 32], &two, &[0xff; 33]] {
+            let mut header = header(&jwe)?;
+            header["clevis"]["sss"]["p"] = base64url::encode(prime).into();
+            forged[0] = base64url::encode(header.to_string().as_bytes());
+            check_err_result(decrypt(&forged.join(".")), "Invalid JWE")?;
+        }
+
+        let header = base64url::encode(br#"{"alg":"dir","enc":"A256GCM","clevis":{"pin":"tang"}}"#);
+        parts[0] = header;
+        check_err_result(
+            decrypt(&parts.join(".")),
+            "The Clevis pin 'tang' is not supported",
+        )
+    }
+}
@@ -0,0 +1,246 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` Clevis secret sharing over GF(p)
+//!
+//! Like Clevis, the key is the constant term of a random polynomial over the
+//! integers modulo a fresh 256-bit prime `p`, and each pin is given a point
+//! `x || y`, both as big-endian numbers as long as `p`.
+
+use super::jwe::KEY_LEN;
+use crate::error::SsssError::{DuplicateShareIndex, InvalidJwe};
+use anyhow::Result;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use rand::Rng;
+
+/// The primes trial division is done with, before Miller-Rabin
+const SMALL_PRIMES: [u32; 15] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47];
+/// The number of Miller-Rabin rounds, for an error below 2^-128
+const ROUNDS: usize = 64;
+
+/// A random polynomial over GF(p), whose constant term is the key
+#[derive(Clone, Debug)]
+pub(crate) struct Polynomial {
+    prime: BigUint,
+    coeffs: Vec<BigUint>,
+}
+
+impl Polynomial {
+    /// A random polynomial with `threshold` coefficients, over a random prime
+    /// of [`KEY_LEN`] bytes
+    pub(crate) fn random(threshold: usize) -> Self {
+        let mut rng = rand::rng();
+        let prime = random_prime(&mut rng);
+        let coeffs = (0..threshold)
+            .map(|_| random_below(&mut rng, &prime))
+            .collect();
+        Polynomial { prime, coeffs }
+    }
+
+    /// The prime, as [`KEY_LEN`] big-endian bytes
+    pub(crate) fn prime(&self) -> Vec<u8> {
+        to_bytes(&self.prime, KEY_LEN)
+    }
+
+    /// The key, the constant term
+    pub(crate) fn key(&self) -> [u8; KEY_LEN] {
+        let mut key = [0; KEY_LEN];
+        key.copy_from_slice(&to_bytes(&self.coeffs[0], KEY_LEN));
+        key
+    }
+
+    /// A point at a random non-zero `x`, as `x || y`
+    pub(crate) fn point(&self) -> Vec<u8> {
+        let mut rng = rand::rng();
+        let x = loop {
+            let x = random_below(&mut rng, &self.prime);
+            if !x.is_zero() {
+                break x;
+            }
+        };
+        let y = self
+            .coeffs
+            .iter()
+            .rev()
+            .fold(BigUint::zero(), |y, coeff| (y * &x + coeff) % &self.prime);
+        let mut point = to_bytes(&x, KEY_LEN);
+        point.extend(to_bytes(&y, KEY_LEN));
+        point
+    }
+}
+
+/// Whether the bytes can be the prime of a JWE, [`KEY_LEN`] big-endian
+/// bytes of at least 3.  The prime comes from the untrusted JWE header, and
+/// [`recover`] needs `p - 2` for its inverses.
+pub(crate) fn is_valid_prime(prime: &[u8]) -> bool {
+    prime.len() == KEY_LEN && BigUint::from_bytes_be(prime) >= BigUint::from(3_u32)
+}
+
+/// Recover the key from points `x || y` over GF(p), with `p` given as
+/// big-endian bytes
+pub(crate) fn recover(prime: &[u8], points: &[Vec<u8>]) -> Result<Vec<u8>> {
+    let len = prime.len();
+    let prime = BigUint::from_bytes_be(prime);
+    let points = points
+        .iter()
+        .map(|point| {
+            if point.len() == 2 * len {
+                let (x, y) = point.split_at(len);
+                Ok((BigUint::from_bytes_be(x), BigUint::from_bytes_be(y)))
+            } else {
+                Err(InvalidJwe.into())
+            }
+        })
+        .collect::<Result<Vec<(BigUint, BigUint)>>>()?;
+
+    let mut key = BigUint::zero();
+    for (i, (x_i, y_i)) in points.iter().enumerate() {
+        let mut numerator = BigUint::one();
+        let mut denominator = BigUint::one();
+        for (j, (x_j, _)) in points.iter().enumerate() {
+            if i != j {
+                numerator = numerator * (&prime - x_j % &prime) % &prime;
+                denominator = denominator * ((x_i + &prime - x_j % &prime) % &prime) % &prime;
+            }
+        }
+        if denominator.is_zero() {
+            return Err(DuplicateShareIndex.into());
+        }
+        // p is prime, so the inverse is `denominator^(p - 2)`
+        let inverse = denominator.modpow(&(&prime - 2_u32), &prime);
+        key = (key + y_i * numerator % &prime * inverse) % &prime;
+    }
+    Ok(to_bytes(&key, len))
+}
+
+/// The number as big-endian bytes, padded on the left to `len`
+fn to_bytes(n: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = n.to_bytes_be();
+    let mut padded = vec![0; len.saturating_sub(bytes.len())];
+    padded.extend(bytes);
+    padded
+}
+
+/// A uniformly random number below the bound
+fn random_below<R: Rng + ?Sized>(rng: &mut R, bound: &BigUint) -> BigUint {
+    let bits = bound.bits();
+    let mut bytes = vec![0; usize::try_from(bits.div_ceil(8)).unwrap_or(KEY_LEN)];
+    loop {
+        rng.fill_bytes(&mut bytes);
+        let n = BigUint::from_bytes_be(&bytes) >> (8 * bytes.len() as u64 - bits);
+        if n < *bound {
+            return n;
+        }
+    }
+}
+
+/// A random prime of exactly [`KEY_LEN`] bytes
+fn random_prime<R: Rng + ?Sized>(rng: &mut R) -> BigUint {
+    let mut bytes = [0; KEY_LEN];
+    loop {
+        rng.fill_bytes(&mut bytes);
+        bytes[0] |= 0x80;
+        bytes[KEY_LEN - 1] |= 1;
+        let candidate = BigUint::from_bytes_be(&bytes);
+        if is_probable_prime(rng, &candidate) {
+            return candidate;
+        }
+    }
+}
+
+/// Whether the number is prime, by trial division and Miller-Rabin
+fn is_probable_prime<R: Rng + ?Sized>(rng: &mut R, n: &BigUint) -> bool {
+    for prime in SMALL_PRIMES {
+        if *n == BigUint::from(prime) {
+            return true;
+        } else if (n % prime).is_zero() {
+            return false;
+        }
+    }
+    if *n < BigUint::from(2_u32) {
+        return false;
+    }
+
+    let n_minus_one = n - 1_u32;
+    let shift = n_minus_one.trailing_zeros().unwrap_or(0);
+    let odd = &n_minus_one >> shift;
+    let below = n - 3_u32;
+    'witness: for _ in 0..ROUNDS {
+        let witness = random_below(rng, &below) + 2_u32;
+        let mut x = witness.modpow(&odd, n);
+        if x.is_one() || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..shift {
+            x = x.modpow(&BigUint::from(2_u32), n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use super::{KEY_LEN, Polynomial, is_probable_prime, random_prime, recover};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+    use num_bigint::BigUint;
+    use num_traits::One;
+
+    #[test]
+    fn primes_are_found() {
+        let mut rng = rand::rng();
+        let mersenne = (BigUint::one() << 127_u32) - 1_u32;
+        let curve25519 = (BigUint::one() << 255_u32) - 19_u32;
+        for prime in [
+            2_u32.into(),
+            47_u32.into(),
+            7919_u32.into(),
+            mersenne,
+            curve25519,
+        ] {
+            assert!(is_probable_prime(&mut rng, &prime), "{prime}");
+        }
+        // 561 is a Carmichael number, and 2^128 + 1 has the factor 59649589127497217
+        let fermat = (BigUint::one() << 128_u32) + 1_u32;
+        for composite in [
+            0_u32.into(),
+            1_u32.into(),
+            561_u32.into(),
+            7917_u32.into(),
+            fermat,
+        ] {
+            assert!(!is_probable_prime(&mut rng, &composite), "{composite}");
+        }
+
+        let prime = random_prime(&mut rng);
+        assert_eq!(prime.bits(), 8 * KEY_LEN as u64);
+        assert!(is_probable_prime(&mut rng, &prime));
+    }
+
+    #[test]
+    fn points_recover_the_key() -> Result<()> {
+        let polynomial = Polynomial::random(3);
+        let points: Vec<Vec<u8>> = (0..5).map(|_| polynomial.point()).collect();
+        assert!(points.iter().all(|point| point.len() == 2 * KEY_LEN));
+        let prime = polynomial.prime();
+        assert_eq!(recover(&prime, &points[..3])?, polynomial.key());
+        assert_eq!(recover(&prime, &points[2..])?, polynomial.key());
+        assert_ne!(recover(&prime, &points[..2])?, polynomial.key());
+
+        check_err_result(
+            recover(&prime, &[points[0].clone(), points[0].clone()]),
+            "A share index is used more than once",
+        )?;
+        check_err_result(recover(&prime, &[points[0][1..].to_vec()]), "Invalid JWE")
+    }
+}
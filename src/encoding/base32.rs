@@ -0,0 +1,124 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` Crockford base32 share encoding
+
+use super::{ShareEncoding, decode_tagged, encode_tagged, position, read_tagged};
+use crate::error::SsssError::InvalidShareFormat;
+use anyhow::Result;
+
+pub(crate) const TAG: &str = "b32";
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+/// [Crockford base32](https://www.crockford.com/base32.html) shares, e.g. `b32:0C:KWP...:9ZD...`.
+///
+/// Decoding is case-insensitive, treats `O` as `0` and `I` and `L` as `1`, and
+/// ignores `-`, so shares can be read aloud and written down in groups.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Base32;
+
+impl ShareEncoding for Base32 {
+    fn encode(&self, index: u8, share: &[u8]) -> String {
+        encode_tagged(TAG, index, share, normalize, encode)
+    }
+
+    fn decode(&self, share: &str) -> Result<(u8, Vec<u8>)> {
+        decode_tagged(TAG, share, normalize, decode)
+    }
+}
+
+/// Read a damaged share, see [`read_tagged`]
+pub(crate) fn read_damaged(share: &str) -> Option<Vec<Option<u8>>> {
+    read_tagged(share, normalize, |c| position(TAG, ALPHABET, c).ok(), 5)
+}
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity((bytes.len() * 8).div_ceil(5));
+    let mut acc = 0u16;
+    let mut bits = 0;
+    for byte in bytes {
+        acc = acc << 8 | u16::from(*byte);
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            result.push(char::from(ALPHABET[usize::from((acc >> bits) & 0x1f)]));
+        }
+    }
+    if bits > 0 {
+        result.push(char::from(
+            ALPHABET[usize::from((acc << (5 - bits)) & 0x1f)],
+        ));
+    }
+    result
+}
+
+#[allow(clippy::cast_possible_truncation)]
+pub(crate) fn decode(input: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(input.len() * 5 / 8);
+    let mut acc = 0u16;
+    let mut bits = 0;
+    for c in input.chars().filter_map(normalize) {
+        acc = acc << 5 | u16::from(position(TAG, ALPHABET, c)?);
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push(((acc >> bits) & 0xff) as u8);
+        }
+    }
+    // Any trailing bits are padding, and must be a partial (zero) byte
+    if bits >= 5 || acc & ((1 << bits) - 1) != 0 {
+        Err(InvalidShareFormat.into())
+    } else {
+        Ok(bytes)
+    }
+}
+
+/// Crockford base32 is case-insensitive, treats `O` as `0` and `I` and `L`
+/// as `1`, and ignores `-`
+fn normalize(c: char) -> Option<char> {
+    match c.to_ascii_uppercase() {
+        '-' => None,
+        'O' => Some('0'),
+        'I' | 'L' => Some('1'),
+        c => Some(c),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+
+    #[test]
+    fn encode_works() {
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "CR");
+        assert_eq!(encode(b"foobar"), "CSQPYRK1E8");
+        assert_eq!(encode(&[0xff; 5]), "ZZZZZZZZ");
+    }
+
+    #[test]
+    fn decode_works() -> Result<()> {
+        assert_eq!(decode("csqpyrkle8")?, b"foobar");
+        assert_eq!(decode("CSQP-YRK1-E8")?, b"foobar");
+        assert_eq!(decode("0o")?, [0]);
+        Ok(())
+    }
+
+    #[test]
+    fn decode_bad_padding_errors() -> Result<()> {
+        check_err_result(decode("CS"), "Invalid share format")?;
+        check_err_result(decode("CRZ"), "Invalid share format")
+    }
+
+    #[test]
+    fn decode_bad_character_errors() -> Result<()> {
+        check_err_result(decode("CU"), "Invalid b32 character: U")
+    }
+}
@@ -0,0 +1,122 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` base64url share encoding
+
+use super::{ShareEncoding, decode_tagged, encode_tagged, position, read_tagged};
+use crate::error::SsssError::InvalidShareFormat;
+use anyhow::Result;
+
+pub(crate) const TAG: &str = "b64";
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Unpadded base64url ([RFC 4648 §5](https://www.rfc-editor.org/rfc/rfc4648#section-5))
+/// shares, e.g. `b64:Aw:nyw...:Kx0...`.
+///
+/// Padding is accepted, but not required, when decoding.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Base64Url;
+
+impl ShareEncoding for Base64Url {
+    fn encode(&self, index: u8, share: &[u8]) -> String {
+        encode_tagged(TAG, index, share, normalize, encode)
+    }
+
+    fn decode(&self, share: &str) -> Result<(u8, Vec<u8>)> {
+        decode_tagged(TAG, share, normalize, decode)
+    }
+}
+
+/// Read a damaged share, see [`read_tagged`]
+pub(crate) fn read_damaged(share: &str) -> Option<Vec<Option<u8>>> {
+    read_tagged(share, normalize, |c| position(TAG, ALPHABET, c).ok(), 6)
+}
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let mut buf = [0u8; 3];
+        buf[..chunk.len()].copy_from_slice(chunk);
+        let val = u32::from_be_bytes([0, buf[0], buf[1], buf[2]]);
+        for i in 0..=chunk.len() {
+            let sextet = (val >> (18 - 6 * i)) & 0x3f;
+            result.push(char::from(ALPHABET[sextet as usize]));
+        }
+    }
+    result
+}
+
+pub(crate) fn decode(input: &str) -> Result<Vec<u8>> {
+    let sextets = input
+        .trim_end_matches('=')
+        .chars()
+        .map(|c| position(TAG, ALPHABET, c))
+        .collect::<Result<Vec<u8>>>()?;
+
+    let mut bytes = Vec::with_capacity(sextets.len() * 3 / 4);
+    for chunk in sextets.chunks(4) {
+        if chunk.len() == 1 {
+            return Err(InvalidShareFormat.into());
+        }
+        let val = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |acc, (i, s)| acc | u32::from(*s) << (18 - 6 * i));
+        let len = chunk.len() - 1;
+        let [_, b0, b1, b2] = val.to_be_bytes();
+        if len < 3 && [b0, b1, b2][len] != 0 {
+            // The unused bits of a trailing chunk must be zero
+            return Err(InvalidShareFormat.into());
+        }
+        bytes.extend_from_slice(&[b0, b1, b2][..len]);
+    }
+    Ok(bytes)
+}
+
+#[allow(clippy::unnecessary_wraps)]
+fn normalize(c: char) -> Option<char> {
+    Some(c)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+
+    #[test]
+    fn encode_works() {
+        // RFC 4648 test vectors, unpadded
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "Zg");
+        assert_eq!(encode(b"fo"), "Zm8");
+        assert_eq!(encode(b"foo"), "Zm9v");
+        assert_eq!(encode(b"foob"), "Zm9vYg");
+        assert_eq!(encode(b"fooba"), "Zm9vYmE");
+        assert_eq!(encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(encode(&[0xfb, 0xff]), "-_8");
+    }
+
+    #[test]
+    fn decode_works() -> Result<()> {
+        assert_eq!(decode("Zm9vYmE")?, b"fooba");
+        assert_eq!(decode("Zm9vYg==")?, b"foob");
+        assert_eq!(decode("-_8")?, [0xfb, 0xff]);
+        Ok(())
+    }
+
+    #[test]
+    fn decode_bad_length_errors() -> Result<()> {
+        check_err_result(decode("Zm9vY"), "Invalid share format")
+    }
+
+    #[test]
+    fn decode_bad_character_errors() -> Result<()> {
+        check_err_result(decode("Zm9+"), "Invalid b64 character: +")
+    }
+}
@@ -0,0 +1,319 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` bech32m share encoding
+
+use super::{ShareEncoding, decode_index, position, read_bits};
+use crate::error::SsssError::{ChecksumError, HrpMismatch, InvalidChecksum, InvalidShareFormat};
+use anyhow::Result;
+
+const NAME: &str = "bech32m";
+const DEFAULT_HRP: &str = "ssss";
+const SEPARATOR: char = '1';
+const CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const CHECKSUM_LEN: usize = 6;
+const BECH32M_CONST: u32 = 0x2bc8_30a3;
+const GENERATOR: [u32; 5] = [
+    0x3b6a_57b2,
+    0x2650_8e6d,
+    0x1ea1_19fa,
+    0x3d42_33dd,
+    0x2a14_62b3,
+];
+
+/// [Bech32m](https://github.com/bitcoin/bips/blob/master/bip-0350.mediawiki) shares,
+/// e.g. `ssss1qv...`.
+///
+/// The share index and the share bytes are encoded together after a
+/// human-readable prefix, which defaults to `ssss`.  Shares are lowercase when
+/// encoded, but may be entirely uppercase when decoded.
+#[derive(Clone, Debug)]
+pub struct Bech32m {
+    hrp: String,
+}
+
+impl Default for Bech32m {
+    fn default() -> Self {
+        Self::new(DEFAULT_HRP)
+    }
+}
+
+impl Bech32m {
+    /// Create a bech32m encoding with the given human-readable prefix
+    #[must_use]
+    pub fn new(hrp: &str) -> Self {
+        Self {
+            hrp: hrp.to_ascii_lowercase(),
+        }
+    }
+}
+
+impl ShareEncoding for Bech32m {
+    fn encode(&self, index: u8, share: &[u8]) -> String {
+        let mut bytes = vec![index];
+        bytes.extend_from_slice(share);
+        encode(&self.hrp, &bytes)
+    }
+
+    fn decode(&self, share: &str) -> Result<(u8, Vec<u8>)> {
+        let (hrp, bytes) = decode(share)?;
+        if hrp != self.hrp {
+            Err(HrpMismatch {
+                expected: self.hrp.clone(),
+                found: hrp,
+            }
+            .into())
+        } else if let Some((index, share)) = bytes.split_first() {
+            Ok((decode_index(&[*index])?, share.to_vec()))
+        } else {
+            Err(InvalidShareFormat.into())
+        }
+    }
+}
+
+/// Split the human-readable prefix from a bech32m string
+pub(crate) fn hrp(input: &str) -> Result<String> {
+    input
+        .rsplit_once(SEPARATOR)
+        .filter(|(hrp, _)| !hrp.is_empty())
+        .map(|(hrp, _)| hrp.to_ascii_lowercase())
+        .ok_or_else(|| InvalidShareFormat.into())
+}
+
+pub(crate) fn encode(hrp: &str, bytes: &[u8]) -> String {
+    let data = convert_bits(bytes, 8, 5, true).unwrap_or_default();
+    let checksum = checksum(hrp, &data);
+    let mut result = format!("{hrp}{SEPARATOR}");
+    result.extend(
+        data.iter()
+            .chain(checksum.iter())
+            .map(|d| char::from(CHARSET[usize::from(*d)])),
+    );
+    result
+}
+
+pub(crate) fn decode(input: &str) -> Result<(String, Vec<u8>)> {
+    if input.chars().any(|c| c.is_ascii_lowercase())
+        && input.chars().any(|c| c.is_ascii_uppercase())
+    {
+        return Err(InvalidShareFormat.into());
+    }
+    let input = input.to_ascii_lowercase();
+    let hrp = hrp(&input)?;
+    let data = input[hrp.len() + 1..]
+        .chars()
+        .map(|c| position(NAME, CHARSET, c))
+        .collect::<Result<Vec<u8>>>()?;
+
+    let residue = polymod(&expand_hrp(&hrp), &data) ^ BECH32M_CONST;
+    if data.len() < CHECKSUM_LEN {
+        Err(InvalidShareFormat.into())
+    } else if residue != 0 {
+        match locate(residue, data.len()) {
+            Some(idx) => Err(ChecksumError {
+                position: hrp.len() + idx + 2,
+            }
+            .into()),
+            None => Err(InvalidChecksum.into()),
+        }
+    } else {
+        let bytes = convert_bits(&data[..data.len() - CHECKSUM_LEN], 5, 8, false)?;
+        Ok((hrp, bytes))
+    }
+}
+
+/// Read the bytes of a damaged share, ignoring its checksum
+pub(crate) fn read_damaged(share: &str) -> Option<Vec<Option<u8>>> {
+    let (_, data) = share.rsplit_once(SEPARATOR)?;
+    let data = data.get(..data.len().checked_sub(CHECKSUM_LEN)?)?;
+    Some(read_bits(
+        data.chars()
+            .map(|c| position(NAME, CHARSET, c.to_ascii_lowercase()).ok()),
+        5,
+    ))
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn checksum(hrp: &str, data: &[u8]) -> [u8; CHECKSUM_LEN] {
+    let mut values = data.to_vec();
+    values.extend_from_slice(&[0; CHECKSUM_LEN]);
+    let polymod = polymod(&expand_hrp(hrp), &values) ^ BECH32M_CONST;
+    let mut checksum = [0; CHECKSUM_LEN];
+    for (i, c) in checksum.iter_mut().enumerate() {
+        *c = ((polymod >> (5 * (5 - i))) & 0x1f) as u8;
+    }
+    checksum
+}
+
+fn polymod(hrp: &[u8], data: &[u8]) -> u32 {
+    hrp.iter()
+        .chain(data)
+        .fold(1, |chk, value| step(chk, *value))
+}
+
+/// Multiply the checksum polynomial by `x`, add `value`, and reduce
+fn step(chk: u32, value: u8) -> u32 {
+    let top = chk >> 25;
+    let mut chk = ((chk & 0x01ff_ffff) << 5) ^ u32::from(value);
+    for (i, generator) in GENERATOR.iter().enumerate() {
+        if (top >> i) & 1 == 1 {
+            chk ^= generator;
+        }
+    }
+    chk
+}
+
+/// Find the index of a single substituted data character from the residue
+/// of a failed checksum.
+///
+/// The checksum is linear over GF(32), so an error `e` in the character `k`
+/// places from the end leaves a residue of `e * x^k mod g(x)`.
+fn locate(residue: u32, data_len: usize) -> Option<usize> {
+    let mut power = 1;
+    for k in 0..data_len {
+        if (1..32).any(|e| scale(power, e) == residue) {
+            return Some(data_len - 1 - k);
+        }
+        power = step(power, 0);
+    }
+    None
+}
+
+/// Multiply each GF(32) coefficient of a checksum polynomial by `e`
+fn scale(poly: u32, e: u8) -> u32 {
+    (0..CHECKSUM_LEN).fold(0, |acc, i| {
+        let coeff = ((poly >> (5 * i)) & 0x1f) as u8;
+        acc | u32::from(gf32_mul(coeff, e)) << (5 * i)
+    })
+}
+
+/// Multiply in GF(32), with the field polynomial `x^5 + x^3 + 1`
+fn gf32_mul(a: u8, b: u8) -> u8 {
+    let mut result = 0;
+    let mut a = a;
+    for i in 0..5 {
+        if (b >> i) & 1 == 1 {
+            result ^= a;
+        }
+        a <<= 1;
+        if a & 0x20 != 0 {
+            a ^= 0x29;
+        }
+    }
+    result
+}
+
+fn expand_hrp(hrp: &str) -> Vec<u8> {
+    let mut expanded: Vec<u8> = hrp.bytes().map(|b| b >> 5).collect();
+    expanded.push(0);
+    expanded.extend(hrp.bytes().map(|b| b & 0x1f));
+    expanded
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Result<Vec<u8>> {
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let max = (1 << to) - 1;
+    let mut result = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for value in data {
+        acc = (acc << from) | u32::from(*value);
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            result.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            result.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return Err(InvalidShareFormat.into());
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BECH32M_CONST, CHARSET, decode, encode, expand_hrp, hrp, polymod, position};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+
+    fn verify(input: &str) -> Result<bool> {
+        let input = input.to_ascii_lowercase();
+        let hrp = hrp(&input)?;
+        let data = input[hrp.len() + 1..]
+            .chars()
+            .map(|c| position("bech32m", CHARSET, c))
+            .collect::<Result<Vec<u8>>>()?;
+        Ok(polymod(&expand_hrp(&hrp), &data) == BECH32M_CONST)
+    }
+
+    #[test]
+    fn bip350_valid_checksums() -> Result<()> {
+        for valid in [
+            "A1LQFN3A",
+            "a1lqfn3a",
+            "abcdef1l7aum6echk45nj3s0wdvt2fg8x9yrzpqzd3ryx",
+            "split1checkupstagehandshakeupstreamerranterredcaperredlc445v",
+        ] {
+            assert!(verify(valid)?, "{valid}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn encode_decode_roundtrips() -> Result<()> {
+        let encoded = encode("ssss", b"correct horse battery staple");
+        assert!(encoded.starts_with("ssss1"));
+        assert_eq!(
+            decode(&encoded)?,
+            ("ssss".to_string(), b"correct horse battery staple".to_vec())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn decode_bad_checksum_errors() -> Result<()> {
+        let mut encoded = encode("ssss", b"abc");
+        let last = if encoded.ends_with('q') { 'p' } else { 'q' };
+        let _ = encoded.pop();
+        encoded.push(last);
+        check_err_result(
+            decode(&encoded),
+            &format!(
+                "Invalid share checksum, check the share near character {}",
+                encoded.len()
+            ),
+        )
+    }
+
+    #[test]
+    fn decode_locates_substitution() -> Result<()> {
+        let encoded = encode("ssss", b"correct horse battery staple");
+        for i in 5..encoded.len() {
+            let replacement = if &encoded[i..=i] == "q" { "p" } else { "q" };
+            let mut mistyped = encoded.clone();
+            mistyped.replace_range(i..=i, replacement);
+            check_err_result(
+                decode(&mistyped),
+                &format!(
+                    "Invalid share checksum, check the share near character {}",
+                    i + 1
+                ),
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn decode_mixed_case_errors() -> Result<()> {
+        check_err_result(decode("A1lqfn3a"), "Invalid share format")
+    }
+}
@@ -0,0 +1,337 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` share checksums
+//!
+//! The checksum is a Reed–Solomon code over GF(2^16), a BCH code like those
+//! of bech32 and of the SLIP-0039 RS1024 checksum.  Each character of a share
+//! is a symbol of the code, and the checksum is four symbols, the remainder
+//! of the share divided by the generator polynomial
+//! `(x - a)(x - a^2)(x - a^3)(x - a^4)`, where `a` is the generator of the
+//! field.
+//!
+//! The code has a distance of 5, so any error affecting at most four
+//! characters is detected, which includes every swap of two characters.  A
+//! single substituted character, or a swap of two adjacent characters, is
+//! also located from the syndromes of the share.  This holds for up to
+//! [`MAX_SYMBOLS`] checksummed characters, the length of the code, and a
+//! longer share is refused when it is verified.
+
+use crate::{
+    error::SsssError::{ChecksumError, ChecksumLength, InvalidChecksum, InvalidShareFormat},
+    gf65536,
+};
+use anyhow::Result;
+use std::iter;
+
+/// The number of check symbols
+const CHECK_SYMBOLS: usize = 4;
+/// The most characters a checksum covers.  The code word, the leading 1, the
+/// characters and the check symbols, is at most the 65535 non-zero elements
+/// of the field long, so that no two symbols share a power of `a`.
+pub(crate) const MAX_SYMBOLS: usize = 65535 - 1 - CHECK_SYMBOLS;
+/// The encoded length of a checksum in bytes
+const CHECKSUM_LEN: usize = 2 * CHECK_SYMBOLS;
+/// The separator between a share and its checksum
+const SEPARATOR: char = ':';
+
+/// Maps a character to the symbol covered by the checksum, or `None` if the
+/// character should be ignored.
+pub(crate) type Normalize = fn(char) -> Option<char>;
+
+/// The check symbols, or the syndromes, of a share
+type Checks = [u16; CHECK_SYMBOLS];
+
+/// Append a checksum over `share[start..]` to the given share
+pub(crate) fn append(
+    share: &str,
+    start: usize,
+    normalize: Normalize,
+    encode: fn(&[u8]) -> String,
+) -> String {
+    let checksum: Vec<u8> = remainder(&symbols(share, start, normalize))
+        .iter()
+        .flat_map(|check| check.to_be_bytes())
+        .collect();
+    format!("{share}{SEPARATOR}{}", encode(&checksum))
+}
+
+/// Verify the checksum over `share[start..]`, returning the share without it.
+///
+/// If the checksum does not match, the error includes the likely position of
+/// a single mistyped character or a pair of swapped characters, when one can
+/// be found.  A share of more than [`MAX_SYMBOLS`] checksummed characters is
+/// refused.
+pub(crate) fn verify(
+    share: &str,
+    start: usize,
+    normalize: Normalize,
+    decode: fn(&str) -> Result<Vec<u8>>,
+) -> Result<&str> {
+    let (body, checksum) = share
+        .rsplit_once(SEPARATOR)
+        .filter(|(body, _)| body.len() >= start)
+        .ok_or(InvalidShareFormat)?;
+    let checksum: [u8; CHECKSUM_LEN] = decode(checksum)
+        .ok()
+        .and_then(|checksum| checksum.try_into().ok())
+        .ok_or(InvalidChecksum)?;
+    let expected: Checks =
+        std::array::from_fn(|i| u16::from_be_bytes([checksum[2 * i], checksum[2 * i + 1]]));
+
+    let symbols = symbols(body, start, normalize);
+    if symbols.len() > MAX_SYMBOLS {
+        return Err(ChecksumLength {
+            length: symbols.len(),
+            max: MAX_SYMBOLS,
+        }
+        .into());
+    }
+    let actual = remainder(&symbols);
+    if actual == expected {
+        Ok(body)
+    } else {
+        match locate(&symbols, syndromes(actual, expected)) {
+            Some(position) => Err(ChecksumError { position }.into()),
+            None => Err(InvalidChecksum.into()),
+        }
+    }
+}
+
+/// The 1-based character position and symbol of each character in
+/// `share[start..]`
+fn symbols(share: &str, start: usize, normalize: Normalize) -> Vec<(usize, u16)> {
+    share
+        .chars()
+        .enumerate()
+        .skip(share[..start].chars().count())
+        .filter_map(|(i, c)| normalize(c).map(|c| (i + 1, symbol(c))))
+        .collect()
+}
+
+/// Characters outside the 16 bits of a symbol, which no encoding uses, are
+/// folded into it
+#[allow(clippy::cast_possible_truncation)]
+fn symbol(c: char) -> u16 {
+    let c = u32::from(c);
+    (c ^ c >> 16) as u16
+}
+
+/// The coefficients of the generator polynomial below its leading 1, highest
+/// degree first
+fn generator() -> Checks {
+    let generator = (1..=CHECK_SYMBOLS).fold(vec![1], |poly, j| {
+        // Multiply by `x + a^j`
+        let root = gf65536::exp(j);
+        let mut product = poly.clone();
+        product.push(0);
+        for (i, coefficient) in poly.iter().enumerate() {
+            product[i + 1] ^= gf65536::mul(root, *coefficient);
+        }
+        product
+    });
+    std::array::from_fn(|i| generator[i + 1])
+}
+
+/// The remainder of the symbols times `x^4` divided by the generator
+/// polynomial.  The symbols are led by a 1, so that leading zero symbols
+/// still count.
+fn remainder(symbols: &[(usize, u16)]) -> Checks {
+    let generator = generator();
+    iter::once(1)
+        .chain(symbols.iter().map(|(_, symbol)| *symbol))
+        .fold([0; CHECK_SYMBOLS], |checks, symbol| {
+            let feedback = checks[0] ^ symbol;
+            std::array::from_fn(|i| {
+                checks.get(i + 1).copied().unwrap_or(0) ^ gf65536::mul(feedback, generator[i])
+            })
+        })
+}
+
+/// The syndromes of a share, its errors evaluated at `a, a^2, a^3, a^4`.  The
+/// symbols of the share and of its checksum together are a multiple of the
+/// generator polynomial, so the errors leave the difference between the
+/// actual and the expected remainders.
+fn syndromes(actual: Checks, expected: Checks) -> Checks {
+    std::array::from_fn(|i| {
+        let root = gf65536::exp(i + 1);
+        actual
+            .iter()
+            .zip(expected)
+            .fold(0, |sum, (a, e)| gf65536::mul(sum, root) ^ a ^ e)
+    })
+}
+
+/// Find the likely position of a single substituted character, or of the
+/// first of two swapped adjacent characters, from the syndromes
+fn locate(symbols: &[(usize, u16)], syndromes: Checks) -> Option<usize> {
+    // Substituting `e` into the symbol of degree `p` gives the syndromes
+    // `e a^(jp)`
+    error_degree(syndromes)
+        .and_then(|degree| position(symbols, degree))
+        .or_else(|| {
+            // Swapping the symbols of degrees `p + 1` and `p`, which differ by
+            // `e`, gives the syndromes `e a^(jp) (a^j + 1)`
+            let scaled: Checks =
+                std::array::from_fn(|i| gf65536::div(syndromes[i], gf65536::exp(i + 1) ^ 1));
+            error_degree(scaled)
+                .filter(|degree| position(symbols, *degree).is_some())
+                .and_then(|degree| position(symbols, degree + 1))
+        })
+}
+
+/// The degree `p` of the syndromes, if they are `e a^(jp)` for some `e`
+fn error_degree(syndromes: Checks) -> Option<usize> {
+    if syndromes[0] == 0 {
+        return None;
+    }
+    let ratio = gf65536::div(syndromes[1], syndromes[0]);
+    syndromes
+        .windows(2)
+        .all(|pair| pair[1] == gf65536::mul(pair[0], ratio))
+        .then(|| gf65536::log(ratio))
+        .flatten()
+}
+
+/// The character position of the symbol of the given degree, below which are
+/// the check symbols
+fn position(symbols: &[(usize, u16)], degree: usize) -> Option<usize> {
+    degree
+        .checked_sub(CHECK_SYMBOLS)
+        .and_then(|degree| symbols.len().checked_sub(degree + 1))
+        .and_then(|idx| symbols.get(idx))
+        .map(|(position, _)| *position)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{append, verify};
+    use crate::{encoding::hex, utils::check_err_result};
+    use anyhow::Result;
+
+    #[allow(clippy::unnecessary_wraps)]
+    fn normalize(c: char) -> Option<char> {
+        Some(c)
+    }
+
+    #[test]
+    fn append_verify_roundtrips() -> Result<()> {
+        let share = append("tag:0123456789", 4, normalize, hex::encode);
+        assert_eq!(verify(&share, 4, normalize, hex::decode)?, "tag:0123456789");
+        Ok(())
+    }
+
+    #[test]
+    fn substitution_is_located() -> Result<()> {
+        let share = append("tag:0123456789", 4, normalize, hex::encode);
+        check_err_result(
+            verify(&share.replacen('5', "6", 1), 4, normalize, hex::decode),
+            "Invalid share checksum, check the share near character 10",
+        )
+    }
+
+    #[test]
+    fn transposition_is_located() -> Result<()> {
+        let share = append("tag:0123456789", 4, normalize, hex::encode);
+        check_err_result(
+            verify(&share.replacen("34", "43", 1), 4, normalize, hex::decode),
+            "Invalid share checksum, check the share near character 8",
+        )
+    }
+
+    #[test]
+    fn every_substitution_and_swap_is_located() -> Result<()> {
+        let body = "tag:00112233445566778899aabbccddeeff";
+        let share = append(body, 4, normalize, hex::encode);
+        let chars: Vec<char> = body.chars().collect();
+        let check = |typo: Vec<char>, position: usize| {
+            let typo: String = typo.into_iter().collect();
+            check_err_result(
+                verify(&share.replacen(body, &typo, 1), 4, normalize, hex::decode),
+                &format!("Invalid share checksum, check the share near character {position}"),
+            )
+        };
+        for i in 4..chars.len() {
+            for c in "0123456789abcdef".chars().filter(|c| *c != chars[i]) {
+                let mut typo = chars.clone();
+                typo[i] = c;
+                check(typo, i + 1)?;
+            }
+            if i + 1 < chars.len() && chars[i] != chars[i + 1] {
+                let mut swap = chars.clone();
+                swap.swap(i, i + 1);
+                check(swap, i + 1)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn double_errors_are_detected() {
+        let body = "tag:0123456789abcdef";
+        let share = append(body, 4, normalize, hex::encode);
+        let chars: Vec<char> = body.chars().collect();
+        for i in 4..chars.len() {
+            for j in i + 1..chars.len() {
+                for (a, b) in [('0', 'f'), ('7', '8'), ('a', '1')] {
+                    let mut typo = chars.clone();
+                    typo[i] = a;
+                    typo[j] = b;
+                    let typo: String = typo.into_iter().collect();
+                    if typo != body {
+                        assert!(
+                            verify(&share.replacen(body, &typo, 1), 4, normalize, hex::decode)
+                                .is_err(),
+                            "{typo}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn long_shares_error() -> Result<()> {
+        let share = append(
+            &format!("tag:{}", "0".repeat(65_531)),
+            4,
+            normalize,
+            hex::encode,
+        );
+        check_err_result(
+            verify(&share, 4, normalize, hex::decode),
+            "A share with 65531 checksummed characters is too long for its checksum, which covers at most 65530",
+        )?;
+        let share = append(
+            &format!("tag:{}", "0".repeat(65_530)),
+            4,
+            normalize,
+            hex::encode,
+        );
+        assert!(verify(&share, 4, normalize, hex::decode).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn tag_is_not_covered() {
+        let share = append("tag:0123456789", 4, normalize, hex::encode);
+        assert!(verify(&share.replacen("tag", "TAG", 1), 4, normalize, hex::decode).is_ok());
+    }
+
+    #[test]
+    fn bad_checksum_errors() -> Result<()> {
+        check_err_result(
+            verify("tag:0123456789:00", 4, normalize, hex::decode),
+            "Invalid share checksum",
+        )?;
+        check_err_result(
+            verify("tag", 4, normalize, hex::decode),
+            "Invalid share format",
+        )
+    }
+}
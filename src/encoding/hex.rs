@@ -0,0 +1,99 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` hex share encoding
+
+use super::{ShareEncoding, decode_tagged, encode_tagged, position, read_tagged};
+use crate::error::SsssError::InvalidShareFormat;
+use anyhow::Result;
+
+pub(crate) const TAG: &str = "hex";
+const ALPHABET: &[u8; 16] = b"0123456789abcdef";
+
+/// Lowercase hexadecimal shares, e.g. `hex:03:9f2c...:5e0a...`.
+///
+/// Decoding is case-insensitive.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Hex;
+
+impl ShareEncoding for Hex {
+    fn encode(&self, index: u8, share: &[u8]) -> String {
+        encode_tagged(TAG, index, share, normalize, encode)
+    }
+
+    fn decode(&self, share: &str) -> Result<(u8, Vec<u8>)> {
+        decode_tagged(TAG, share, normalize, decode)
+    }
+}
+
+/// Read a damaged share, see [`read_tagged`]
+pub(crate) fn read_damaged(share: &str) -> Option<Vec<Option<u8>>> {
+    read_tagged(share, normalize, |c| position(TAG, ALPHABET, c).ok(), 4)
+}
+
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .flat_map(|b| {
+            [
+                ALPHABET[usize::from(b >> 4)],
+                ALPHABET[usize::from(b & 0xf)],
+            ]
+        })
+        .map(char::from)
+        .collect()
+}
+
+pub(crate) fn decode(input: &str) -> Result<Vec<u8>> {
+    let nibbles = input
+        .chars()
+        .map(|c| position(TAG, ALPHABET, c.to_ascii_lowercase()))
+        .collect::<Result<Vec<u8>>>()?;
+    if nibbles.len() % 2 == 0 {
+        Ok(nibbles
+            .chunks(2)
+            .map(|pair| pair[0] << 4 | pair[1])
+            .collect())
+    } else {
+        Err(InvalidShareFormat.into())
+    }
+}
+
+/// Hex is case-insensitive
+#[allow(clippy::unnecessary_wraps)]
+fn normalize(c: char) -> Option<char> {
+    Some(c.to_ascii_lowercase())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+
+    #[test]
+    fn encode_works() {
+        assert_eq!(encode(&[0x00, 0x9f, 0xff]), "009fff");
+    }
+
+    #[test]
+    fn decode_works() -> Result<()> {
+        assert_eq!(decode("009FfF")?, [0x00, 0x9f, 0xff]);
+        Ok(())
+    }
+
+    #[test]
+    fn decode_odd_length_errors() -> Result<()> {
+        check_err_result(decode("abc"), "Invalid share format")
+    }
+
+    #[test]
+    fn decode_bad_character_errors() -> Result<()> {
+        check_err_result(decode("0g"), "Invalid hex character: g")
+    }
+}
@@ -0,0 +1,369 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` mnemonic share encodings
+//!
+//! A mnemonic share is the share index followed by the share bytes, written
+//! as words from a word list and followed by a single checksum word.
+//!
+//! The checksum word is chosen so that all of the words, read as the
+//! coefficients of a polynomial over GF(2^b) where b is the number of bits per
+//! word, evaluate to zero at x.  Every single mistyped word and every swap of
+//! two adjacent, different words, the checksum word included, is detected.
+//!
+//! Decoding is lenient: case is ignored, and a word may be shortened to any
+//! prefix of at least four letters that matches only one word in the list.
+
+mod bip39;
+pub(crate) mod pgp;
+
+use super::ShareEncoding;
+use crate::error::SsssError::{AmbiguousWord, InvalidChecksum, InvalidShareFormat, UnknownWord};
+use anyhow::Result;
+
+/// The shortest prefix accepted in place of a whole word
+const MIN_PREFIX: usize = 4;
+
+/// Shares written as words from the BIP39 English word list, e.g.
+/// `adapt ability rotate ... zoo`.
+///
+/// Each word carries 11 bits.  The data is terminated with a single set bit
+/// and padded with zero bits to a whole number of words, so the share length
+/// is recovered exactly.  The first four letters of every BIP39 word are
+/// unique, so four letters are always enough when decoding.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Bip39;
+
+/// Shares written as words from the PGP word list, e.g.
+/// `topmost istanbul pluto ... vagabond`.
+///
+/// Each word carries one byte.  Bytes at even positions use the two-syllable
+/// word list and bytes at odd positions the three-syllable list, so a dropped,
+/// repeated or swapped word is noticed before the checksum is checked.  A few
+/// words in the PGP lists share their first four letters, e.g. `snapline` and
+/// `snapshot`; these need more letters when decoding.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PgpWords;
+
+/// A word list a share can be written in
+trait WordList {
+    /// The number of bits carried by each word
+    const BITS: usize;
+    /// The reduction polynomial of GF(2^`BITS`) used for the checksum word
+    const POLY: u16;
+
+    /// The words available at the given position in the share
+    fn words(position: usize) -> &'static [&'static str];
+}
+
+impl WordList for Bip39 {
+    const BITS: usize = 11;
+    // x^11 + x^2 + 1
+    const POLY: u16 = 0x805;
+
+    fn words(_position: usize) -> &'static [&'static str] {
+        &bip39::WORDS
+    }
+}
+
+impl WordList for PgpWords {
+    const BITS: usize = 8;
+    // x^8 + x^4 + x^3 + x^2 + 1
+    const POLY: u16 = 0x11d;
+
+    fn words(position: usize) -> &'static [&'static str] {
+        if position % 2 == 0 {
+            &pgp::EVEN
+        } else {
+            &pgp::ODD
+        }
+    }
+}
+
+impl ShareEncoding for Bip39 {
+    fn encode(&self, index: u8, share: &[u8]) -> String {
+        encode::<Self>(index, share)
+    }
+
+    fn decode(&self, share: &str) -> Result<(u8, Vec<u8>)> {
+        decode::<Self>(share)
+    }
+}
+
+impl ShareEncoding for PgpWords {
+    fn encode(&self, index: u8, share: &[u8]) -> String {
+        encode::<Self>(index, share)
+    }
+
+    fn decode(&self, share: &str) -> Result<(u8, Vec<u8>)> {
+        decode::<Self>(share)
+    }
+}
+
+/// Pick the word list the given mnemonic share was most likely written in
+pub(crate) fn detect(share: &str) -> Box<dyn ShareEncoding> {
+    if matches::<PgpWords>(share) > matches::<Bip39>(share) {
+        Box::new(PgpWords)
+    } else {
+        Box::new(Bip39)
+    }
+}
+
+/// Count the words of the share found in the given word list
+fn matches<W: WordList>(share: &str) -> usize {
+    share
+        .split_whitespace()
+        .enumerate()
+        .filter(|(position, word)| lookup(W::words(*position), *position, word).is_ok())
+        .count()
+}
+
+fn encode<W: WordList>(index: u8, share: &[u8]) -> String {
+    let mut bytes = Vec::with_capacity(share.len() + 1);
+    bytes.push(index);
+    bytes.extend_from_slice(share);
+
+    let mut values = to_words::<W>(&bytes);
+    values.push(checksum::<W>(&values));
+    values
+        .iter()
+        .enumerate()
+        .map(|(position, value)| W::words(position)[usize::from(*value)])
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn decode<W: WordList>(share: &str) -> Result<(u8, Vec<u8>)> {
+    let mut values = share
+        .split_whitespace()
+        .enumerate()
+        .map(|(position, word)| lookup(W::words(position), position, word))
+        .collect::<Result<Vec<u16>>>()?;
+    let check = values.pop().ok_or(InvalidShareFormat)?;
+    if checksum::<W>(&values) != check {
+        return Err(InvalidChecksum.into());
+    }
+
+    let bytes = from_words::<W>(&values)?;
+    match bytes.split_first() {
+        Some((index, share)) => Ok((*index, share.to_vec())),
+        None => Err(InvalidShareFormat.into()),
+    }
+}
+
+/// Find a word, or an unambiguous prefix of a word, in the word list
+pub(crate) fn lookup(words: &[&str], position: usize, word: &str) -> Result<u16> {
+    let word = word.to_ascii_lowercase();
+    let found = match words.iter().position(|w| *w == word) {
+        Some(found) => Some(found),
+        None if word.len() >= MIN_PREFIX => {
+            let mut candidates = words
+                .iter()
+                .enumerate()
+                .filter(|(_, w)| w.starts_with(&word));
+            match (candidates.next(), candidates.next()) {
+                (Some((found, _)), None) => Some(found),
+                (Some(_), Some(_)) => {
+                    return Err(AmbiguousWord {
+                        position: position + 1,
+                        word,
+                    }
+                    .into());
+                }
+                (None, _) => None,
+            }
+        }
+        None => None,
+    };
+    found
+        .and_then(|found| u16::try_from(found).ok())
+        .ok_or_else(|| {
+            UnknownWord {
+                position: position + 1,
+                word,
+            }
+            .into()
+        })
+}
+
+/// Split the bytes into words of `W::BITS` bits.  If a word holds less than a
+/// byte, a set bit marks the end of the data before padding.
+fn to_words<W: WordList>(bytes: &[u8]) -> Vec<u16> {
+    let mut bits: Vec<bool> = bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1 == 1))
+        .collect();
+    if W::BITS != 8 {
+        bits.push(true);
+        bits.resize(bits.len().div_ceil(W::BITS) * W::BITS, false);
+    }
+    bits.chunks(W::BITS)
+        .map(|chunk| chunk.iter().fold(0, |acc, bit| acc << 1 | u16::from(*bit)))
+        .collect()
+}
+
+/// Join words of `W::BITS` bits back into bytes, the inverse of [`to_words`]
+fn from_words<W: WordList>(values: &[u16]) -> Result<Vec<u8>> {
+    let mut bits: Vec<bool> = values
+        .iter()
+        .flat_map(|value| (0..W::BITS).rev().map(move |i| value >> i & 1 == 1))
+        .collect();
+    if W::BITS != 8 {
+        let end = bits
+            .iter()
+            .rposition(|bit| *bit)
+            .ok_or(InvalidShareFormat)?;
+        if bits.len() - end > W::BITS {
+            return Err(InvalidShareFormat.into());
+        }
+        bits.truncate(end);
+    }
+    if bits.len() % 8 != 0 {
+        return Err(InvalidShareFormat.into());
+    }
+    Ok(bits
+        .chunks(8)
+        .map(|chunk| chunk.iter().fold(0, |acc, bit| acc << 1 | u8::from(*bit)))
+        .collect())
+}
+
+/// The word that makes the words, followed by it, evaluate to zero at x in
+/// GF(2^`W::BITS`)
+fn checksum<W: WordList>(values: &[u16]) -> u16 {
+    values.iter().chain([&0]).fold(0, |acc, value| {
+        let mut acc = acc << 1;
+        if acc >> W::BITS != 0 {
+            acc ^= W::POLY;
+        }
+        acc ^ value
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Bip39, PgpWords, ShareEncoding, bip39, pgp};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+
+    fn encodings() -> Vec<Box<dyn ShareEncoding>> {
+        vec![Box::new(Bip39), Box::new(PgpWords)]
+    }
+
+    #[test]
+    fn word_lists_are_complete() {
+        assert_eq!(bip39::WORDS[0], "abandon");
+        assert_eq!(bip39::WORDS[2047], "zoo");
+        assert_eq!(pgp::EVEN[0], "aardvark");
+        assert_eq!(pgp::EVEN[255], "zulu");
+        assert_eq!(pgp::ODD[0], "adroitness");
+        assert_eq!(pgp::ODD[255], "yucatan");
+    }
+
+    #[test]
+    fn pgp_words_match_reference() -> Result<()> {
+        // The fingerprint example from the PGP word list, with the index byte
+        // and checksum word added
+        let encoded = PgpWords.encode(
+            0xe5,
+            &[
+                0x82, 0x94, 0xf2, 0xe9, 0xa2, 0x27, 0x48, 0x6e, 0x8b, 0x06, 0x1b, 0x31, 0xcc, 0x52,
+                0x8f, 0xd7, 0xfa, 0x3f, 0x19,
+            ],
+        );
+        assert!(encoded.starts_with(
+            "topmost istanbul pluto vagabond treadmill pacific brackish dictator goldfish \
+             medusa afflict bravado chatter revolver dupont midsummer stopwatch whimsical \
+             cowbell bottomless "
+        ));
+        assert_eq!(PgpWords.decode(&encoded)?.0, 0xe5);
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip_all_lengths() -> Result<()> {
+        for len in 0..40 {
+            let share: Vec<u8> = (0..len)
+                .map(|i: u8| i.wrapping_mul(37).wrapping_add(11))
+                .collect();
+            for encoding in encodings() {
+                let encoded = encoding.encode(3, &share);
+                assert_eq!(encoding.decode(&encoded)?, (3, share.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn prefixes_and_case_are_accepted() -> Result<()> {
+        let share = "correct horse battery staple".as_bytes();
+        for encoding in encodings() {
+            let encoded = encoding.encode(9, share);
+            let shortened = encoded
+                .split(' ')
+                .map(|word| word.chars().take(4).collect::<String>().to_uppercase())
+                .collect::<Vec<String>>()
+                .join("\n");
+            assert_eq!(encoding.decode(&shortened)?, (9, share.to_vec()));
+        }
+        assert_eq!(
+            Bip39.decode(&Bip39.encode(9, share).replace("ability", "abil"))?,
+            (9, share.to_vec())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ambiguous_prefix_errors() -> Result<()> {
+        check_err_result(
+            PgpWords.decode("snap adroitness"),
+            "The word 'snap' at position 1 matches more than one word, use more letters",
+        )
+    }
+
+    #[test]
+    fn unknown_word_errors() -> Result<()> {
+        check_err_result(
+            Bip39.decode("abandon zzzz zoo"),
+            "Unknown word 'zzzz' at position 2",
+        )?;
+        // Odd positions use the three-syllable list
+        check_err_result(
+            PgpWords.decode("aardvark aardvark"),
+            "Unknown word 'aardvark' at position 2",
+        )
+    }
+
+    #[test]
+    fn substitutions_and_swaps_are_detected() {
+        let share = "correct horse battery staple".as_bytes();
+        let encoded = Bip39.encode(3, share);
+        let words: Vec<&str> = encoded.split(' ').collect();
+        for i in 0..words.len() {
+            for replacement in ["abandon", "zoo", "legal"] {
+                let mut mistyped = words.clone();
+                mistyped[i] = replacement;
+                if mistyped == words {
+                    continue;
+                }
+                assert!(Bip39.decode(&mistyped.join(" ")).is_err());
+            }
+            if i + 1 < words.len() && words[i] != words[i + 1] {
+                let mut swapped = words.clone();
+                swapped.swap(i, i + 1);
+                assert!(Bip39.decode(&swapped.join(" ")).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn bad_checksum_errors() -> Result<()> {
+        let encoded = PgpWords.encode(1, &[0xde, 0xad]);
+        let mistyped = encoded.replacen(encoded.split(' ').next().unwrap_or_default(), "acme", 1);
+        check_err_result(PgpWords.decode(&mistyped), "Invalid share checksum")
+    }
+}
@@ -0,0 +1,348 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` share encodings
+
+pub(crate) mod base32;
+pub(crate) mod base64url;
+mod bech32m;
+pub(crate) mod checksum;
+pub(crate) mod hex;
+pub(crate) mod mnemonic;
+pub(crate) mod parity;
+
+pub use self::base32::Base32;
+pub use self::base64url::Base64Url;
+pub use self::bech32m::Bech32m;
+pub use self::hex::Hex;
+pub use self::mnemonic::{Bip39, PgpWords};
+
+use self::checksum::Normalize;
+use self::parity::ReedSolomon;
+use crate::{
+    base62::{self, Base62},
+    error::SsssError::{InvalidCharacter, InvalidShareFormat},
+};
+use anyhow::Result;
+#[cfg(feature = "fuzz")]
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+
+/// A text encoding for a single share.
+///
+/// An encoding turns a share index and the share bytes into text, and back
+/// again.  Every encoding produced by this crate can be recognized from the
+/// text alone, so [`unlock`](crate::unlock) can be given a mixed set of shares.
+///
+/// Every encoding produced by this crate also carries a checksum, so that a
+/// mistyped or transposed character is reported, along with its likely
+/// position, instead of silently corrupting the secret.
+pub trait ShareEncoding {
+    /// Encode the share with the given index as text
+    fn encode(&self, index: u8, share: &[u8]) -> String;
+
+    /// Decode the given text into a share index and the share bytes
+    ///
+    /// # Errors
+    /// * This function will generate an error if the text is not a valid share
+    ///   in this encoding.
+    fn decode(&self, share: &str) -> Result<(u8, Vec<u8>)>;
+}
+
+/// The share encodings built into this crate, used to select the encoding
+/// in [`SsssConfig`](crate::SsssConfig).
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+pub enum Encoding {
+    /// See [`Base62`]
+    #[default]
+    Base62,
+    /// See [`Hex`]
+    Hex,
+    /// See [`Base64Url`]
+    Base64Url,
+    /// See [`Base32`]
+    Base32,
+    /// See [`Bech32m`], using the default human-readable prefix
+    Bech32m,
+    /// See [`Bip39`]
+    Bip39,
+    /// See [`PgpWords`]
+    PgpWords,
+}
+
+impl Encoding {
+    pub(crate) fn encoder(self, canonical: bool) -> Box<dyn ShareEncoding> {
+        match self {
+            Encoding::Base62 => Box::new(Base62::new(canonical)),
+            Encoding::Hex => Box::new(Hex),
+            Encoding::Base64Url => Box::new(Base64Url),
+            Encoding::Base32 => Box::new(Base32),
+            Encoding::Bech32m => Box::new(Bech32m::default()),
+            Encoding::Bip39 => Box::new(Bip39),
+            Encoding::PgpWords => Box::new(PgpWords),
+        }
+    }
+}
+
+/// Detect the encoding of the given share from its text.
+///
+/// Mnemonic shares are words separated by whitespace, tagged encodings look
+/// like `tag:index:share:checksum`, bech32m shares have no `:` at all, and
+/// anything else is base62.  Any of these may be wrapped in Reed–Solomon
+/// parity, like `rs8:share`.
+pub(crate) fn detect(share: &str) -> Result<Box<dyn ShareEncoding>> {
+    if let Some((parity, inner)) = parity::split(share) {
+        return Ok(Box::new(ReedSolomon::new(parity, detect(inner)?)));
+    }
+    if share.trim().contains(char::is_whitespace) {
+        return Ok(mnemonic::detect(share));
+    }
+    match share.split_once(':') {
+        None => Ok(Box::new(Bech32m::new(&bech32m::hrp(share)?))),
+        Some((tag, _)) if tag.eq_ignore_ascii_case(hex::TAG) => Ok(Box::new(Hex)),
+        Some((tag, _)) if tag.eq_ignore_ascii_case(base64url::TAG) => Ok(Box::new(Base64Url)),
+        Some((tag, _)) if tag.eq_ignore_ascii_case(base32::TAG) => Ok(Box::new(Base32)),
+        Some(_) => Ok(Box::new(Base62::default())),
+    }
+}
+
+/// Read the index and share bytes of a damaged share, ignoring its checksum.
+///
+/// Bytes that cannot be read are `None`.  Returns `None` if the share is not
+/// in an encoding that can be read this way.
+pub(crate) fn read_damaged(share: &str) -> Option<Vec<Option<u8>>> {
+    if share.trim().contains(char::is_whitespace) {
+        return None;
+    }
+    match share.split_once(':') {
+        None => bech32m::read_damaged(share),
+        Some((tag, _)) if tag.eq_ignore_ascii_case(hex::TAG) => hex::read_damaged(share),
+        Some((tag, _)) if tag.eq_ignore_ascii_case(base64url::TAG) => {
+            base64url::read_damaged(share)
+        }
+        Some((tag, _)) if tag.eq_ignore_ascii_case(base32::TAG) => base32::read_damaged(share),
+        Some(_) => base62::read_damaged(share),
+    }
+}
+
+/// Encode a share as `tag:index:share:checksum`
+pub(crate) fn encode_tagged(
+    tag: &str,
+    index: u8,
+    share: &[u8],
+    normalize: Normalize,
+    encode: fn(&[u8]) -> String,
+) -> String {
+    let share = format!("{tag}:{}:{}", encode(&[index]), encode(share));
+    checksum::append(&share, tag.len() + 1, normalize, encode)
+}
+
+/// Decode a share encoded as `tag:index:share:checksum`
+pub(crate) fn decode_tagged(
+    tag: &str,
+    share: &str,
+    normalize: Normalize,
+    decode: fn(&str) -> Result<Vec<u8>>,
+) -> Result<(u8, Vec<u8>)> {
+    let share = checksum::verify(share, tag.len() + 1, normalize, decode)?;
+    match share.split(':').collect::<Vec<&str>>()[..] {
+        [share_tag, index, share] if share_tag.eq_ignore_ascii_case(tag) => {
+            let index = decode_index(&decode(index)?)?;
+            Ok((index, decode(share)?))
+        }
+        _ => Err(InvalidShareFormat.into()),
+    }
+}
+
+/// Read a damaged share encoded as `tag:index:share:checksum`, where each
+/// symbol holds `bits` bits
+pub(crate) fn read_tagged(
+    share: &str,
+    normalize: Normalize,
+    symbol: fn(char) -> Option<u8>,
+    bits: usize,
+) -> Option<Vec<Option<u8>>> {
+    let read = |field: &str| read_bits(field.chars().filter_map(normalize).map(symbol), bits);
+    match share.split(':').collect::<Vec<&str>>()[..] {
+        [_, index, share] | [_, index, share, _] => {
+            let mut bytes = read(index);
+            bytes.extend(read(share));
+            Some(bytes)
+        }
+        _ => None,
+    }
+}
+
+/// Pack symbols of `bits` bits each into bytes, dropping any partial byte at
+/// the end.  A byte holding any bit of an unreadable symbol is `None`.
+pub(crate) fn read_bits(symbols: impl Iterator<Item = Option<u8>>, bits: usize) -> Vec<Option<u8>> {
+    let bits: Vec<Option<bool>> = symbols
+        .flat_map(|symbol| {
+            (0..bits)
+                .rev()
+                .map(move |i| symbol.map(|symbol| symbol >> i & 1 == 1))
+        })
+        .collect();
+    bits.chunks_exact(8)
+        .map(|byte| {
+            byte.iter()
+                .try_fold(0, |acc, bit| bit.map(|bit| acc << 1 | u8::from(bit)))
+        })
+        .collect()
+}
+
+/// Decode a share index, which is always a single byte
+pub(crate) fn decode_index(bytes: &[u8]) -> Result<u8> {
+    Ok(u8::from_be_bytes(bytes.try_into()?))
+}
+
+/// Look up a character in an alphabet
+pub(crate) fn position(encoding: &'static str, alphabet: &[u8], c: char) -> Result<u8> {
+    alphabet
+        .iter()
+        .position(|a| char::from(*a) == c)
+        .and_then(|pos| u8::try_from(pos).ok())
+        .ok_or_else(|| InvalidCharacter { encoding, c }.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Base32, Base64Url, Bech32m, Bip39, Hex, PgpWords, ShareEncoding, detect};
+    use crate::{base62::Base62, utils::check_err_result};
+    use anyhow::Result;
+
+    fn encodings() -> Vec<Box<dyn ShareEncoding>> {
+        vec![
+            Box::new(Base62::default()),
+            Box::new(Base62::new(true)),
+            Box::new(Hex),
+            Box::new(Base64Url),
+            Box::new(Base32),
+            Box::new(Bech32m::default()),
+            Box::new(Bech32m::new("custom")),
+        ]
+    }
+
+    #[test]
+    fn all_encodings_roundtrip() -> Result<()> {
+        let share = "correct horse battery staple".as_bytes();
+        for encoding in encodings() {
+            for index in [1, 7, 255] {
+                let encoded = encoding.encode(index, share);
+                assert_eq!(encoding.decode(&encoded)?, (index, share.to_vec()));
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn all_encodings_are_detected() -> Result<()> {
+        let share = [0, 1, 2, 253, 254, 255];
+        for encoding in encodings() {
+            let encoded = encoding.encode(3, &share);
+            assert_eq!(detect(&encoded)?.decode(&encoded)?, (3, share.to_vec()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn mnemonics_are_detected() -> Result<()> {
+        let share = [0, 1, 2, 253, 254, 255];
+        for encoded in [Bip39.encode(3, &share), PgpWords.encode(3, &share)] {
+            assert_eq!(detect(&encoded)?.decode(&encoded)?, (3, share.to_vec()));
+            let lines = format!("  {}\n", encoded.replace(' ', "\n"));
+            assert_eq!(detect(&lines)?.decode(&lines)?, (3, share.to_vec()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn detect_is_case_insensitive() -> Result<()> {
+        let share = [0xde, 0xad, 0xbe, 0xef];
+        for encoded in [
+            Hex.encode(1, &share),
+            Base32.encode(1, &share),
+            Bech32m::default().encode(1, &share),
+        ] {
+            let upper = encoded.to_ascii_uppercase();
+            assert_eq!(detect(&upper)?.decode(&upper)?, (1, share.to_vec()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn unknown_tag_errors() -> Result<()> {
+        let share = "abc:00:00";
+        check_err_result(detect(share)?.decode(share), "Invalid share format")
+    }
+
+    #[test]
+    fn single_substitutions_are_detected() {
+        let share = [0xde, 0xad, 0xbe, 0xef];
+        for encoding in encodings() {
+            let encoded = encoding.encode(3, &share);
+            for (i, c) in encoded.char_indices() {
+                for replacement in ['0', '7', 'A', 'z', ':', '-'] {
+                    if replacement.eq_ignore_ascii_case(&c) {
+                        continue;
+                    }
+                    let mut mistyped = encoded.clone();
+                    mistyped.replace_range(i..=i, &replacement.to_string());
+                    let decoded = detect(&mistyped).and_then(|enc| enc.decode(&mistyped));
+                    assert!(
+                        decoded.map_or(true, |decoded| decoded != (3, share.to_vec())),
+                        "{mistyped}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn single_substitutions_are_located() -> Result<()> {
+        let share = "correct horse battery staple".as_bytes();
+        for encoding in encodings() {
+            let encoded = encoding.encode(3, share);
+            let i = encoded.len() / 2;
+            let replacement = if &encoded[i..=i] == "2" { "3" } else { "2" };
+            let mut mistyped = encoded.clone();
+            mistyped.replace_range(i..=i, replacement);
+            check_err_result(
+                detect(&mistyped)?.decode(&mistyped),
+                &format!(
+                    "Invalid share checksum, check the share near character {}",
+                    i + 1
+                ),
+            )?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn adjacent_transpositions_are_detected() {
+        let share = "correct horse battery staple".as_bytes();
+        for encoding in encodings() {
+            let encoded = encoding.encode(3, share);
+            let chars: Vec<char> = encoded.chars().collect();
+            for i in 0..chars.len() - 1 {
+                if chars[i].eq_ignore_ascii_case(&chars[i + 1]) {
+                    continue;
+                }
+                let mut swapped = chars.clone();
+                swapped.swap(i, i + 1);
+                let swapped: String = swapped.into_iter().collect();
+                let decoded = detect(&swapped).and_then(|enc| enc.decode(&swapped));
+                assert!(
+                    decoded.map_or(true, |decoded| decoded != (3, share.to_vec())),
+                    "{swapped}"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` Reed–Solomon protected shares
+
+use super::{ShareEncoding, decode_index, read_damaged};
+use crate::{error::SsssError::InvalidShareFormat, gf256::reed_solomon};
+use anyhow::Result;
+use std::iter;
+
+pub(crate) const TAG: &str = "rs";
+/// The maximum number of parity bytes per block
+pub(crate) const MAX_PARITY: u8 = 64;
+
+/// Shares of another encoding with Reed–Solomon parity added to the share
+/// bytes, e.g. `rs8:b32:0C:KWP...:9ZD...`.
+///
+/// The parity covers the share index and the share bytes.  When the inner
+/// share no longer decodes, it is read again ignoring its checksum, with any
+/// unreadable character, such as a `?` written in place of an illegible one,
+/// marking the bytes it held as erased.  Mnemonic shares are only repaired
+/// when every word can still be read.
+pub(crate) struct ReedSolomon {
+    parity: u8,
+    inner: Box<dyn ShareEncoding>,
+}
+
+impl ReedSolomon {
+    pub(crate) fn new(parity: u8, inner: Box<dyn ShareEncoding>) -> Self {
+        Self { parity, inner }
+    }
+}
+
+impl ShareEncoding for ReedSolomon {
+    fn encode(&self, index: u8, share: &[u8]) -> String {
+        let data: Vec<u8> = iter::once(index).chain(share.iter().copied()).collect();
+        let codeword = reed_solomon::protect(&data, self.parity);
+        format!(
+            "{TAG}{}:{}",
+            self.parity,
+            self.inner.encode(index, &codeword[1..])
+        )
+    }
+
+    fn decode(&self, share: &str) -> Result<(u8, Vec<u8>)> {
+        let (parity, inner) = split(share)
+            .filter(|(parity, _)| *parity == self.parity)
+            .ok_or(InvalidShareFormat)?;
+        let codeword = match self.inner.decode(inner) {
+            Ok((index, share)) => iter::once(index).chain(share).map(Some).collect(),
+            Err(e) => read_damaged(inner).ok_or(e)?,
+        };
+        let data = reed_solomon::correct(&codeword, parity)?;
+        match data.split_first() {
+            Some((index, share)) => Ok((decode_index(&[*index])?, share.to_vec())),
+            None => Err(InvalidShareFormat.into()),
+        }
+    }
+}
+
+/// Split a protected share into its number of parity bytes and the inner share
+pub(crate) fn split(share: &str) -> Option<(u8, &str)> {
+    let (tag, inner) = share.split_once(':')?;
+    tag.get(..TAG.len())
+        .filter(|prefix| prefix.eq_ignore_ascii_case(TAG))
+        .and_then(|_| tag.get(TAG.len()..))
+        .filter(|parity| !parity.is_empty() && parity.bytes().all(|b| b.is_ascii_digit()))
+        .and_then(|parity| parity.parse().ok())
+        .filter(|parity| (1..=MAX_PARITY).contains(parity))
+        .map(|parity| (parity, inner))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ReedSolomon, split};
+    use crate::{
+        base62::Base62,
+        encoding::{Base32, Base64Url, Bech32m, Bip39, Hex, PgpWords, ShareEncoding, detect},
+    };
+    use anyhow::Result;
+
+    fn encodings() -> Vec<Box<dyn ShareEncoding>> {
+        vec![
+            Box::new(Base62::default()),
+            Box::new(Base62::new(true)),
+            Box::new(Hex),
+            Box::new(Base64Url),
+            Box::new(Base32),
+            Box::new(Bech32m::default()),
+            Box::new(Bip39),
+            Box::new(PgpWords),
+        ]
+    }
+
+    #[test]
+    fn split_works() {
+        assert_eq!(split("rs8:hex:01:02:03"), Some((8, "hex:01:02:03")));
+        assert_eq!(split("RS12:abc"), Some((12, "abc")));
+        assert_eq!(split("rs:abc"), None);
+        assert_eq!(split("rs0:abc"), None);
+        assert_eq!(split("rs65:abc"), None);
+        assert_eq!(split("rs+8:abc"), None);
+        assert_eq!(split("hex:01:02:03"), None);
+    }
+
+    #[test]
+    fn undamaged_shares_roundtrip() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        for inner in encodings() {
+            let share = ReedSolomon::new(6, inner).encode(3, secret);
+            assert!(share.starts_with("rs6:"));
+            assert_eq!(detect(&share)?.decode(&share)?, (3, secret.to_vec()));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn damaged_shares_are_repaired() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        for inner in encodings() {
+            let share = ReedSolomon::new(16, inner).encode(3, secret);
+            // Smudge two characters in the middle of the share, or in the
+            // first word of a mnemonic share
+            let middle = share.find(' ').unwrap_or(share.len() / 2);
+            let damaged = format!("{}??{}", &share[..middle], &share[middle + 2..]);
+            let decoded = detect(&damaged)?.decode(&damaged);
+            if share.contains(' ') {
+                assert!(decoded.is_err());
+            } else {
+                assert_eq!(decoded?, (3, secret.to_vec()));
+            }
+        }
+        Ok(())
+    }
+}
@@ -20,7 +20,7 @@ pub(crate) enum SsssError {
         threshold,
         shares
     )]
-    ThresholdToLow { threshold: u8, shares: u8 },
+    ThresholdToLow { threshold: u16, shares: u16 },
     #[error("The secret cannot be empty")]
     EmptySecret,
     #[error(
@@ -39,4 +39,212 @@ pub(crate) enum SsssError {
     BadCharacter { c: char },
     #[error("Invalid share format")]
     InvalidShareFormat,
+    #[error("Invalid {encoding} character: {c}")]
+    InvalidCharacter { encoding: &'static str, c: char },
+    #[error("Invalid share checksum")]
+    InvalidChecksum,
+    #[error("Invalid share checksum, check the share near character {}", position)]
+    ChecksumError { position: usize },
+    #[error(
+        "A share with {} checksummed characters is too long for its checksum, which covers at most {}",
+        length,
+        max
+    )]
+    ChecksumLength { length: usize, max: usize },
+    #[error(
+        "The human-readable prefix '{}' does not match the expected prefix '{}'",
+        found,
+        expected
+    )]
+    HrpMismatch { expected: String, found: String },
+    #[error("Unknown word '{}' at position {}", word, position)]
+    UnknownWord { position: usize, word: String },
+    #[error(
+        "The word '{}' at position {} matches more than one word, use more letters",
+        word,
+        position
+    )]
+    AmbiguousWord { position: usize, word: String },
+    #[error(
+        "Verification is limited to {} shares, but {} were requested",
+        max,
+        shares
+    )]
+    VerifyTooManyShares { shares: u16, max: u16 },
+    #[error("The generated shares failed verification")]
+    VerificationFailed,
+    #[error("Invalid share URI")]
+    InvalidUri,
+    #[error("Line {} of the paper backup does not match its checksum", line)]
+    PaperChecksum { line: u16 },
+    #[error("Line {} of the paper backup is missing", line)]
+    PaperLine { line: u16 },
+    #[error(
+        "The paper backup holds {} bytes, but its header says {}",
+        found,
+        expected
+    )]
+    PaperLength { expected: usize, found: usize },
+    #[error("The paper backup is missing the '{}' field", field)]
+    PaperMissingField { field: &'static str },
+    #[error(
+        "The number of parity bytes '{}' is larger than the maximum allowed '{}'",
+        parity,
+        max
+    )]
+    ParityTooLarge { parity: u8, max: u8 },
+    #[error("The share is too damaged to be repaired")]
+    Unrepairable,
+    #[error("Not an ssss binary share")]
+    BinaryMagic,
+    #[error("Unsupported binary share version {}", version)]
+    BinaryVersion { version: u8 },
+    #[error("Unknown binary share flags {:#04x}", flags)]
+    BinaryFlags { flags: u8 },
+    #[error("Invalid binary share checksum")]
+    BinaryChecksum,
+    #[error("The binary share is truncated")]
+    BinaryLength,
+    #[error("Invalid share armor")]
+    InvalidArmor,
+    #[error(
+        "A share of {} bytes is too short to fingerprint, it must be at least {}",
+        length,
+        min
+    )]
+    FingerprintShareLength { length: usize, min: usize },
+    #[error(
+        "The secret length '{}' and its padding do not fit the padded size '{}'",
+        length,
+        size
+    )]
+    PaddingTooSmall { length: usize, size: usize },
+    #[error(
+        "The padded size '{}' is larger than the maximum allowed '{}'",
+        size,
+        max
+    )]
+    PaddingTooLarge { size: usize, max: usize },
+    #[error("Expected {} share indices, but {} were given", expected, found)]
+    IndexCount { expected: u16, found: usize },
+    #[error("A share index cannot be 0")]
+    IndexZero,
+    #[error("The share index {} is used more than once", index)]
+    DuplicateIndex { index: u16 },
+    #[error("The share index {} is larger than the field allows ({})", index, max)]
+    IndexTooLarge { index: u16, max: u16 },
+    #[error(
+        "The field supports at most {} shares, but {} were requested",
+        max,
+        shares
+    )]
+    TooManyShares { shares: u16, max: u16 },
+    #[error("The shares were not all split over the same field")]
+    FieldMismatch,
+    #[error("The bytes are not an element of the field")]
+    InvalidElement,
+    #[error("A share index is used more than once")]
+    DuplicateShareIndex,
+    #[error("{} shares are required, but {} were given", threshold, found)]
+    NotEnoughShares { threshold: u16, found: usize },
+    #[error(
+        "Too many shares are wrong to recover the secret, at most {} can be corrected",
+        max
+    )]
+    TooManyBadShares { max: usize },
+    #[error("Invalid Vault unseal key")]
+    InvalidVaultKey,
+    #[error("The security level {} is not a multiple of 8 from 8 to 1024", level)]
+    LegacyLevel { level: usize },
+    #[error("The threshold must be at least 2")]
+    LegacyThreshold,
+    #[error("The token must be at most 128 characters, without a '-'")]
+    LegacyToken,
+    #[error("The shares have different security levels")]
+    LegacyLevelMismatch,
+    #[error("The number of bits must be from 3 to 20, but was {}", bits)]
+    SecretsJsBits { bits: u8 },
+    #[error("The number of shares must be from 2 to {}, but was {}", max, shares)]
+    SecretsJsShares { shares: u32, max: u32 },
+    #[error(
+        "The threshold must be from 2 to the number of shares ({}), but was {}",
+        shares,
+        threshold
+    )]
+    SecretsJsThreshold { threshold: u32, shares: u32 },
+    #[error("The padding length must be at most 1024, but was {}", length)]
+    SecretsJsPadLength { length: usize },
+    #[error("The shares were split with different numbers of bits")]
+    SecretsJsBitsMismatch,
+    #[error("The shares do not all hold the same encrypted message")]
+    SssCiphertextMismatch,
+    #[error("The shares could not be decrypted")]
+    SssDecrypt,
+    #[error("The Clevis pin '{}' is not supported", pin)]
+    ClevisPin { pin: String },
+    #[error("Invalid Clevis pin configuration")]
+    ClevisConfig,
+    #[error(
+        "The threshold {} must be from 1 to the number of pins ({})",
+        threshold,
+        pins
+    )]
+    ClevisThreshold { threshold: u64, pins: usize },
+    #[error(
+        "{} pins are required, but only {} could be decrypted",
+        threshold,
+        found
+    )]
+    ClevisNotEnoughPins { threshold: usize, found: usize },
+    #[error("The null pin is configured to fail")]
+    ClevisNullFail,
+    #[error("Invalid JWE")]
+    InvalidJwe,
+    #[error("The JWE could not be decrypted")]
+    JweDecrypt,
+    #[error(
+        "The master secret must be an even number of at least 16 bytes, but was {} bytes",
+        length
+    )]
+    Slip39SecretLength { length: usize },
+    #[error("The passphrase must contain only printable ASCII characters")]
+    Slip39Passphrase,
+    #[error("The iteration exponent must be less than 16, but was {}", exponent)]
+    Slip39IterationExponent { exponent: u8 },
+    #[error(
+        "At most 16 groups or group members are supported, but {} were requested",
+        count
+    )]
+    Slip39TooManyShares { count: usize },
+    #[error(
+        "Creating multiple member shares with member threshold 1 is not allowed, use 1-of-1 member sharing instead"
+    )]
+    Slip39MemberThresholdOne,
+    #[error("Invalid mnemonic length: {} words", words)]
+    Slip39MnemonicLength { words: usize },
+    #[error("Invalid mnemonic padding")]
+    Slip39Padding,
+    #[error("The mnemonics do not all belong to the same set of shares")]
+    Slip39Mismatch,
+    #[error("Share indices must be unique")]
+    Slip39DuplicateIndex,
+    #[error("Invalid digest of the shared secret")]
+    Slip39Digest,
+    #[error(
+        "Wrong number of mnemonic groups, expected {} but {} were provided",
+        expected,
+        found
+    )]
+    Slip39GroupCount { expected: u8, found: usize },
+    #[error(
+        "Wrong number of mnemonics in group {}, expected {} but {} were provided",
+        group,
+        expected,
+        found
+    )]
+    Slip39MemberCount {
+        group: u8,
+        expected: u8,
+        found: usize,
+    },
 }
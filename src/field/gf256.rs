@@ -0,0 +1,204 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` GF(2^8) elements
+
+use super::Field;
+use crate::{error::SsssError::InvalidElement, gf256};
+use anyhow::Result;
+use rand::Rng;
+use std::ops::{Add, AddAssign, Div, Mul, MulAssign, Neg, Sub, SubAssign};
+
+/// An element of GF(2^8), the field the shares of
+/// [`gen_shares`](crate::gen_shares) are split over by default.  The field is
+/// reduced by [`Poly11b`](crate::Poly11b); see [`Gf2_8`](crate::Gf2_8) for
+/// the others.
+///
+/// The usual arithmetic operators work on elements.  Addition and subtraction
+/// are both XOR, every element is its own negation, and dividing by zero
+/// panics.
+///
+/// # Example
+/// ```
+/// # use ssss::Gf256;
+/// #
+/// let a = Gf256(0xb6);
+/// let b = Gf256(0x53);
+/// assert_eq!(a * b, Gf256(0x36));
+/// assert_eq!(a * b / b, a);
+/// assert_eq!(a + b, a - b);
+/// assert_eq!(-a, a);
+/// assert_eq!(a * a.inv().unwrap(), Gf256(1));
+/// assert_eq!(Gf256(0).inv(), None);
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Gf256(pub u8);
+
+impl Gf256 {
+    /// The multiplicative inverse, or `None` for zero
+    #[must_use]
+    pub fn inv(self) -> Option<Self> {
+        (self.0 != 0).then(|| Gf256(gf256::div(1, self.0)))
+    }
+}
+
+impl From<u8> for Gf256 {
+    fn from(value: u8) -> Self {
+        Gf256(value)
+    }
+}
+
+impl From<Gf256> for u8 {
+    fn from(value: Gf256) -> Self {
+        value.0
+    }
+}
+
+impl Add for Gf256 {
+    type Output = Gf256;
+
+    fn add(self, rhs: Gf256) -> Gf256 {
+        Gf256(gf256::add(self.0, rhs.0))
+    }
+}
+
+impl Sub for Gf256 {
+    type Output = Gf256;
+
+    fn sub(self, rhs: Gf256) -> Gf256 {
+        Gf256(gf256::sub(self.0, rhs.0))
+    }
+}
+
+impl Mul for Gf256 {
+    type Output = Gf256;
+
+    fn mul(self, rhs: Gf256) -> Gf256 {
+        Gf256(gf256::mul(self.0, rhs.0))
+    }
+}
+
+impl Div for Gf256 {
+    type Output = Gf256;
+
+    /// # Panics
+    /// * Dividing by zero panics.
+    fn div(self, rhs: Gf256) -> Gf256 {
+        assert!(rhs.0 != 0, "attempt to divide by zero");
+        Gf256(gf256::div(self.0, rhs.0))
+    }
+}
+
+impl Neg for Gf256 {
+    type Output = Gf256;
+
+    fn neg(self) -> Gf256 {
+        self
+    }
+}
+
+impl AddAssign for Gf256 {
+    fn add_assign(&mut self, rhs: Gf256) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Gf256 {
+    fn sub_assign(&mut self, rhs: Gf256) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Gf256 {
+    fn mul_assign(&mut self, rhs: Gf256) {
+        *self = *self * rhs;
+    }
+}
+
+impl Field for Gf256 {
+    fn zero() -> Self {
+        Gf256(0)
+    }
+
+    fn one() -> Self {
+        Gf256(1)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        *self + *other
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        *self - *other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        *self * *other
+    }
+
+    fn inv(&self) -> Option<Self> {
+        Gf256::inv(*self)
+    }
+
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut byte = [0];
+        rng.fill_bytes(&mut byte);
+        Gf256(byte[0])
+    }
+
+    fn byte_len() -> usize {
+        1
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![self.0]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        match bytes {
+            [byte] => Ok(Gf256(*byte)),
+            _ => Err(InvalidElement.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Gf256;
+
+    #[test]
+    fn operators_work() {
+        let mut a = Gf256(133);
+        assert_eq!(a * Gf256(5), Gf256(167));
+        assert_eq!(Gf256(90) / Gf256(21), Gf256(189));
+        assert_eq!(Gf256(100) + Gf256(30), Gf256(122));
+        assert_eq!(Gf256(100) - Gf256(30), Gf256(122));
+        a += Gf256(1);
+        assert_eq!(a, Gf256(132));
+        a -= Gf256(1);
+        a *= Gf256(5);
+        assert_eq!(a, Gf256(167));
+        assert_eq!(u8::from(-Gf256::from(7)), 7);
+    }
+
+    #[test]
+    fn inv_works() {
+        for i in 1..=u8::MAX {
+            let a = Gf256(i);
+            assert_eq!(a.inv().map(|inv| inv * a), Some(Gf256(1)));
+            assert_eq!(Gf256(1) / a, a.inv().unwrap_or_default());
+        }
+        assert_eq!(Gf256(0).inv(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to divide by zero")]
+    fn div_by_zero_panics() {
+        let _ = Gf256(1) / Gf256(0);
+    }
+}
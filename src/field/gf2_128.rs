@@ -0,0 +1,109 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` GF(2^128) elements
+
+use super::Field;
+use crate::error::SsssError::InvalidElement;
+use anyhow::Result;
+use rand::Rng;
+
+/// The low terms of the reduction polynomial, `x^128 + x^7 + x^2 + x + 1`
+const REDUCTION: u128 = 0x87;
+
+/// An element of GF(2^128), a polynomial over GF(2) whose coefficient of
+/// `x^i` is bit `i` of the integer.  The field is reduced by
+/// `x^128 + x^7 + x^2 + x + 1`, and elements are serialized as 16 big-endian
+/// bytes.
+///
+/// Multiplication runs in constant time.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Gf2_128(pub u128);
+
+impl Field for Gf2_128 {
+    fn zero() -> Self {
+        Gf2_128(0)
+    }
+
+    fn one() -> Self {
+        Gf2_128(1)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Gf2_128(self.0 ^ other.0)
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.add(other)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        let mut a = self.0;
+        let mut b = other.0;
+        let mut product = 0;
+        for _ in 0..128 {
+            product ^= a & (b & 1).wrapping_neg();
+            b >>= 1;
+            a = (a << 1) ^ (REDUCTION & (a >> 127).wrapping_neg());
+        }
+        Gf2_128(product)
+    }
+
+    fn inv(&self) -> Option<Self> {
+        if self.0 == 0 {
+            return None;
+        }
+        // a^(2^128 - 2), by squaring and multiplying for every bit but the last
+        let mut inverse = Gf2_128::one();
+        for bit in (0..128).rev() {
+            inverse = inverse.mul(&inverse);
+            if bit != 0 {
+                inverse = inverse.mul(self);
+            }
+        }
+        Some(inverse)
+    }
+
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut bytes = [0; 16];
+        rng.fill_bytes(&mut bytes);
+        Gf2_128(u128::from_be_bytes(bytes))
+    }
+
+    fn byte_len() -> usize {
+        16
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let bytes: [u8; 16] = bytes.try_into().map_err(|_| InvalidElement)?;
+        Ok(Gf2_128(u128::from_be_bytes(bytes)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Field, Gf2_128};
+
+    #[test]
+    fn mul_reduces() {
+        assert_eq!(Gf2_128(1 << 127).mul(&Gf2_128(2)), Gf2_128(0x87));
+        assert_eq!(Gf2_128(0x8000).mul(&Gf2_128(0x10)), Gf2_128(0x8_0000));
+        assert_eq!(Gf2_128(3).mul(&Gf2_128(3)), Gf2_128(5));
+    }
+
+    #[test]
+    fn inv_works() {
+        assert_eq!(Gf2_128(1).inv(), Some(Gf2_128(1)));
+        assert_eq!(Gf2_128(2).inv(), Some(Gf2_128((1 << 127) | 0x43)));
+        assert_eq!(Gf2_128(0).inv(), None);
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` GF(2^8) elements over any reduction polynomial
+
+use super::Field;
+use crate::{
+    error::SsssError::InvalidElement,
+    gf256::{self, Gf256Params},
+};
+use anyhow::Result;
+use rand::Rng;
+use std::{
+    fmt::{self, Debug, Formatter},
+    hash::{Hash, Hasher},
+    marker::PhantomData,
+    ops::{Add, Div, Mul, Neg, Sub},
+};
+
+/// An element of the GF(2^8) given by `P`, for combining shares split by
+/// implementations that use another reduction polynomial than
+/// [`Gf256`](crate::Gf256).
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{unlock_over, Gf2_8, Poly11d};
+/// #
+/// # pub fn main() -> Result<()> {
+/// // Two shares of "hi", split over the field reduced by 0x11d
+/// let share = |x: u8, ys: [u8; 2]| (Gf2_8::<Poly11d>::new(x), ys.map(Gf2_8::new).to_vec());
+/// let shares = [share(1, [0xf4, 0x7e]), share(2, [0x4d, 0x47])];
+///
+/// let secret: Vec<u8> = unlock_over(&shares)?.into_iter().map(Gf2_8::value).collect();
+/// assert_eq!(secret, b"hi");
+/// # Ok(())
+/// # }
+/// ```
+pub struct Gf2_8<P: Gf256Params> {
+    value: u8,
+    params: PhantomData<fn() -> P>,
+}
+
+impl<P: Gf256Params> Gf2_8<P> {
+    /// The element with the given bits
+    #[must_use]
+    pub const fn new(value: u8) -> Self {
+        Gf2_8 {
+            value,
+            params: PhantomData,
+        }
+    }
+
+    /// The bits of the element
+    #[must_use]
+    pub const fn value(self) -> u8 {
+        self.value
+    }
+
+    /// The multiplicative inverse, or `None` for zero
+    #[must_use]
+    pub fn inv(self) -> Option<Self> {
+        (self.value != 0).then(|| Gf2_8::new(P::TABLES.div(1, self.value)))
+    }
+}
+
+impl<P: Gf256Params> Clone for Gf2_8<P> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<P: Gf256Params> Copy for Gf2_8<P> {}
+
+impl<P: Gf256Params> PartialEq for Gf2_8<P> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<P: Gf256Params> Eq for Gf2_8<P> {}
+
+impl<P: Gf256Params> Hash for Gf2_8<P> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+    }
+}
+
+impl<P: Gf256Params> Debug for Gf2_8<P> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Gf2_8").field(&self.value).finish()
+    }
+}
+
+impl<P: Gf256Params> Add for Gf2_8<P> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Gf2_8::new(gf256::add(self.value, rhs.value))
+    }
+}
+
+impl<P: Gf256Params> Sub for Gf2_8<P> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Gf2_8::new(gf256::sub(self.value, rhs.value))
+    }
+}
+
+impl<P: Gf256Params> Mul for Gf2_8<P> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        Gf2_8::new(P::TABLES.mul(self.value, rhs.value))
+    }
+}
+
+impl<P: Gf256Params> Div for Gf2_8<P> {
+    type Output = Self;
+
+    /// # Panics
+    /// * Dividing by zero panics.
+    fn div(self, rhs: Self) -> Self {
+        assert!(rhs.value != 0, "attempt to divide by zero");
+        Gf2_8::new(P::TABLES.div(self.value, rhs.value))
+    }
+}
+
+impl<P: Gf256Params> Neg for Gf2_8<P> {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        self
+    }
+}
+
+impl<P: Gf256Params> Field for Gf2_8<P> {
+    fn zero() -> Self {
+        Gf2_8::new(0)
+    }
+
+    fn one() -> Self {
+        Gf2_8::new(1)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        *self + *other
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        *self - *other
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        *self * *other
+    }
+
+    fn inv(&self) -> Option<Self> {
+        Gf2_8::inv(*self)
+    }
+
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut byte = [0];
+        rng.fill_bytes(&mut byte);
+        Gf2_8::new(byte[0])
+    }
+
+    fn byte_len() -> usize {
+        1
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        vec![self.value]
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        match bytes {
+            [byte] => Ok(Gf2_8::new(*byte)),
+            _ => Err(InvalidElement.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Gf2_8;
+    use crate::{
+        field::Gf256,
+        gf256::{Poly11b, Poly11d},
+    };
+
+    #[test]
+    fn poly11b_is_gf256() {
+        for a in 0..=u8::MAX {
+            for b in [1, 2, 0x53, 0xca, u8::MAX] {
+                let (x, y) = (Gf2_8::<Poly11b>::new(a), Gf2_8::<Poly11b>::new(b));
+                assert_eq!((x * y).value(), (Gf256(a) * Gf256(b)).0);
+                assert_eq!((x / y).value(), (Gf256(a) / Gf256(b)).0);
+            }
+        }
+    }
+
+    #[test]
+    fn poly11d_works() {
+        let x = Gf2_8::<Poly11d>::new(0x80);
+        assert_eq!(x * Gf2_8::new(2), Gf2_8::new(0x1d));
+        assert_eq!(x.inv().map(|inv| inv * x), Some(Gf2_8::new(1)));
+        assert_eq!(Gf2_8::<Poly11d>::new(0).inv(), None);
+        assert_eq!(-x - x, Gf2_8::new(0));
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` GF(2^16) elements
+
+use super::Field;
+use crate::{error::SsssError::InvalidElement, gf65536};
+use anyhow::Result;
+use rand::Rng;
+
+/// An element of GF(2^16), the field of [`FieldKind::Gf65536`](crate::FieldKind)
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Gf65536(pub u16);
+
+impl Field for Gf65536 {
+    fn zero() -> Self {
+        Gf65536(0)
+    }
+
+    fn one() -> Self {
+        Gf65536(1)
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Gf65536(gf65536::add(self.0, other.0))
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        self.add(other)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        Gf65536(gf65536::mul(self.0, other.0))
+    }
+
+    fn inv(&self) -> Option<Self> {
+        (self.0 != 0).then(|| Gf65536(gf65536::div(1, self.0)))
+    }
+
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let mut bytes = [0; 2];
+        rng.fill_bytes(&mut bytes);
+        Gf65536(u16::from_be_bytes(bytes))
+    }
+
+    fn byte_len() -> usize {
+        2
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_be_bytes().to_vec()
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        match bytes {
+            [hi, lo] => Ok(Gf65536(u16::from_be_bytes([*hi, *lo]))),
+            _ => Err(InvalidElement.into()),
+        }
+    }
+}
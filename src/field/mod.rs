@@ -0,0 +1,133 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` finite fields
+//!
+//! Secrets are split over a [`Field`].  The crate ships GF(2^8), over any
+//! reduction polynomial, GF(2^16), GF(2^128) and prime fields GF(p); any other
+//! field can be plugged into
+//! [`gen_shares_over`](crate::gen_shares_over) and
+//! [`unlock_over`](crate::unlock_over) by implementing the trait.
+
+mod gf256;
+mod gf2_128;
+mod gf2_8;
+mod gf65536;
+mod polynomial;
+mod prime;
+
+pub use self::gf2_8::Gf2_8;
+pub use self::gf2_128::Gf2_128;
+pub use self::gf256::Gf256;
+pub use self::gf65536::Gf65536;
+pub use self::polynomial::Polynomial;
+pub use self::prime::{Fp, Mersenne127, Modulus};
+use anyhow::Result;
+use rand::Rng;
+use std::fmt::Debug;
+
+/// The elements of a finite field.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{Field, Gf256};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let a = Gf256(0xb6);
+/// let b = Gf256(0x53);
+/// assert_eq!(a.mul(&b), Gf256(0x36));
+/// assert_eq!(a.mul(&b).mul(&b.inv().unwrap()), a);
+/// assert_eq!(Gf256::from_bytes(&a.to_bytes())?, a);
+/// # Ok(())
+/// # }
+/// ```
+pub trait Field: Clone + Debug + Eq {
+    /// The additive identity
+    fn zero() -> Self;
+
+    /// The multiplicative identity
+    fn one() -> Self;
+
+    /// The sum of two elements
+    #[must_use]
+    fn add(&self, other: &Self) -> Self;
+
+    /// The difference of two elements
+    #[must_use]
+    fn sub(&self, other: &Self) -> Self;
+
+    /// The product of two elements
+    #[must_use]
+    fn mul(&self, other: &Self) -> Self;
+
+    /// The multiplicative inverse, or `None` for zero
+    fn inv(&self) -> Option<Self>;
+
+    /// A uniformly random element
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> Self;
+
+    /// The number of bytes of a serialized element
+    fn byte_len() -> usize;
+
+    /// The element as [`byte_len`](Field::byte_len) big-endian bytes
+    fn to_bytes(&self) -> Vec<u8>;
+
+    /// Read an element from big-endian bytes.
+    ///
+    /// # Errors
+    /// * This function will generate an error if the bytes are not an
+    ///   element of the field.
+    fn from_bytes(bytes: &[u8]) -> Result<Self>;
+
+    /// Whether this is the additive identity
+    fn is_zero(&self) -> bool {
+        *self == Self::zero()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Field, Fp, Gf2_8, Gf2_128, Gf256, Gf65536, Mersenne127};
+    use crate::gf256::Poly11d;
+    use anyhow::Result;
+    use rand::rng;
+
+    fn check_field<F: Field>() -> Result<()> {
+        let mut rng = rng();
+        for _ in 0..50 {
+            let a = F::random(&mut rng);
+            let b = F::random(&mut rng);
+            let c = F::random(&mut rng);
+            assert_eq!(a.add(&b), b.add(&a));
+            assert_eq!(a.mul(&b), b.mul(&a));
+            assert_eq!(a.add(&b).sub(&b), a);
+            assert_eq!(a.mul(&b.add(&c)), a.mul(&b).add(&a.mul(&c)));
+            assert_eq!(a.mul(&F::one()), a);
+            assert_eq!(a.add(&F::zero()), a);
+            if let Some(inv) = a.inv() {
+                assert_eq!(a.mul(&inv), F::one());
+            } else {
+                assert!(a.is_zero());
+            }
+            assert_eq!(a.to_bytes().len(), F::byte_len());
+            assert_eq!(F::from_bytes(&a.to_bytes())?, a);
+        }
+        assert!(F::zero().inv().is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn fields_are_fields() -> Result<()> {
+        check_field::<Gf256>()?;
+        check_field::<Gf2_8<Poly11d>>()?;
+        check_field::<Gf65536>()?;
+        check_field::<Gf2_128>()?;
+        check_field::<Fp<Mersenne127>>()
+    }
+}
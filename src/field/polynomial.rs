@@ -0,0 +1,176 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` polynomials
+
+use super::Field;
+use rand::Rng;
+
+/// A polynomial over a [`Field`], stored as its coefficients from the
+/// constant term up.
+///
+/// # Example
+/// ```
+/// # use ssss::{Gf256, Polynomial};
+/// #
+/// let secret = Gf256(42);
+/// let p = Polynomial::random(&mut rand::rng(), 2, secret);
+/// assert_eq!(p.degree(), 2);
+///
+/// let points: Vec<(Gf256, Gf256)> = (1..=3).map(|x| (Gf256(x), p.eval(&Gf256(x)))).collect();
+/// assert_eq!(Polynomial::interpolate(&points, &Gf256(0)), Some(secret));
+/// assert_eq!(Polynomial::interpolate(&points, &Gf256(9)), Some(p.eval(&Gf256(9))));
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Polynomial<F: Field> {
+    coeffs: Vec<F>,
+}
+
+impl<F: Field> Polynomial<F> {
+    /// The polynomial with the given coefficients, constant term first
+    #[must_use]
+    pub fn new(coeffs: Vec<F>) -> Self {
+        Polynomial { coeffs }
+    }
+
+    /// A random polynomial of exactly the given degree whose constant term is
+    /// `constant`.  Every other coefficient is random, and the leading one is
+    /// non-zero.
+    pub fn random<R: Rng + ?Sized>(rng: &mut R, degree: usize, constant: F) -> Self {
+        let mut coeffs: Vec<F> = (0..=degree).map(|_| F::random(rng)).collect();
+        while degree > 0 && coeffs[degree].is_zero() {
+            coeffs[degree] = F::random(rng);
+        }
+        coeffs[0] = constant;
+        Polynomial { coeffs }
+    }
+
+    /// The coefficients, constant term first
+    #[must_use]
+    pub fn coefficients(&self) -> &[F] {
+        &self.coeffs
+    }
+
+    /// The degree, the index of the highest non-zero coefficient.  A constant
+    /// polynomial, including zero, has degree 0.
+    #[must_use]
+    pub fn degree(&self) -> usize {
+        self.coeffs
+            .iter()
+            .rposition(|coeff| !coeff.is_zero())
+            .unwrap_or(0)
+    }
+
+    /// Evaluate the polynomial at `x`
+    #[must_use]
+    pub fn eval(&self, x: &F) -> F {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(F::zero(), |result, coeff| result.mul(x).add(coeff))
+    }
+
+    /// Evaluate the lowest degree polynomial passing through the given
+    /// `(x, y)` points at `x` by Lagrange interpolation, or `None` if two
+    /// points share an `x`
+    #[must_use]
+    pub fn interpolate(points: &[(F, F)], x: &F) -> Option<F> {
+        let mut y = F::zero();
+
+        for (i, (a_x, a_y)) in points.iter().enumerate() {
+            let mut li = F::one();
+            for (j, (b_x, _)) in points.iter().enumerate() {
+                if i != j {
+                    li = li.mul(&x.sub(b_x)).mul(&a_x.sub(b_x).inv()?);
+                }
+            }
+            y = y.add(&li.mul(a_y));
+        }
+
+        Some(y)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Polynomial;
+    use crate::field::{Field, Fp, Gf256, Mersenne127};
+    use rand::rng;
+
+    fn gf256(coeffs: &[u8]) -> Polynomial<Gf256> {
+        Polynomial::new(coeffs.iter().copied().map(Gf256).collect())
+    }
+
+    #[test]
+    fn degree_works() {
+        assert_eq!(gf256(&[1, 2]).degree(), 1);
+        assert_eq!(gf256(&[1, 2, 0]).degree(), 1);
+        assert_eq!(gf256(&[1, 2, 3]).degree(), 2);
+        assert_eq!(gf256(&[4]).degree(), 0);
+        assert_eq!(gf256(&[]).degree(), 0);
+    }
+
+    #[test]
+    fn eval_works() {
+        assert_eq!(gf256(&[1, 0, 2, 3]).eval(&Gf256(2)), Gf256(17));
+        assert_eq!(gf256(&[]).eval(&Gf256(2)), Gf256(0));
+    }
+
+    #[test]
+    fn random_works() {
+        let mut rng = rng();
+        let p = Polynomial::random(&mut rng, 4, Gf256(20));
+        assert_eq!(p.coefficients().len(), 5);
+        assert_eq!(p.coefficients()[0], Gf256(20));
+        assert_eq!(p.degree(), 4);
+        assert_eq!(Polynomial::random(&mut rng, 0, Gf256(20)), gf256(&[20]));
+    }
+
+    #[test]
+    fn interpolate_works() {
+        let points = |ys: [u8; 3]| -> Vec<(Gf256, Gf256)> {
+            (1..=3).zip(ys).map(|(x, y)| (Gf256(x), Gf256(y))).collect()
+        };
+        let zero = Gf256(0);
+        assert_eq!(
+            Polynomial::interpolate(&points([1, 2, 3]), &zero),
+            Some(Gf256(0))
+        );
+        assert_eq!(
+            Polynomial::interpolate(&points([80, 90, 20]), &zero),
+            Some(Gf256(30))
+        );
+        assert_eq!(
+            Polynomial::interpolate(&points([43, 22, 86]), &zero),
+            Some(Gf256(107))
+        );
+        assert_eq!(
+            Polynomial::interpolate(&[(Gf256(1), zero), (Gf256(1), zero)], &zero),
+            None
+        );
+    }
+
+    #[test]
+    fn interpolate_at_works() {
+        let mut rng = rng();
+        let secret = Fp::<Mersenne127>::random(&mut rng);
+        let p = Polynomial::random(&mut rng, 3, secret.clone());
+        let points: Vec<_> = (1..=4_u8)
+            .map(|x| {
+                let x = Fp::new(x.into());
+                let y = p.eval(&x);
+                (x, y)
+            })
+            .collect();
+        for x in [0_u8, 5, 200] {
+            let x = Fp::new(x.into());
+            assert_eq!(Polynomial::interpolate(&points, &x), Some(p.eval(&x)));
+        }
+        assert_eq!(Polynomial::interpolate(&points, &Fp::zero()), Some(secret));
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` prime fields
+
+use super::Field;
+use crate::error::SsssError::InvalidElement;
+use anyhow::Result;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use rand::Rng;
+use std::{
+    fmt::{self, Debug, Formatter},
+    marker::PhantomData,
+    sync::LazyLock,
+};
+
+/// The prime modulus of a field GF(p)
+pub trait Modulus {
+    /// The modulus, which must be prime
+    fn modulus() -> &'static BigUint;
+}
+
+/// The Mersenne prime 2^127 - 1
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Mersenne127;
+
+static MERSENNE_127: LazyLock<BigUint> = LazyLock::new(|| (BigUint::one() << 127_u32) - 1_u32);
+
+impl Modulus for Mersenne127 {
+    fn modulus() -> &'static BigUint {
+        &MERSENNE_127
+    }
+}
+
+/// An element of the prime field GF(p), an integer modulo the prime `p` given
+/// by `M`.  Elements are serialized as big-endian integers, padded to the
+/// length of the modulus.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use num_bigint::BigUint;
+/// # use ssss::{Field, Fp, Modulus};
+/// # use std::sync::LazyLock;
+/// #
+/// # pub fn main() -> Result<()> {
+/// struct P257;
+///
+/// static P: LazyLock<BigUint> = LazyLock::new(|| BigUint::from(257_u32));
+///
+/// impl Modulus for P257 {
+///     fn modulus() -> &'static BigUint {
+///         &P
+///     }
+/// }
+///
+/// let a = Fp::<P257>::new(BigUint::from(300_u32));
+/// assert_eq!(a.value(), &BigUint::from(43_u32));
+/// assert_eq!(a.mul(&a.inv().unwrap()), Fp::one());
+/// assert_eq!(a.to_bytes(), [0, 43]);
+/// # Ok(())
+/// # }
+/// ```
+pub struct Fp<M: Modulus> {
+    value: BigUint,
+    modulus: PhantomData<fn() -> M>,
+}
+
+impl<M: Modulus> Fp<M> {
+    /// The element congruent to the given integer
+    #[must_use]
+    pub fn new(value: BigUint) -> Self {
+        Fp {
+            value: value % M::modulus(),
+            modulus: PhantomData,
+        }
+    }
+
+    /// The integer in `0..p`
+    #[must_use]
+    pub fn value(&self) -> &BigUint {
+        &self.value
+    }
+}
+
+impl<M: Modulus> Clone for Fp<M> {
+    fn clone(&self) -> Self {
+        Fp {
+            value: self.value.clone(),
+            modulus: PhantomData,
+        }
+    }
+}
+
+impl<M: Modulus> PartialEq for Fp<M> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<M: Modulus> Eq for Fp<M> {}
+
+impl<M: Modulus> Debug for Fp<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Fp").field(&self.value).finish()
+    }
+}
+
+impl<M: Modulus> Field for Fp<M> {
+    fn zero() -> Self {
+        Fp::new(BigUint::zero())
+    }
+
+    fn one() -> Self {
+        Fp::new(BigUint::one())
+    }
+
+    fn add(&self, other: &Self) -> Self {
+        Fp::new(&self.value + &other.value)
+    }
+
+    fn sub(&self, other: &Self) -> Self {
+        Fp::new(M::modulus() + &self.value - &other.value)
+    }
+
+    fn mul(&self, other: &Self) -> Self {
+        Fp::new(&self.value * &other.value)
+    }
+
+    fn inv(&self) -> Option<Self> {
+        // a^(p - 2) = a^-1 for a prime p
+        let p = M::modulus();
+        (!self.value.is_zero()).then(|| Fp::new(self.value.modpow(&(p - 2_u32), p)))
+    }
+
+    fn random<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let p = M::modulus();
+        let excess = Self::byte_len() * 8 - usize::try_from(p.bits()).unwrap_or(usize::MAX);
+        let mut bytes = vec![0; Self::byte_len()];
+        // Rejection sampling over the bit length of p, so every element is
+        // equally likely
+        loop {
+            rng.fill_bytes(&mut bytes);
+            bytes[0] &= u8::MAX >> excess;
+            let value = BigUint::from_bytes_be(&bytes);
+            if &value < p {
+                return Fp::new(value);
+            }
+        }
+    }
+
+    fn byte_len() -> usize {
+        usize::try_from(M::modulus().bits().div_ceil(8)).unwrap_or(usize::MAX)
+    }
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let value = self.value.to_bytes_be();
+        let mut bytes = vec![0; Self::byte_len().saturating_sub(value.len())];
+        bytes.extend(value);
+        bytes
+    }
+
+    /// Read an element from big-endian bytes.  Leading zero bytes may be left
+    /// out.
+    fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        let value = BigUint::from_bytes_be(bytes);
+        if bytes.len() > Self::byte_len() || &value >= M::modulus() {
+            Err(InvalidElement.into())
+        } else {
+            Ok(Fp::new(value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Field, Fp, Mersenne127};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn mersenne127_works() -> Result<()> {
+        let p_minus_one = Fp::<Mersenne127>::zero().sub(&Fp::one());
+        assert_eq!(p_minus_one.to_bytes(), {
+            let mut bytes = vec![0xff; 16];
+            bytes[0] = 0x7f;
+            bytes[15] = 0xfe;
+            bytes
+        });
+        assert_eq!(p_minus_one.add(&Fp::one()), Fp::zero());
+        assert_eq!(p_minus_one.inv(), Some(p_minus_one.clone()));
+        assert_eq!(
+            Fp::<Mersenne127>::from_bytes(&[1, 0])?,
+            Fp::new(BigUint::from(256_u32))
+        );
+        check_err_result(
+            Fp::<Mersenne127>::from_bytes(&[0x80; 16]),
+            "The bytes are not an element of the field",
+        )
+    }
+}
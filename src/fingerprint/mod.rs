@@ -0,0 +1,341 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` share and share-set fingerprints
+
+mod randomart;
+
+use crate::{
+    encoding::{hex, mnemonic::pgp},
+    error::SsssError::{
+        EmptySharesMap, FieldMismatch, FingerprintShareLength, ShareLengthMismatch,
+    },
+    shamir::{FieldKind, decode_point},
+};
+use anyhow::Result;
+use sha2::{Digest, Sha256};
+use std::{collections::BTreeMap, fmt};
+
+/// The domain separation prefix of a share fingerprint
+const SHARE_DOMAIN: &[u8] = b"ssss share fingerprint v1\0";
+/// The domain separation prefix of a share-set fingerprint
+const SET_DOMAIN: &[u8] = b"ssss share-set fingerprint v1\0";
+/// The number of digest bytes shown in the hex and word forms
+const SHORT_BYTES: usize = 6;
+/// The length of the shortest share that is fingerprinted.  The fingerprint of
+/// a shorter share could be reversed by trying every share.
+const MIN_SHARE_BYTES: usize = 16;
+
+/// What a fingerprint was taken of
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+enum Kind {
+    Share,
+    Set,
+}
+
+/// A short fingerprint of a share or of a share set, to be compared by eye or
+/// read out over the phone.
+///
+/// The fingerprint is a SHA-256 digest of the decoded share index and bytes, so
+/// it does not depend on the encoding a share was written in: the Base62, the
+/// mnemonic and the paper copies of a share all have the same fingerprint.  A
+/// share-set fingerprint is the digest of the fingerprints of all of the
+/// shares in the set, in index order.
+///
+/// Only a digest of the share is shown, never the share itself, but the digest
+/// can still be used to confirm a guess of the share.  Anyone holding one share
+/// fewer than the threshold can compute every other share from a guess of the
+/// secret, so an overheard fingerprint lets them check guesses of the secret
+/// offline.  Shares shorter than 16 bytes are refused, so that a short key is
+/// never fingerprinted, but a secret that can be guessed, such as a PIN or a
+/// password, is just as exposed when it is [padded](crate::Padding) to a longer
+/// share.  Do not read out the fingerprints of shares of such a secret where
+/// they can be overheard.
+///
+/// ```text
+/// 73df 0886 cff1
+/// hockey therapist aimless letterhead stagehand vacancy
+/// +--[SSSS SHARE]---+
+/// |             o.*+|
+/// |             .B +|
+/// |             .oo |
+/// |         . . Eo  |
+/// |        S * o... |
+/// |         * * *.  |
+/// |          B Oo+. |
+/// |         =.O.=*o |
+/// |        o+=o++*o |
+/// +----[SHA256]-----+
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Fingerprint {
+    kind: Kind,
+    digest: [u8; 32],
+}
+
+impl Fingerprint {
+    /// The full SHA-256 digest
+    #[must_use]
+    pub fn digest(&self) -> &[u8; 32] {
+        &self.digest
+    }
+
+    /// The first six digest bytes in hex, in groups of four digits, e.g.
+    /// `73df 0886 cff1`
+    #[must_use]
+    pub fn to_hex(&self) -> String {
+        hex::encode(&self.digest[..SHORT_BYTES])
+            .as_bytes()
+            .chunks(4)
+            .filter_map(|group| std::str::from_utf8(group).ok())
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    /// The first six digest bytes as words from the PGP word list, which was
+    /// designed to be read aloud, e.g.
+    /// `hockey therapist aimless letterhead stagehand vacancy`.
+    ///
+    /// Bytes at even and odd positions use different lists, so a skipped or
+    /// repeated word is noticed.
+    #[must_use]
+    pub fn to_words(&self) -> String {
+        self.digest[..SHORT_BYTES]
+            .iter()
+            .enumerate()
+            .map(|(i, byte)| {
+                if i % 2 == 0 {
+                    pgp::EVEN[usize::from(*byte)]
+                } else {
+                    pgp::ODD[usize::from(*byte)]
+                }
+            })
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    /// The full digest drawn as randomart, like the host key art of SSH
+    #[must_use]
+    pub fn randomart(&self) -> String {
+        let title = match self.kind {
+            Kind::Share => "SSSS SHARE",
+            Kind::Set => "SSSS SET",
+        };
+        randomart::draw(&self.digest, title, "SHA256")
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+/// Fingerprint a share, in any encoding accepted by [`unlock`](crate::unlock).
+///
+/// # Errors
+/// * This function will generate an error if the share cannot be decoded, or
+///   is shorter than 16 bytes.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{fingerprint, gen_shares, reencode, PgpWords, SsssConfig};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = "correct horse battery staple".as_bytes();
+/// let shares = gen_shares(&SsssConfig::default(), &secret)?;
+///
+/// // The fingerprint does not depend on the encoding of the share
+/// let words = reencode(&shares[0], &PgpWords)?;
+/// assert_eq!(fingerprint(&shares[0])?, fingerprint(&words)?);
+/// assert_ne!(fingerprint(&shares[0])?, fingerprint(&shares[1])?);
+/// assert_eq!(fingerprint(&shares[0])?.to_words().split(' ').count(), 6);
+/// # Ok(())
+/// # }
+/// ```
+pub fn fingerprint(share: &str) -> Result<Fingerprint> {
+    let (field, idx, bytes) = decode_point(share.to_string())?;
+    check_length(bytes.len())?;
+    Ok(share_fingerprint(field, idx, &bytes))
+}
+
+/// Fingerprint a share set, from all of the shares generated for a secret.
+///
+/// The shares may be given in any order and any encoding.  Whoever generated
+/// the shares can read out the set fingerprint along with the fingerprint of
+/// each share, so that every holder can later confirm that the shares brought
+/// together for unlocking are the ones that were handed out.
+///
+/// # Errors
+/// * This function will generate an error if no shares are given, if a share
+///   cannot be decoded, or if the shares are not all the same length or are
+///   shorter than 16 bytes.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{canonicalize, gen_shares, set_fingerprint, SsssConfig};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = "correct horse battery staple".as_bytes();
+/// let mut shares = gen_shares(&SsssConfig::default(), &secret)?;
+/// let set = set_fingerprint(&shares)?;
+///
+/// shares.reverse();
+/// shares[0] = canonicalize(&shares[0])?;
+/// assert_eq!(set_fingerprint(&shares)?, set);
+/// assert!(set.randomart().starts_with("+---[SSSS SET]----+"));
+/// # Ok(())
+/// # }
+/// ```
+pub fn set_fingerprint(shares: &[String]) -> Result<Fingerprint> {
+    let mut decoded = BTreeMap::new();
+    for share in shares {
+        let (field, idx, bytes) = decode_point(share.clone())?;
+        let _old = decoded.insert(idx, (field, bytes));
+    }
+    let (field, len) = decoded
+        .values()
+        .next()
+        .map(|(field, bytes)| (*field, bytes.len()))
+        .ok_or(EmptySharesMap)?;
+    if decoded.values().any(|(other, _)| *other != field) {
+        return Err(FieldMismatch.into());
+    } else if decoded.values().any(|(_, bytes)| bytes.len() != len) {
+        return Err(ShareLengthMismatch.into());
+    }
+    check_length(len)?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(SET_DOMAIN);
+    for (idx, (field, bytes)) in &decoded {
+        hasher.update(share_fingerprint(*field, *idx, bytes).digest);
+    }
+    Ok(Fingerprint {
+        kind: Kind::Set,
+        digest: hasher.finalize().into(),
+    })
+}
+
+/// Check that a share is long enough to be fingerprinted
+fn check_length(length: usize) -> Result<()> {
+    if length < MIN_SHARE_BYTES {
+        Err(FingerprintShareLength {
+            length,
+            min: MIN_SHARE_BYTES,
+        }
+        .into())
+    } else {
+        Ok(())
+    }
+}
+
+fn share_fingerprint(field: FieldKind, idx: u16, bytes: &[u8]) -> Fingerprint {
+    let mut hasher = Sha256::new();
+    hasher.update(SHARE_DOMAIN);
+    match field {
+        FieldKind::Gf256 => hasher.update(&idx.to_be_bytes()[1..]),
+        FieldKind::Gf65536 => {
+            hasher.update(field.prefix());
+            hasher.update(idx.to_be_bytes());
+        }
+    }
+    hasher.update(bytes);
+    Fingerprint {
+        kind: Kind::Share,
+        digest: hasher.finalize().into(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FieldKind, fingerprint, set_fingerprint, share_fingerprint};
+    use crate::{
+        Encoding, Hex, PaperShare, SsssConfig, encoding::hex, gen_shares, reencode,
+        shamir::decode_share, utils::check_err_result,
+    };
+    use anyhow::Result;
+
+    #[test]
+    fn fingerprint_forms() {
+        let fingerprint = share_fingerprint(FieldKind::Gf256, 1, b"correct horse battery staple");
+        let hex = fingerprint.to_hex();
+        assert_eq!(hex.len(), 14);
+        assert_eq!(hex, fingerprint.to_string());
+        assert_eq!(
+            hex.replace(' ', ""),
+            hex::encode(&fingerprint.digest()[..6])
+        );
+        assert_eq!(fingerprint.to_words().split(' ').count(), 6);
+        assert!(fingerprint.randomart().starts_with("+--[SSSS SHARE]---+\n"));
+        assert!(fingerprint.randomart().ends_with("+----[SHA256]-----+\n"));
+    }
+
+    #[test]
+    fn fingerprint_ignores_encoding() -> Result<()> {
+        let config = SsssConfig::builder()
+            .encoding(Encoding::Bip39)
+            .parity(4)
+            .build();
+        let shares = gen_shares(&config, b"correct horse battery staple")?;
+        let expected = fingerprint(&shares[0])?;
+        assert_eq!(fingerprint(&reencode(&shares[0], &Hex)?)?, expected);
+        let paper = PaperShare::set(&config, &shares)?;
+        assert_eq!(fingerprint(&paper[0].to_text())?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn fingerprint_hides_the_share() -> Result<()> {
+        let shares = gen_shares(&SsssConfig::default(), b"correct horse battery staple")?;
+        let fingerprint = fingerprint(&shares[0])?;
+        let (_, bytes) = decode_share(shares[0].clone())?;
+        assert!(!hex::encode(&bytes).contains(&fingerprint.to_hex().replace(' ', "")));
+        Ok(())
+    }
+
+    #[test]
+    fn set_fingerprint_works() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        let shares = gen_shares(&SsssConfig::default(), secret)?;
+        let others = gen_shares(&SsssConfig::default(), secret)?;
+        let set = set_fingerprint(&shares)?;
+        assert_ne!(set, fingerprint(&shares[0])?);
+        assert_ne!(set, set_fingerprint(&others)?);
+        assert_ne!(set, set_fingerprint(&shares[1..])?);
+
+        let mut mixed = shares.clone();
+        mixed[0] = others[1].clone();
+        assert_ne!(set, set_fingerprint(&mixed)?);
+        Ok(())
+    }
+
+    #[test]
+    fn short_shares_error() -> Result<()> {
+        let shares = gen_shares(&SsssConfig::default(), b"12345678")?;
+        let message = "A share of 8 bytes is too short to fingerprint, it must be at least 16";
+        check_err_result(fingerprint(&shares[0]), message)?;
+        check_err_result(set_fingerprint(&shares), message)?;
+        let shares = gen_shares(&SsssConfig::default(), b"0123456789abcdef")?;
+        assert!(fingerprint(&shares[0]).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn bad_sets_error() -> Result<()> {
+        check_err_result(set_fingerprint(&[]), "The shares map cannot be empty")?;
+        let short = gen_shares(&SsssConfig::default(), b"correct horse")?;
+        let long = gen_shares(&SsssConfig::default(), b"correct horse battery staple")?;
+        let shares = [short[0].clone(), long[1].clone()];
+        check_err_result(
+            set_fingerprint(&shares),
+            "The shares must be the same length",
+        )
+    }
+}
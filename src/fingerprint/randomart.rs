@@ -0,0 +1,97 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` randomart, drawn with the "drunken bishop" walk of OpenSSH
+
+/// The width of the board
+const WIDTH: usize = 17;
+/// The height of the board
+const HEIGHT: usize = 9;
+/// The symbols for the number of visits to a square, followed by the symbols
+/// for the start and the end of the walk
+const SYMBOLS: &[u8] = b" .o+=*BOX@%&#/^SE";
+/// The most visits counted on a single square
+const MAX_VISITS: usize = SYMBOLS.len() - 3;
+
+/// Draw the randomart of the bytes, framed with the title at the top and the
+/// hash name at the bottom
+pub(crate) fn draw(bytes: &[u8], title: &str, hash: &str) -> String {
+    let mut board = [[0; WIDTH]; HEIGHT];
+    let (mut x, mut y) = (WIDTH / 2, HEIGHT / 2);
+    let start = (x, y);
+
+    // Each byte moves the bishop four times, diagonally, least significant
+    // bits first, sliding along the walls rather than leaving the board
+    for byte in bytes {
+        for step in 0..4 {
+            let bits = byte >> (2 * step);
+            x = if bits & 1 == 0 {
+                x.saturating_sub(1)
+            } else {
+                (x + 1).min(WIDTH - 1)
+            };
+            y = if bits & 2 == 0 {
+                y.saturating_sub(1)
+            } else {
+                (y + 1).min(HEIGHT - 1)
+            };
+            if board[y][x] < MAX_VISITS {
+                board[y][x] += 1;
+            }
+        }
+    }
+    board[start.1][start.0] = SYMBOLS.len() - 2;
+    board[y][x] = SYMBOLS.len() - 1;
+
+    let mut art = frame(title);
+    for row in board {
+        art.push('|');
+        art.extend(row.iter().map(|visits| char::from(SYMBOLS[*visits])));
+        art.push_str("|\n");
+    }
+    art.push_str(&frame(hash));
+    art
+}
+
+/// A horizontal edge of the frame with the label in the middle
+fn frame(label: &str) -> String {
+    let label = format!("[{label}]");
+    let left = WIDTH.saturating_sub(label.len()) / 2;
+    let right = WIDTH.saturating_sub(left + label.len());
+    format!("+{}{label}{}+\n", "-".repeat(left), "-".repeat(right))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{HEIGHT, WIDTH, draw};
+
+    #[test]
+    fn bishop_walks_from_the_center() {
+        // 0x00 walks up and to the left four times, 0xff walks back down and
+        // to the right, ending where it started
+        let art = draw(&[0x00, 0xff], "TEST", "SHA256");
+        let lines: Vec<&str> = art.lines().collect();
+        assert_eq!(lines.len(), HEIGHT + 2);
+        assert_eq!(lines[0], "+-----[TEST]------+");
+        assert_eq!(lines[HEIGHT + 1], "+----[SHA256]-----+");
+        assert!(lines.iter().all(|line| line.len() == WIDTH + 2));
+        assert_eq!(lines[1], "|    .            |");
+        assert_eq!(lines[2], "|     o           |");
+        assert_eq!(lines[3], "|      o          |");
+        assert_eq!(lines[4], "|       o         |");
+        assert_eq!(lines[5], "|        E        |");
+    }
+
+    #[test]
+    fn bishop_stays_on_the_board() {
+        let art = draw(&[0x00; 16], "TEST", "SHA256");
+        let lines: Vec<&str> = art.lines().collect();
+        assert_eq!(lines[1], "|E....            |");
+        assert_eq!(lines[5], "|        S        |");
+    }
+}
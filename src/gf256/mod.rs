@@ -7,94 +7,52 @@
 // modified, or distributed except according to those terms.
 
 //! `ssss` GF(2^8)
-
-mod constants;
-
-use self::constants::{EXP, LOG};
-use rand::Rng;
-
-pub(crate) fn generate_coeffs(d: u8, x: u8) -> Vec<u8> {
-    let mut rng = rand::rng();
-    let d_usize = usize::from(d);
-    let mut rand_bytes = vec![0; d_usize];
-
-    loop {
-        rng.fill_bytes(&mut rand_bytes);
-        if degree(&rand_bytes) == (d_usize - 1) {
-            break;
-        }
-    }
-
-    rand_bytes[0] = x;
-    rand_bytes
-}
-
-pub(crate) fn eval(p: &[u8], x: u8) -> u8 {
-    let mut result = 0;
-
-    for i in (0..p.len()).rev() {
-        result = add(mul(result, x), p[i]);
-    }
-    result
-}
-
-pub(crate) fn interpolate(points: &[Vec<u8>]) -> u8 {
-    let x = 0;
-    let mut y = 0;
-
-    for i in 0..points.len() {
-        let a_x = points[i][0];
-        let a_y = points[i][1];
-        let mut li = 1;
-
-        for (j, thing) in points.iter().enumerate() {
-            let b_x = thing[0];
-            if i != j {
-                li = mul(li, div(sub(x, b_x), sub(a_x, b_x)));
-            }
-        }
-        y = add(y, mul(li, a_y));
-    }
-
-    y
+//!
+//! The crate splits secrets over the field reduced by `0x11b`, with generator
+//! 3.  See [`Poly11b`].
+
+pub(crate) mod reed_solomon;
+mod tables;
+
+pub use self::tables::{Gf256Params, Gf256Tables, Poly11b, Poly11d};
+use crate::field::{Gf256, Polynomial};
+
+/// The tables of the field the crate splits secrets over
+const TABLES: &Gf256Tables = Poly11b::TABLES;
+
+/// Evaluate the polynomial passing through the given `[x, y]` points at `x`
+pub(crate) fn interpolate_at(points: &[Vec<u8>], x: u8) -> u8 {
+    let points: Vec<(Gf256, Gf256)> = points
+        .iter()
+        .map(|point| (Gf256(point[0]), Gf256(point[1])))
+        .collect();
+    Polynomial::interpolate(&points, &Gf256(x)).map_or(0, |y| y.0)
 }
 
-fn degree(p: &[u8]) -> usize {
-    for i in (1..p.len()).rev() {
-        if p[i] != 0 {
-            return i;
-        }
-    }
-    0
+pub(crate) fn mul(a: u8, b: u8) -> u8 {
+    TABLES.mul(a, b)
 }
 
-fn mul(a: u8, b: u8) -> u8 {
-    if a == 0 || b == 0 {
-        0
-    } else {
-        let a = usize::from(a);
-        let b = usize::from(b);
-        let left = usize::from(LOG[a]);
-        let right = usize::from(LOG[b]);
-        EXP[left + right]
-    }
+pub(crate) fn div(a: u8, b: u8) -> u8 {
+    TABLES.div(a, b)
 }
 
-fn div(a: u8, b: u8) -> u8 {
-    mul(a, EXP[255_usize - usize::from(LOG[usize::from(b)])])
+pub(crate) fn add(a: u8, b: u8) -> u8 {
+    a ^ b
 }
 
-fn add(a: u8, b: u8) -> u8 {
-    a ^ b
+/// The generator of the field raised to the given power
+pub(crate) fn exp(power: usize) -> u8 {
+    TABLES.exp(power)
 }
 
-fn sub(a: u8, b: u8) -> u8 {
+pub(crate) fn sub(a: u8, b: u8) -> u8 {
     add(a, b)
 }
 
 #[cfg(test)]
 mod test {
-    use super::{add, degree, div, eval, generate_coeffs, interpolate, mul, sub};
+    use super::{Gf256, Polynomial, add, div, interpolate_at, mul, sub};
 
     #[test]
     fn add_works() {
@@ -168,30 +126,15 @@ mod test {
     }
 
     #[test]
-    fn degree_works() {
-        assert_eq!(degree(&[1, 2]), 1);
-        assert_eq!(degree(&[1, 2, 0]), 1);
-        assert_eq!(degree(&[1, 2, 3]), 2);
-        assert_eq!(degree(&[4]), 0);
-    }
-
-    #[test]
-    fn eval_works() {
-        assert_eq!(eval(&[1, 0, 2, 3], 2), 17);
-    }
-
-    #[test]
-    fn generate_works() {
-        let p = generate_coeffs(5, 20);
-        assert_eq!(p[0], 20);
-        // assert_eq!(p.len(), 6);
-        assert!(p[p.len() - 1] != 0);
-    }
-
-    #[test]
-    fn interpolate_works() {
-        assert_eq!(interpolate(&[vec![1, 1], vec![2, 2], vec![3, 3]]), 0);
-        assert_eq!(interpolate(&[vec![1, 80], vec![2, 90], vec![3, 20]]), 30);
-        assert_eq!(interpolate(&[vec![1, 43], vec![2, 22], vec![3, 86]]), 107);
+    fn interpolate_at_works() {
+        let p = Polynomial::new(vec![Gf256(7), Gf256(0), Gf256(2), Gf256(3)]);
+        let points: Vec<Vec<u8>> = [1, 2, 3, 4]
+            .iter()
+            .map(|x| vec![*x, p.eval(&Gf256(*x)).0])
+            .collect();
+        for x in [0, 5, 200, 254, 255] {
+            assert_eq!(interpolate_at(&points, x), p.eval(&Gf256(x)).0);
+        }
+        assert_eq!(interpolate_at(&points, 2), points[1][1]);
     }
 }
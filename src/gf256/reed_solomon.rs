@@ -0,0 +1,316 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` Reed–Solomon codes over GF(2^8)
+//!
+//! A systematic code with `p` parity bytes, whose generator polynomial has
+//! the roots `a^0 .. a^(p-1)` for the generator `a` of the field.  A block can
+//! hold at most 255 bytes, so longer data is split across several blocks,
+//! interleaved byte by byte so that a run of damaged bytes is spread over all
+//! of them.
+//!
+//! Polynomials here are stored with the highest degree coefficient first, the
+//! order in which the bytes of a block are written.
+
+use super::{add, div, exp, mul};
+use crate::error::SsssError::{InvalidShareFormat, Unrepairable};
+use anyhow::Result;
+
+/// The maximum length of a block, data and parity bytes included
+const BLOCK_LEN: usize = 255;
+
+/// Append the interleaved parity bytes of `parity` bytes per block to the data
+pub(crate) fn protect(data: &[u8], parity: u8) -> Vec<u8> {
+    let parity = usize::from(parity);
+    let blocks = data.len().div_ceil(BLOCK_LEN - parity).max(1);
+    let generator = generator(parity);
+
+    let mut codeword = data.to_vec();
+    codeword.resize(data.len() + parity * blocks, 0);
+    for block in 0..blocks {
+        let message: Vec<u8> = data.iter().skip(block).step_by(blocks).copied().collect();
+        for (k, byte) in remainder(&message, &generator).into_iter().enumerate() {
+            codeword[data.len() + block + k * blocks] = byte;
+        }
+    }
+    codeword
+}
+
+/// Correct the errors in a codeword produced by [`protect`], returning the
+/// data without its parity.
+///
+/// Bytes that are `None` are erased.  Each block can restore as many erased
+/// bytes as it has parity bytes, or correct half as many bytes that are
+/// wrong, or any combination where twice the errors plus the erasures is at
+/// most the parity.
+pub(crate) fn correct(codeword: &[Option<u8>], parity: u8) -> Result<Vec<u8>> {
+    let parity = usize::from(parity);
+    let blocks = codeword.len().div_ceil(BLOCK_LEN);
+    let data_len = codeword
+        .len()
+        .checked_sub(parity * blocks)
+        .filter(|len| *len > 0)
+        .ok_or(InvalidShareFormat)?;
+
+    let mut data = vec![0; data_len];
+    for block in 0..blocks {
+        let positions: Vec<usize> = (block..data_len)
+            .step_by(blocks)
+            .chain((0..parity).map(|k| data_len + block + k * blocks))
+            .collect();
+        let received: Vec<Option<u8>> = positions.iter().map(|pos| codeword[*pos]).collect();
+        let corrected = correct_block(&received, parity)?;
+        for (pos, byte) in positions
+            .iter()
+            .zip(corrected)
+            .take_while(|(pos, _)| **pos < data_len)
+        {
+            data[*pos] = byte;
+        }
+    }
+    Ok(data)
+}
+
+/// The generator polynomial `(x - a^0) .. (x - a^(p-1))`
+fn generator(parity: usize) -> Vec<u8> {
+    (0..parity).fold(vec![1], |generator, i| poly_mul(&generator, &[1, exp(i)]))
+}
+
+/// The remainder of `message * x^p` divided by the generator, i.e. the parity
+fn remainder(message: &[u8], generator: &[u8]) -> Vec<u8> {
+    let parity = generator.len() - 1;
+    let mut buf = message.to_vec();
+    buf.resize(message.len() + parity, 0);
+    for i in 0..message.len() {
+        let coeff = buf[i];
+        if coeff != 0 {
+            for (j, g) in generator.iter().enumerate().skip(1) {
+                buf[i + j] = add(buf[i + j], mul(*g, coeff));
+            }
+        }
+    }
+    buf.split_off(message.len())
+}
+
+/// Correct a single block with errors and erasures
+fn correct_block(received: &[Option<u8>], parity: usize) -> Result<Vec<u8>> {
+    let erasures: Vec<usize> = received
+        .iter()
+        .enumerate()
+        .filter(|(_, byte)| byte.is_none())
+        .map(|(pos, _)| pos)
+        .collect();
+    if erasures.len() > parity {
+        return Err(Unrepairable.into());
+    }
+    let mut block: Vec<u8> = received.iter().map(|byte| byte.unwrap_or(0)).collect();
+
+    let received = syndromes(&block, parity);
+    if received.iter().all(|s| *s == 0) {
+        return Ok(block);
+    }
+
+    let forney = forney_syndromes(&received, &erasures, block.len());
+    let locator = error_locator(&forney, parity, erasures.len())?;
+    let mut errata = erasures;
+    errata.extend(find_errors(&locator, block.len())?);
+    correct_errata(&mut block, &received, &errata);
+
+    if syndromes(&block, parity).iter().all(|s| *s == 0) {
+        Ok(block)
+    } else {
+        Err(Unrepairable.into())
+    }
+}
+
+/// The block evaluated at each root of the generator
+fn syndromes(block: &[u8], parity: usize) -> Vec<u8> {
+    (0..parity).map(|i| poly_eval(block, exp(i))).collect()
+}
+
+/// Remove the known erasures from the syndromes, leaving only the errors
+fn forney_syndromes(syndromes: &[u8], erasures: &[usize], len: usize) -> Vec<u8> {
+    let mut forney = syndromes.to_vec();
+    for pos in erasures {
+        let x = exp(len - 1 - pos);
+        for j in 0..forney.len().saturating_sub(1) {
+            forney[j] = add(mul(forney[j], x), forney[j + 1]);
+        }
+    }
+    forney
+}
+
+/// Find the error locator polynomial with the Berlekamp–Massey algorithm
+fn error_locator(syndromes: &[u8], parity: usize, erasures: usize) -> Result<Vec<u8>> {
+    let mut locator = vec![1];
+    let mut old = vec![1];
+    for k in 0..parity - erasures {
+        let mut delta = syndromes[k];
+        for j in 1..locator.len() {
+            delta = add(delta, mul(locator[locator.len() - 1 - j], syndromes[k - j]));
+        }
+        old.push(0);
+        if delta != 0 {
+            if old.len() > locator.len() {
+                let scaled = poly_scale(&old, delta);
+                old = poly_scale(&locator, div(1, delta));
+                locator = scaled;
+            }
+            locator = poly_add(&locator, &poly_scale(&old, delta));
+        }
+    }
+    let leading = locator.iter().take_while(|c| **c == 0).count();
+    let locator = locator.split_off(leading);
+    if 2 * (locator.len() - 1) + erasures > parity {
+        Err(Unrepairable.into())
+    } else {
+        Ok(locator)
+    }
+}
+
+/// Find the positions of the errors from the roots of the locator
+fn find_errors(locator: &[u8], len: usize) -> Result<Vec<usize>> {
+    let reversed: Vec<u8> = locator.iter().rev().copied().collect();
+    let errors: Vec<usize> = (0..len)
+        .filter(|i| poly_eval(&reversed, exp(*i)) == 0)
+        .map(|i| len - 1 - i)
+        .collect();
+    if errors.len() == locator.len() - 1 {
+        Ok(errors)
+    } else {
+        Err(Unrepairable.into())
+    }
+}
+
+/// Compute the error magnitudes at the given positions with the Forney
+/// algorithm, and remove them from the block
+fn correct_errata(block: &mut [u8], syndromes: &[u8], errata: &[usize]) {
+    let powers: Vec<usize> = errata.iter().map(|pos| block.len() - 1 - pos).collect();
+    let locator = powers.iter().fold(vec![1], |locator, power| {
+        poly_mul(&locator, &[exp(*power), 1])
+    });
+
+    // The evaluator is the product of the syndromes and the locator, modulo
+    // x^(errata + 1)
+    let reversed: Vec<u8> = syndromes.iter().rev().chain([&0]).copied().collect();
+    let product = poly_mul(&reversed, &locator);
+    let evaluator = &product[product.len().saturating_sub(locator.len())..];
+
+    let xs: Vec<u8> = powers.iter().map(|power| exp(*power)).collect();
+    for (i, (x, pos)) in xs.iter().zip(errata).enumerate() {
+        let x_inv = div(1, *x);
+        let derivative = xs
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .fold(1, |acc, (_, xj)| mul(acc, add(1, mul(x_inv, *xj))));
+        let y = mul(*x, poly_eval(evaluator, x_inv));
+        block[*pos] = add(block[*pos], div(y, derivative));
+    }
+}
+
+fn poly_eval(poly: &[u8], x: u8) -> u8 {
+    poly.iter().fold(0, |acc, c| add(mul(acc, x), *c))
+}
+
+fn poly_add(p: &[u8], q: &[u8]) -> Vec<u8> {
+    let len = p.len().max(q.len());
+    let mut result = vec![0; len];
+    for (i, c) in p.iter().enumerate() {
+        result[i + len - p.len()] = *c;
+    }
+    for (i, c) in q.iter().enumerate() {
+        result[i + len - q.len()] = add(result[i + len - q.len()], *c);
+    }
+    result
+}
+
+fn poly_mul(p: &[u8], q: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; p.len() + q.len() - 1];
+    for (i, a) in p.iter().enumerate() {
+        for (j, b) in q.iter().enumerate() {
+            result[i + j] = add(result[i + j], mul(*a, *b));
+        }
+    }
+    result
+}
+
+fn poly_scale(p: &[u8], x: u8) -> Vec<u8> {
+    p.iter().map(|c| mul(*c, x)).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{correct, protect, syndromes};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+    use rand::{RngExt, rng, seq::index::sample};
+
+    fn received(codeword: &[u8]) -> Vec<Option<u8>> {
+        codeword.iter().copied().map(Some).collect()
+    }
+
+    #[test]
+    fn codewords_have_zero_syndromes() {
+        let codeword = protect(b"correct horse battery staple", 8);
+        assert_eq!(codeword.len(), 36);
+        assert_eq!(&codeword[..28], b"correct horse battery staple");
+        assert!(syndromes(&codeword, 8).iter().all(|s| *s == 0));
+    }
+
+    #[test]
+    fn errors_and_erasures_are_corrected() -> Result<()> {
+        let data = b"correct horse battery staple";
+        let codeword = protect(data, 8);
+        let mut rng = rng();
+        for (errors, erasures) in [(0, 0), (4, 0), (0, 8), (1, 6), (3, 2)] {
+            for _ in 0..32 {
+                let mut damaged = received(&codeword);
+                let positions = sample(&mut rng, codeword.len(), errors + erasures);
+                for (i, pos) in positions.iter().enumerate() {
+                    damaged[pos] = if i < errors {
+                        damaged[pos].map(|byte| byte ^ rng.random_range(1..=255))
+                    } else {
+                        None
+                    };
+                }
+                assert_eq!(correct(&damaged, 8)?, data);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn long_data_is_interleaved() -> Result<()> {
+        let data: Vec<u8> = (0..=255).cycle().take(1000).collect();
+        let codeword = protect(&data, 16);
+        // 1000 bytes need 5 blocks of at most 239 data bytes each
+        assert_eq!(codeword.len(), 1080);
+
+        // Runs of damaged bytes are spread across the blocks, leaving each
+        // with six erased data bytes, two erased parity bytes and two errors
+        let mut damaged = received(&codeword);
+        damaged[100..130].fill(None);
+        damaged[500..510].fill(Some(0));
+        damaged[1070..].fill(None);
+        assert_eq!(correct(&damaged, 16)?, data);
+        Ok(())
+    }
+
+    #[test]
+    fn too_much_damage_errors() -> Result<()> {
+        let codeword = protect(b"correct horse battery staple", 4);
+        let mut damaged = received(&codeword);
+        damaged[..5].fill(None);
+        check_err_result(
+            correct(&damaged, 4),
+            "The share is too damaged to be repaired",
+        )?;
+        check_err_result(correct(&damaged[..4], 4), "Invalid share format")
+    }
+}
@@ -0,0 +1,215 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` GF(2^8) tables
+//!
+//! The log and exp tables of a GF(2^8) are computed at compile time from its
+//! reduction polynomial and generator, so that shares split over the fields
+//! used by other implementations can be combined.
+
+use std::fmt::Debug;
+
+/// The number of non-zero elements, the order of a generator
+const ORDER: usize = 255;
+
+/// The log and exp tables of GF(2^8) for a reduction polynomial and
+/// generator.  The exp table is doubled, so the sum of two logs can be looked
+/// up without reducing it first.
+///
+/// # Example
+/// ```
+/// # use ssss::Gf256Tables;
+/// #
+/// const TABLES: Gf256Tables = Gf256Tables::new(0x11d, 2);
+///
+/// assert_eq!(TABLES.mul(0x80, 2), 0x1d);
+/// assert_eq!(TABLES.div(TABLES.mul(0x57, 0x83), 0x83), 0x57);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Gf256Tables {
+    polynomial: u16,
+    generator: u8,
+    log: [u8; ORDER + 1],
+    exp: [u8; 2 * ORDER],
+}
+
+impl Gf256Tables {
+    /// Compute the tables of the field reduced by `polynomial`, a degree 8
+    /// polynomial over GF(2) written as its bits, e.g. `0x11b` for
+    /// `x^8 + x^4 + x^3 + x + 1`.
+    ///
+    /// # Panics
+    /// * This function panics, or fails to compile in a `const`, if the
+    ///   polynomial is not of degree 8, or the generator does not generate
+    ///   every non-zero element.  The latter is the case for every generator
+    ///   if the polynomial is not irreducible.
+    #[must_use]
+    pub const fn new(polynomial: u16, generator: u8) -> Self {
+        assert!(
+            polynomial >> 8 == 1,
+            "The reduction polynomial must have degree 8"
+        );
+        let mut tables = Gf256Tables {
+            polynomial,
+            generator,
+            log: [0; ORDER + 1],
+            exp: [0; 2 * ORDER],
+        };
+        let mut seen = [false; ORDER + 1];
+        let mut x = 1;
+        let mut i = 0;
+        while i < ORDER {
+            assert!(
+                x != 0 && !seen[x as usize],
+                "The generator must generate every non-zero element"
+            );
+            seen[x as usize] = true;
+            tables.exp[i] = x;
+            tables.exp[i + ORDER] = x;
+            // `i` is below 255
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                tables.log[x as usize] = i as u8;
+            }
+            x = slow_mul(x, generator, polynomial);
+            i += 1;
+        }
+        tables
+    }
+
+    /// The reduction polynomial
+    #[must_use]
+    pub const fn polynomial(&self) -> u16 {
+        self.polynomial
+    }
+
+    /// The generator
+    #[must_use]
+    pub const fn generator(&self) -> u8 {
+        self.generator
+    }
+
+    /// The product of two elements
+    #[must_use]
+    pub const fn mul(&self, a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            0
+        } else {
+            self.exp[self.log[a as usize] as usize + self.log[b as usize] as usize]
+        }
+    }
+
+    /// The quotient of two elements.  Dividing by zero gives the dividend.
+    #[must_use]
+    pub const fn div(&self, a: u8, b: u8) -> u8 {
+        self.mul(a, self.exp[ORDER - self.log[b as usize] as usize])
+    }
+
+    /// The generator raised to the given power
+    #[must_use]
+    pub const fn exp(&self, power: usize) -> u8 {
+        self.exp[power % ORDER]
+    }
+}
+
+/// Multiply two elements bit by bit, reducing by the polynomial
+const fn slow_mul(mut a: u8, mut b: u8, polynomial: u16) -> u8 {
+    let mut product = 0;
+    while b != 0 {
+        if b & 1 == 1 {
+            product ^= a;
+        }
+        b >>= 1;
+        let carry = a & 0x80 != 0;
+        a <<= 1;
+        if carry {
+            // Only the low byte of the polynomial is left after the shift
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                a ^= polynomial as u8;
+            }
+        }
+    }
+    product
+}
+
+/// The parameters of a GF(2^8) for [`Gf2_8`](crate::Gf2_8)
+pub trait Gf256Params {
+    /// The tables of the field
+    const TABLES: &'static Gf256Tables;
+}
+
+/// The field reduced by `x^8 + x^4 + x^3 + x + 1` (`0x11b`), as used by AES,
+/// with generator 3.  This is the field of [`Gf256`](crate::Gf256) and of the
+/// shares made by [`gen_shares`](crate::gen_shares).
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Poly11b;
+
+impl Gf256Params for Poly11b {
+    const TABLES: &'static Gf256Tables = &Gf256Tables::new(0x11b, 3);
+}
+
+/// The field reduced by `x^8 + x^4 + x^3 + x^2 + 1` (`0x11d`), as used by
+/// many Reed-Solomon codes, with generator 2
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Poly11d;
+
+impl Gf256Params for Poly11d {
+    const TABLES: &'static Gf256Tables = &Gf256Tables::new(0x11d, 2);
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Gf256Params, Gf256Tables, Poly11b, Poly11d, slow_mul};
+
+    #[test]
+    fn tables_match_slow_mul() {
+        for tables in [Poly11b::TABLES, Poly11d::TABLES] {
+            for a in 0..=u8::MAX {
+                for b in 0..=u8::MAX {
+                    assert_eq!(tables.mul(a, b), slow_mul(a, b, tables.polynomial()));
+                    if b != 0 {
+                        assert_eq!(tables.div(tables.mul(a, b), b), a);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn aes_tables_are_unchanged() {
+        let tables = Poly11b::TABLES;
+        assert_eq!(
+            &tables.log[1..8],
+            [0x00, 0x19, 0x01, 0x32, 0x02, 0x1a, 0xc6]
+        );
+        assert_eq!(
+            &tables.exp[..8],
+            [0x01, 0x03, 0x05, 0x0f, 0x11, 0x33, 0x55, 0xff]
+        );
+        assert_eq!(tables.exp(255), 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "The generator must generate every non-zero element")]
+    fn bad_generator_panics() {
+        let _tables = Gf256Tables::new(0x11b, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "The generator must generate every non-zero element")]
+    fn reducible_polynomial_panics() {
+        let _tables = Gf256Tables::new(0x100, 3);
+    }
+
+    #[test]
+    #[should_panic(expected = "The reduction polynomial must have degree 8")]
+    fn short_polynomial_panics() {
+        let _tables = Gf256Tables::new(0x1b, 3);
+    }
+}
@@ -0,0 +1,154 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` GF(2^16)
+//!
+//! The field is built on the primitive polynomial
+//! `x^16 + x^12 + x^3 + x + 1`, with `x` as the generator.  The log and exp
+//! tables are computed at compile time.
+
+/// The reduction polynomial, `x^16 + x^12 + x^3 + x + 1`
+const POLYNOMIAL: u32 = 0x1_100b;
+/// The number of non-zero elements, the order of the generator
+const ORDER: usize = 65535;
+
+/// The log and exp tables of the field.  The exp table is doubled, so the sum
+/// of two logs can be looked up without reducing it first.
+struct Tables {
+    log: [u16; ORDER + 1],
+    exp: [u16; 2 * ORDER],
+}
+
+impl Tables {
+    // Only evaluated at compile time, to initialize the static tables
+    #[allow(clippy::large_stack_arrays)]
+    const fn new() -> Self {
+        let mut tables = Tables {
+            log: [0; ORDER + 1],
+            exp: [0; 2 * ORDER],
+        };
+        let mut x: u32 = 1;
+        let mut i = 0;
+        while i < ORDER {
+            // `x` never exceeds 16 bits after the reduction below
+            #[allow(clippy::cast_possible_truncation)]
+            let element = x as u16;
+            tables.exp[i] = element;
+            tables.exp[i + ORDER] = element;
+            #[allow(clippy::cast_possible_truncation)]
+            {
+                tables.log[x as usize] = i as u16;
+            }
+            x <<= 1;
+            if x & 0x1_0000 != 0 {
+                x ^= POLYNOMIAL;
+            }
+            i += 1;
+        }
+        tables
+    }
+}
+
+static TABLES: Tables = Tables::new();
+
+pub(crate) fn mul(a: u16, b: u16) -> u16 {
+    if a == 0 || b == 0 {
+        0
+    } else {
+        let left = usize::from(TABLES.log[usize::from(a)]);
+        let right = usize::from(TABLES.log[usize::from(b)]);
+        TABLES.exp[left + right]
+    }
+}
+
+pub(crate) fn div(a: u16, b: u16) -> u16 {
+    mul(
+        a,
+        TABLES.exp[ORDER - usize::from(TABLES.log[usize::from(b)])],
+    )
+}
+
+pub(crate) fn add(a: u16, b: u16) -> u16 {
+    a ^ b
+}
+
+/// The generator `x` raised to the given power
+pub(crate) fn exp(power: usize) -> u16 {
+    TABLES.exp[power % ORDER]
+}
+
+/// The power of the generator `x` that gives the element, if it is non-zero
+pub(crate) fn log(a: u16) -> Option<usize> {
+    (a != 0).then(|| usize::from(TABLES.log[usize::from(a)]))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ORDER, TABLES, add, div, exp, log, mul};
+    use crate::field::{Gf65536, Polynomial};
+
+    #[test]
+    fn generator_is_primitive() {
+        let mut seen = vec![false; ORDER + 1];
+        for element in &TABLES.exp[..ORDER] {
+            assert!(!seen[usize::from(*element)]);
+            seen[usize::from(*element)] = true;
+        }
+        assert!(!seen[0]);
+    }
+
+    #[test]
+    fn mul_works() {
+        assert_eq!(mul(0x8000, 2), 0x100b);
+        assert_eq!(mul(0x1234, 1), 0x1234);
+        assert_eq!(mul(0, 0x1234), 0);
+        assert_eq!(mul(0x1234, 0x5678), mul(0x5678, 0x1234));
+    }
+
+    #[test]
+    fn div_is_inverse_of_mul() {
+        for i in (0..=u16::MAX).step_by(97) {
+            for j in (1..=u16::MAX).step_by(89) {
+                assert_eq!(div(mul(i, j), j), i);
+                assert_eq!(mul(div(i, j), j), i);
+            }
+        }
+    }
+
+    #[test]
+    fn log_is_inverse_of_exp() {
+        assert_eq!(exp(0), 1);
+        assert_eq!(exp(1), 2);
+        assert_eq!(exp(ORDER + 5), exp(5));
+        assert_eq!(log(0), None);
+        for power in (0..ORDER).step_by(101) {
+            assert_eq!(log(exp(power)), Some(power));
+        }
+    }
+
+    #[test]
+    fn mul_distributes_over_add() {
+        for (a, b, c) in [(3, 7, 11), (0x1234, 0xabcd, 0xffff), (0x8000, 0x8000, 2)] {
+            assert_eq!(mul(a, add(b, c)), add(mul(a, b), mul(a, c)));
+        }
+    }
+
+    #[test]
+    fn interpolate_works() {
+        let p = Polynomial::random(&mut rand::rng(), 2, Gf65536(0xbeef));
+        assert_eq!(p.degree(), 2);
+        let points: Vec<(Gf65536, Gf65536)> = [1, 300, 4000]
+            .into_iter()
+            .map(|x| (Gf65536(x), p.eval(&Gf65536(x))))
+            .collect();
+        assert_eq!(
+            Polynomial::interpolate(&points, &Gf65536(0)),
+            Some(Gf65536(0xbeef))
+        );
+    }
+}
@@ -0,0 +1,114 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` legacy diffusion layer
+//!
+//! Before the secret is split, `ssss` scrambles it with many rounds of XTEA,
+//! with an all-zero key, over overlapping 8-byte windows of the secret.  Every
+//! bit of the secret then depends on every other, so a partial secret
+//! recovered from too few shares says nothing about any part of the real one.
+
+use num_bigint::BigUint;
+
+/// The XTEA round constant
+const DELTA: u32 = 0x9e37_79b9;
+/// The number of XTEA cycles
+const CYCLES: u32 = 32;
+
+fn encipher_block(v: &mut [u32; 2]) {
+    let mut sum: u32 = 0;
+    for _ in 0..CYCLES {
+        v[0] = v[0].wrapping_add((((v[1] << 4) ^ (v[1] >> 5)).wrapping_add(v[1])) ^ sum);
+        sum = sum.wrapping_add(DELTA);
+        v[1] = v[1].wrapping_add((((v[0] << 4) ^ (v[0] >> 5)).wrapping_add(v[0])) ^ sum);
+    }
+}
+
+fn decipher_block(v: &mut [u32; 2]) {
+    let mut sum = DELTA.wrapping_mul(CYCLES);
+    for _ in 0..CYCLES {
+        v[1] = v[1].wrapping_sub((((v[0] << 4) ^ (v[0] >> 5)).wrapping_add(v[0])) ^ sum);
+        sum = sum.wrapping_sub(DELTA);
+        v[0] = v[0].wrapping_sub((((v[1] << 4) ^ (v[1] >> 5)).wrapping_add(v[1])) ^ sum);
+    }
+}
+
+/// Run the block function over the 8 bytes at `idx`, wrapping around the end
+fn process_slice(data: &mut [u8], idx: usize, process: fn(&mut [u32; 2])) {
+    let len = data.len();
+    let mut v = [0_u32; 2];
+    for (i, word) in v.iter_mut().enumerate() {
+        let bytes: [u8; 4] = std::array::from_fn(|k| data[(idx + 4 * i + k) % len]);
+        *word = u32::from_be_bytes(bytes);
+    }
+    process(&mut v);
+    for (i, word) in v.iter().enumerate() {
+        for (k, byte) in word.to_be_bytes().into_iter().enumerate() {
+            data[(idx + 4 * i + k) % len] = byte;
+        }
+    }
+}
+
+/// Apply the diffusion layer to an element of the field of the given degree,
+/// or undo it
+pub(crate) fn diffuse(x: &BigUint, degree: usize, undo: bool) -> BigUint {
+    // `ssss` exports the element as 16-bit words, least significant first,
+    // and moves the low byte of a half-filled top word next to the others
+    let len = degree / 8;
+    let mut bytes: Vec<u8> = x
+        .to_u64_digits()
+        .iter()
+        .flat_map(|digit| digit.to_le_bytes())
+        .chain(std::iter::repeat(0))
+        .take((degree + 8) / 16 * 2)
+        .collect::<Vec<u8>>()
+        .chunks(2)
+        .flat_map(|word| [word[1], word[0]])
+        .collect();
+    if degree % 16 == 8 {
+        bytes[len - 1] = bytes[len];
+    }
+
+    let rounds = 40 * len;
+    if undo {
+        for idx in (0..rounds).step_by(2).rev() {
+            process_slice(&mut bytes[..len], idx, decipher_block);
+        }
+    } else {
+        for idx in (0..rounds).step_by(2) {
+            process_slice(&mut bytes[..len], idx, encipher_block);
+        }
+    }
+
+    if degree % 16 == 8 {
+        bytes[len] = bytes[len - 1];
+        bytes[len - 1] = 0;
+    }
+    let le: Vec<u8> = bytes
+        .chunks(2)
+        .flat_map(|word| [word[1], word[0]])
+        .collect();
+    BigUint::from_bytes_le(&le)
+}
+
+#[cfg(test)]
+mod test {
+    use super::diffuse;
+    use num_bigint::BigUint;
+
+    #[test]
+    fn diffusion_roundtrips() {
+        for degree in [64, 72, 128, 184, 1024] {
+            let x = BigUint::from_bytes_be(b"hunter2");
+            let diffused = diffuse(&x, degree, false);
+            assert_ne!(diffused, x);
+            assert!(diffused.bits() <= u64::try_from(degree).unwrap_or(u64::MAX));
+            assert_eq!(diffuse(&diffused, degree, true), x);
+        }
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` legacy GF(2^n)
+//!
+//! `ssss` works in GF(2^n) for every `n` that is a multiple of 8 up to 1024,
+//! reduced by the pentanomial `x^n + x^a + x^b + x^c + 1` listed for `n` in
+//! its table of irreducible polynomials.
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+/// The smallest field degree
+pub(crate) const MIN_DEGREE: usize = 8;
+/// The largest field degree
+pub(crate) const MAX_DEGREE: usize = 1024;
+
+/// The exponents `a`, `b` and `c` of the reduction polynomial of each degree
+/// from 8 to 1024, three at a time, as listed by `ssss`
+const IRREDUCIBLE: [u8; 3 * MAX_DEGREE / 8] = [
+    4, 3, 1, 5, 3, 1, 4, 3, 1, 7, 3, 2, 5, 4, 3, 5, 3, 2, 7, 4, 2, 4, 3, 1, 10, 9, 3, 9, 4, 2, 7,
+    6, 2, 10, 9, 6, 4, 3, 1, 5, 4, 3, 4, 3, 1, 7, 2, 1, 5, 3, 2, 7, 4, 2, 6, 3, 2, 5, 3, 2, 15, 3,
+    2, 11, 3, 2, 9, 8, 7, 7, 2, 1, 5, 3, 2, 9, 3, 1, 7, 3, 1, 9, 8, 3, 9, 4, 2, 8, 5, 3, 15, 14,
+    10, 10, 5, 2, 9, 6, 2, 9, 3, 2, 9, 5, 2, 11, 10, 1, 7, 3, 2, 11, 2, 1, 9, 7, 4, 4, 3, 1, 8, 3,
+    1, 7, 4, 1, 7, 2, 1, 13, 11, 6, 5, 3, 2, 7, 3, 2, 8, 7, 5, 12, 3, 2, 13, 10, 6, 5, 3, 2, 5, 3,
+    2, 9, 5, 2, 9, 7, 2, 13, 4, 3, 4, 3, 1, 11, 6, 4, 18, 9, 6, 19, 18, 13, 11, 3, 2, 15, 9, 6, 4,
+    3, 1, 16, 5, 2, 15, 14, 6, 8, 5, 2, 15, 11, 2, 11, 6, 2, 7, 5, 3, 8, 3, 1, 19, 16, 9, 11, 9, 6,
+    15, 7, 6, 13, 4, 3, 14, 13, 3, 13, 6, 3, 9, 5, 2, 19, 13, 6, 19, 10, 3, 11, 6, 5, 9, 2, 1, 14,
+    3, 2, 13, 3, 1, 7, 5, 4, 11, 9, 8, 11, 6, 5, 23, 16, 9, 19, 14, 6, 23, 10, 2, 8, 3, 2, 5, 4, 3,
+    9, 6, 4, 4, 3, 2, 13, 8, 6, 13, 11, 1, 13, 10, 3, 11, 6, 5, 19, 17, 4, 15, 14, 7, 13, 9, 6, 9,
+    7, 3, 9, 7, 1, 14, 3, 2, 11, 8, 2, 11, 6, 4, 13, 5, 2, 11, 5, 1, 11, 4, 1, 19, 10, 3, 21, 10,
+    6, 13, 3, 1, 15, 7, 5, 19, 18, 10, 7, 5, 3, 12, 7, 2, 7, 5, 1, 14, 9, 6, 10, 3, 2, 15, 13, 12,
+    12, 11, 9, 16, 9, 7, 12, 9, 3, 9, 5, 2, 17, 10, 6, 24, 9, 3, 17, 15, 13, 5, 4, 3, 19, 17, 8,
+    15, 6, 3, 19, 6, 1,
+];
+
+/// Whether `ssss` supports a field of the given degree
+pub(crate) fn valid_degree(degree: usize) -> bool {
+    degree % 8 == 0 && (MIN_DEGREE..=MAX_DEGREE).contains(&degree)
+}
+
+/// GF(2^n), with elements as the integers whose bit `i` is the coefficient of
+/// `x^i`
+#[derive(Clone, Debug)]
+pub(crate) struct Gf2n {
+    degree: u64,
+    polynomial: BigUint,
+}
+
+impl Gf2n {
+    /// The field of the given degree, which must be [valid](valid_degree)
+    pub(crate) fn new(degree: usize) -> Self {
+        let mut polynomial = BigUint::one();
+        for exponent in &IRREDUCIBLE[3 * (degree / 8 - 1)..3 * (degree / 8)] {
+            polynomial.set_bit(u64::from(*exponent), true);
+        }
+        let degree = u64::try_from(degree).unwrap_or(u64::MAX);
+        polynomial.set_bit(degree, true);
+        Gf2n { degree, polynomial }
+    }
+
+    pub(crate) fn add(a: &BigUint, b: &BigUint) -> BigUint {
+        a ^ b
+    }
+
+    pub(crate) fn mul(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        let mut a = a.clone();
+        let mut product = BigUint::zero();
+        for i in 0..self.degree {
+            if b.bit(i) {
+                product ^= &a;
+            }
+            a <<= 1_u8;
+            if a.bit(self.degree) {
+                a ^= &self.polynomial;
+            }
+        }
+        product
+    }
+
+    /// The multiplicative inverse, by the extended Euclidean algorithm, or
+    /// `None` for zero
+    pub(crate) fn inv(&self, a: &BigUint) -> Option<BigUint> {
+        if a.is_zero() {
+            return None;
+        }
+        let (mut u, mut v) = (a.clone(), self.polynomial.clone());
+        let (mut g1, mut g2) = (BigUint::one(), BigUint::zero());
+        while !u.is_one() {
+            if u.bits() < v.bits() {
+                std::mem::swap(&mut u, &mut v);
+                std::mem::swap(&mut g1, &mut g2);
+            }
+            let shift = u.bits() - v.bits();
+            u ^= &v << shift;
+            g1 ^= &g2 << shift;
+        }
+        Some(g1)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Gf2n, MAX_DEGREE, MIN_DEGREE, valid_degree};
+    use num_bigint::BigUint;
+    use num_traits::One;
+
+    #[test]
+    fn degrees_are_valid() {
+        assert!(valid_degree(MIN_DEGREE));
+        assert!(valid_degree(184));
+        assert!(valid_degree(MAX_DEGREE));
+        assert!(!valid_degree(0));
+        assert!(!valid_degree(12));
+        assert!(!valid_degree(MAX_DEGREE + 8));
+    }
+
+    #[test]
+    fn gf2n_works() {
+        // GF(2^8) is reduced by x^8 + x^4 + x^3 + x + 1, as in AES
+        let field = Gf2n::new(8);
+        let product = field.mul(&BigUint::from(0xb6_u8), &BigUint::from(0x53_u8));
+        assert_eq!(product, BigUint::from(0x36_u8));
+
+        for degree in [8, 72, 128, 1024] {
+            let field = Gf2n::new(degree);
+            let a = (BigUint::one() << (degree - 1)) + 0x45_u8;
+            let inv = field.inv(&a).unwrap_or_default();
+            assert_eq!(field.mul(&a, &inv), BigUint::one());
+            assert_eq!(Gf2n::add(&a, &a), BigUint::default());
+        }
+        assert_eq!(Gf2n::new(8).inv(&BigUint::default()), None);
+    }
+}
@@ -0,0 +1,471 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Shares compatible with B. Poettering's
+//! [`ssss-split` and `ssss-combine`](http://point-at-infinity.org/ssss/)
+//! command-line tools.
+//!
+//! `ssss` splits a secret over GF(2^n), where the security level `n` is a
+//! multiple of 8 up to 1024, and by default 8 bits per character of the
+//! secret.  Each share is written as `[token-]index-hex`, e.g.
+//! `3-fa1c3a9c6df8af0779c36de6c33f6e36e989d0e0b91309`, with the index padded
+//! to the number of digits of the share count and `n / 4` hex digits.  Unless
+//! diffusion is turned off, a secret of at least 64 bits is first scrambled
+//! with XTEA, so that a partial secret says nothing about the real one.
+//!
+//! The [`Config`] options match the command-line options of the tools:
+//!
+//! | `ssss` option | [`Config`] field |
+//! |---------------|------------------|
+//! | `-t`          | `threshold`      |
+//! | `-n`          | `num_shares`     |
+//! | `-s`          | `level`          |
+//! | `-w`          | `token`          |
+//! | `-D`          | `diffusion`      |
+//!
+//! A secret given to `ssss-split -x` in hex is split here as the bytes the hex
+//! spells.  A secret shorter than the security level is padded on the left
+//! with zero bytes, which [`combine`] returns as part of the secret.
+//!
+//! # Example
+//! ```
+//! # use anyhow::Result;
+//! # use ssss::legacy_ssss::{combine, Config};
+//! #
+//! # pub fn main() -> Result<()> {
+//! // Shares made by `ssss-split -t 3 -n 5`
+//! let shares = vec![
+//!     "3-fa1c3a9c6df8af0779c36de6c33f6e36e989d0e0b91309".to_string(),
+//!     "5-4756974923c0dce0a55f4774d09ca7a4865f64f56a4ee0".to_string(),
+//!     "2-fbc74a03a50e14ab406c225afb5f45c40ae11976d2b665".to_string(),
+//! ];
+//! let config = Config::builder().threshold(3).build();
+//! assert_eq!(combine(&config, &shares)?, b"my secret root password");
+//! # Ok(())
+//! # }
+//! ```
+
+mod diffusion;
+mod field;
+
+use self::{
+    diffusion::diffuse,
+    field::{Gf2n, valid_degree},
+};
+use crate::error::SsssError::{
+    DuplicateShareIndex, EmptySecret, IndexZero, InvalidShareFormat, LegacyLevel,
+    LegacyLevelMismatch, LegacyThreshold, LegacyToken, NotEnoughShares, SecretLength,
+    ThresholdToLow, TooManyShares,
+};
+use anyhow::Result;
+use bon::Builder;
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The smallest security level the diffusion layer is applied at
+const MIN_DIFFUSION_DEGREE: usize = 64;
+/// The longest token `ssss` accepts
+const MAX_TOKEN_LEN: usize = 128;
+
+/// Configuration used to drive the [`split`] and [`combine`] functions.
+///
+/// # Notes
+/// The default configuration will specify 5 shares with a threshold of 3, a
+/// security level of 8 bits per byte of the secret, diffusion and no token.
+#[derive(Builder, Clone, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// The number of shares required to recover the secret, at least 2
+    #[builder(default = 3)]
+    threshold: u16,
+    /// The number of shares to generate
+    #[builder(default = 5)]
+    num_shares: u16,
+    /// The security level in bits, a multiple of 8 up to 1024.  By default
+    /// the length of the secret in bits
+    level: Option<usize>,
+    /// Scramble the secret before it is split.  Only secrets of at least 64
+    /// bits are scrambled
+    #[builder(default = true)]
+    diffusion: bool,
+    /// A prefix for every share, without any `-`
+    token: Option<String>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::builder().build()
+    }
+}
+
+impl Config {
+    fn validate_threshold(&self) -> Result<()> {
+        if self.threshold < 2 {
+            Err(LegacyThreshold.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn validate(&self, secret: &[u8]) -> Result<usize> {
+        self.validate_threshold()?;
+        if self.threshold > self.num_shares {
+            return Err(ThresholdToLow {
+                threshold: self.threshold,
+                shares: self.num_shares,
+            }
+            .into());
+        }
+        if secret.is_empty() {
+            return Err(EmptySecret.into());
+        }
+        let degree = self.level.unwrap_or(8 * secret.len());
+        if !valid_degree(degree) {
+            return Err(LegacyLevel { level: degree }.into());
+        }
+        if secret.len() > degree / 8 {
+            return Err(SecretLength {
+                length: secret.len(),
+                max: degree / 8,
+            }
+            .into());
+        }
+        if degree < 16 && self.num_shares > u8::MAX.into() {
+            return Err(TooManyShares {
+                shares: self.num_shares,
+                max: u8::MAX.into(),
+            }
+            .into());
+        }
+        if self
+            .token
+            .as_ref()
+            .is_some_and(|token| token.len() > MAX_TOKEN_LEN || token.contains('-'))
+        {
+            return Err(LegacyToken.into());
+        }
+        Ok(degree)
+    }
+
+    fn diffuses(&self, degree: usize) -> bool {
+        self.diffusion && degree >= MIN_DIFFUSION_DEGREE
+    }
+}
+
+/// Split the secret into shares in the format of `ssss-split`.
+///
+/// # Errors
+/// * This function will generate an error if the threshold is less than 2 or
+///   greater than the number of shares.
+/// * This function will generate an error if the secret is empty or longer
+///   than the security level, or the security level is not a multiple of 8
+///   from 8 to 1024.
+/// * This function will generate an error if there are more than 255 shares
+///   at a security level of 8, or the token is longer than 128 characters or
+///   contains a `-`.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::legacy_ssss::{combine, split, Config};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let config = Config::builder()
+///     .threshold(2)
+///     .num_shares(12)
+///     .level(128)
+///     .token("escrow".to_string())
+///     .build();
+/// let shares = split(&config, b"hunter2")?;
+/// assert!(shares[0].starts_with("escrow-01-"));
+/// assert_eq!(shares[0].len(), "escrow-01-".len() + 32);
+///
+/// let secret = combine(&config, &shares[10..])?;
+/// assert_eq!(secret, b"\0\0\0\0\0\0\0\0\0hunter2");
+/// # Ok(())
+/// # }
+/// ```
+pub fn split(config: &Config, secret: &[u8]) -> Result<Vec<String>> {
+    let degree = config.validate(secret)?;
+    let field = Gf2n::new(degree);
+
+    let mut constant = BigUint::from_bytes_be(secret);
+    if config.diffuses(degree) {
+        constant = diffuse(&constant, degree, false);
+    }
+    let mut rng = rand::rng();
+    let mut coeffs = vec![constant];
+    let mut bytes = vec![0; degree / 8];
+    for _ in 1..config.threshold {
+        rng.fill_bytes(&mut bytes);
+        coeffs.push(BigUint::from_bytes_be(&bytes));
+    }
+
+    let width = config.num_shares.to_string().len();
+    let prefix = config
+        .token
+        .as_ref()
+        .map_or_else(String::new, |token| format!("{token}-"));
+    Ok((1..=config.num_shares)
+        .map(|index| {
+            let y = eval_monic(&field, &coeffs, &BigUint::from(index));
+            format!(
+                "{prefix}{index:0width$}-{:0>digits$x}",
+                y,
+                digits = degree / 4
+            )
+        })
+        .collect())
+}
+
+/// Combine `threshold` shares in the format of `ssss-split` into the secret.
+/// Any shares beyond the first `threshold` are ignored, and tokens are not
+/// checked.
+///
+/// The secret is as long as the security level of the shares, so a secret
+/// that was shorter than the security level starts with zero bytes.
+///
+/// # Errors
+/// * This function will generate an error if the threshold is less than 2, or
+///   fewer shares are given.
+/// * This function will generate an error if a share cannot be read, the
+///   shares have different security levels or an index is 0 or given more
+///   than once.
+pub fn combine(config: &Config, shares: &[String]) -> Result<Vec<u8>> {
+    config.validate_threshold()?;
+    let threshold = usize::from(config.threshold);
+    if shares.len() < threshold {
+        return Err(NotEnoughShares {
+            threshold: config.threshold,
+            found: shares.len(),
+        }
+        .into());
+    }
+
+    let points = shares[..threshold]
+        .iter()
+        .map(|share| parse_share(share))
+        .collect::<Result<Vec<(BigUint, BigUint, usize)>>>()?;
+    let degree = points[0].2;
+    if points.iter().any(|(_, _, other)| *other != degree) {
+        return Err(LegacyLevelMismatch.into());
+    }
+    let field = Gf2n::new(degree);
+
+    // Every share lies on a monic polynomial of degree `threshold`, so
+    // subtracting `x^threshold` leaves points of the polynomial of degree
+    // `threshold - 1` whose constant term is the secret
+    let points: Vec<(&BigUint, BigUint)> = points
+        .iter()
+        .map(|(x, y, _)| {
+            let leading = (0..threshold).fold(BigUint::one(), |power, _| field.mul(&power, x));
+            (x, Gf2n::add(y, &leading))
+        })
+        .collect();
+    let mut secret = BigUint::zero();
+    for (i, (x_i, y_i)) in points.iter().enumerate() {
+        let mut numerator = BigUint::one();
+        let mut denominator = BigUint::one();
+        for (j, (x_j, _)) in points.iter().enumerate() {
+            if i != j {
+                numerator = field.mul(&numerator, x_j);
+                denominator = field.mul(&denominator, &Gf2n::add(x_i, x_j));
+            }
+        }
+        let basis = field.mul(
+            &numerator,
+            &field.inv(&denominator).ok_or(DuplicateShareIndex)?,
+        );
+        secret = Gf2n::add(&secret, &field.mul(y_i, &basis));
+    }
+
+    if config.diffuses(degree) {
+        secret = diffuse(&secret, degree, true);
+    }
+    let bytes = secret.to_bytes_be();
+    let mut padded = vec![0; degree / 8 - bytes.len().min(degree / 8)];
+    padded.extend(bytes);
+    Ok(padded)
+}
+
+/// Evaluate `x^t + c[t-1] x^(t-1) + ... + c[0]` at `x`, the polynomial
+/// `ssss` evaluates for a threshold of `t`
+fn eval_monic(field: &Gf2n, coeffs: &[BigUint], x: &BigUint) -> BigUint {
+    let leading = coeffs
+        .iter()
+        .skip(1)
+        .rev()
+        .fold(x.clone(), |y, coeff| field.mul(&Gf2n::add(&y, coeff), x));
+    Gf2n::add(&leading, &coeffs[0])
+}
+
+/// Read a share into its index, its value and the degree of its field
+fn parse_share(share: &str) -> Result<(BigUint, BigUint, usize)> {
+    let parts: Vec<&str> = share.trim().split('-').collect();
+    let ([index, hex] | [_, index, hex]) = parts[..] else {
+        return Err(InvalidShareFormat.into());
+    };
+    let index: u64 = index.parse().map_err(|_| InvalidShareFormat)?;
+    if index == 0 {
+        return Err(IndexZero.into());
+    }
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(InvalidShareFormat.into());
+    }
+    let degree = 4 * hex.len();
+    if !valid_degree(degree) {
+        return Err(LegacyLevel { level: degree }.into());
+    } else if degree < 64 && index >> degree != 0 {
+        // The index must be an element of the field
+        return Err(InvalidShareFormat.into());
+    }
+    let y = BigUint::parse_bytes(hex.as_bytes(), 16).ok_or(InvalidShareFormat)?;
+    Ok((BigUint::from(index), y, degree))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Config, combine, split};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+
+    /// The example shares of the `ssss` documentation
+    const SHARES: [&str; 5] = [
+        "1-1c41ef496eccfbeba439714085df8437236298da8dd824",
+        "2-fbc74a03a50e14ab406c225afb5f45c40ae11976d2b665",
+        "3-fa1c3a9c6df8af0779c36de6c33f6e36e989d0e0b91309",
+        "4-468de7d6eb36674c9cf008c8e8fc8c566537ad6301eb9e",
+        "5-4756974923c0dce0a55f4774d09ca7a4865f64f56a4ee0",
+    ];
+
+    fn shares(indices: &[usize]) -> Vec<String> {
+        indices.iter().map(|i| SHARES[i - 1].to_string()).collect()
+    }
+
+    #[test]
+    fn combine_ssss_shares() -> Result<()> {
+        let config = Config::default();
+        for indices in [[3, 5, 2], [1, 2, 3], [1, 4, 5], [5, 4, 3]] {
+            assert_eq!(
+                combine(&config, &shares(&indices))?,
+                b"my secret root password"
+            );
+        }
+        let tokens: Vec<String> = SHARES.iter().map(|share| format!("root-{share}")).collect();
+        assert_eq!(combine(&config, &tokens[2..])?, b"my secret root password");
+
+        let no_diffusion = Config::builder().diffusion(false).build();
+        assert_ne!(
+            combine(&no_diffusion, &shares(&[1, 2, 3]))?,
+            b"my secret root password"
+        );
+        let threshold = Config::builder().threshold(2).build();
+        assert_ne!(
+            combine(&threshold, &shares(&[1, 2]))?,
+            b"my secret root password"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn split_and_combine() -> Result<()> {
+        for (level, diffusion) in [
+            (None, true),
+            (Some(8 * 28), false),
+            (Some(232), true),
+            (Some(1024), true),
+        ] {
+            let config = Config::builder()
+                .threshold(4)
+                .num_shares(6)
+                .maybe_level(level)
+                .diffusion(diffusion)
+                .build();
+            let secret = b"correct horse battery staple";
+            let shares = split(&config, secret)?;
+            assert_eq!(shares.len(), 6);
+            let recovered = combine(&config, &shares[1..5])?;
+            assert_eq!(recovered.len(), level.unwrap_or(8 * secret.len()) / 8);
+            assert!(recovered.ends_with(secret));
+            assert_eq!(combine(&config, &shares[2..])?, recovered);
+            let too_few = Config::builder()
+                .threshold(3)
+                .maybe_level(level)
+                .diffusion(diffusion)
+                .build();
+            assert_ne!(combine(&too_few, &shares[..3])?, recovered);
+        }
+        let config = Config::builder().threshold(2).num_shares(255).build();
+        let shares = split(&config, b"x")?;
+        assert!(shares[0].starts_with("001-"));
+        assert_eq!(combine(&config, &shares[200..])?, b"x");
+        Ok(())
+    }
+
+    #[test]
+    fn bad_config_errors() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        check_err_result(
+            split(&Config::builder().threshold(1).build(), secret),
+            "The threshold must be at least 2",
+        )?;
+        check_err_result(
+            split(&Config::builder().num_shares(2).build(), secret),
+            "You have specified an invalid threshold.  It must be less than or equal to the number of shares. (3 is not <= 2)",
+        )?;
+        check_err_result(split(&Config::default(), b""), "The secret cannot be empty")?;
+        check_err_result(
+            split(&Config::builder().level(100).build(), secret),
+            "The security level 100 is not a multiple of 8 from 8 to 1024",
+        )?;
+        check_err_result(
+            split(&Config::builder().level(128).build(), secret),
+            "The secret length '28' is longer than the maximum allowed '16'",
+        )?;
+        check_err_result(
+            split(&Config::builder().num_shares(256).build(), b"x"),
+            "The field supports at most 255 shares, but 256 were requested",
+        )?;
+        check_err_result(
+            split(&Config::builder().token("a-b".to_string()).build(), secret),
+            "The token must be at most 128 characters, without a '-'",
+        )
+    }
+
+    #[test]
+    fn bad_shares_error() -> Result<()> {
+        let config = Config::default();
+        check_err_result(
+            combine(&config, &shares(&[1, 2])),
+            "3 shares are required, but 2 were given",
+        )?;
+        let mut bad = shares(&[1, 2, 3]);
+        bad[2] = "3-abc".to_string();
+        check_err_result(
+            combine(&config, &bad),
+            "The security level 12 is not a multiple of 8 from 8 to 1024",
+        )?;
+        bad[2] = "3-abcd".to_string();
+        check_err_result(
+            combine(&config, &bad),
+            "The shares have different security levels",
+        )?;
+        bad[2] = "0-1c41ef496eccfbeba439714085df8437236298da8dd824".to_string();
+        check_err_result(combine(&config, &bad), "A share index cannot be 0")?;
+        bad[2] = SHARES[0].to_string();
+        check_err_result(
+            combine(&config, &bad),
+            "A share index is used more than once",
+        )?;
+        bad[2] = "3-xyz".to_string();
+        check_err_result(combine(&config, &bad), "Invalid share format")?;
+        bad[2] = "a-b-3-1c".to_string();
+        check_err_result(combine(&config, &bad), "Invalid share format")
+    }
+}
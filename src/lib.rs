@@ -309,13 +309,71 @@
 
 #[cfg(all(feature = "arbitrary", not(feature = "fuzz")))]
 use arbitrary as _;
+#[cfg(all(feature = "png", not(feature = "qr")))]
+use png as _;
+#[cfg(all(feature = "qrcode", not(feature = "qr")))]
+use qrcode as _;
 mod base62;
+mod binary;
+pub mod clevis;
+mod encoding;
 mod error;
+mod field;
+mod fingerprint;
 mod gf256;
+mod gf65536;
+pub mod legacy_ssss;
+mod paper;
+pub mod pycryptodome;
+pub mod secretsjs;
 mod shamir;
+pub mod slip39;
+pub mod sss;
+mod uri;
 mod utils;
+pub mod vault;
 
+pub use self::base62::Base62;
+pub use self::binary::BinaryShare;
+pub use self::binary::convert;
+pub use self::encoding::Base32;
+pub use self::encoding::Base64Url;
+pub use self::encoding::Bech32m;
+pub use self::encoding::Bip39;
+pub use self::encoding::Encoding;
+pub use self::encoding::Hex;
+pub use self::encoding::PgpWords;
+pub use self::encoding::ShareEncoding;
+pub use self::field::Field;
+pub use self::field::Fp;
+pub use self::field::Gf2_8;
+pub use self::field::Gf2_128;
+pub use self::field::Gf256;
+pub use self::field::Gf65536;
+pub use self::field::Mersenne127;
+pub use self::field::Modulus;
+pub use self::field::Polynomial;
+pub use self::fingerprint::Fingerprint;
+pub use self::fingerprint::fingerprint;
+pub use self::fingerprint::set_fingerprint;
+pub use self::gf256::Gf256Params;
+pub use self::gf256::Gf256Tables;
+pub use self::gf256::Poly11b;
+pub use self::gf256::Poly11d;
+pub use self::paper::PaperShare;
+pub use self::shamir::FieldKind;
+pub use self::shamir::Indices;
+pub use self::shamir::Padding;
 pub use self::shamir::SsssConfig;
+pub use self::shamir::canonicalize;
 pub use self::shamir::gen_shares;
+pub use self::shamir::gen_shares_at;
+pub use self::shamir::gen_shares_over;
+pub use self::shamir::reencode;
+pub use self::shamir::repair;
 pub use self::shamir::unlock;
+pub use self::shamir::unlock_over;
+pub use self::shamir::unlock_robust;
+pub use self::shamir::unlock_robust_over;
+pub use self::uri::ShareUri;
 pub use self::utils::remove_random_entry;
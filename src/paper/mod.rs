@@ -0,0 +1,496 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` printable paper backups
+
+mod pdf;
+
+use crate::{
+    SsssConfig,
+    encoding::{Base32, ShareEncoding, base32},
+    error::SsssError::{PaperChecksum, PaperLength, PaperLine, PaperMissingField},
+    shamir::decode_share,
+};
+use anyhow::Result;
+use rand::Rng;
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The first line of every paper backup
+const TITLE: &str = "SSSS PAPER BACKUP";
+/// The number of share bytes on each numbered line
+const LINE_BYTES: usize = 10;
+/// The number of characters in each printed group
+const GROUP_CHARS: usize = 4;
+/// The number of characters in the checksum at the end of each line
+const CHECKSUM_CHARS: usize = 4;
+
+/// A share laid out for printing and transcribing by hand, in the spirit of
+/// `paperkey`.
+///
+/// The share bytes are written in Crockford base32, ten bytes to a numbered
+/// line, and each line ends with a CRC-16 of its number and its bytes.  A
+/// header records the share-set id, the share index, the threshold, the
+/// creation date and the share length.
+///
+/// ```text
+/// SSSS PAPER BACKUP
+/// Set:       K3QF-9D2A
+/// Share:     2
+/// Threshold: 3
+/// Created:   2026-10-18
+/// Length:    28
+///
+/// 1: HGFT 81WK BRGX 0TSW  V8QG
+/// 2: W949 M5ZN 1NJB GBMH  VNW0
+/// 3: FB1G BQTP 1FMK G     XPH0
+/// ```
+///
+/// Parsing a transcription ignores whitespace and line breaks within the
+/// payload, treats `O` as `0` and `I` and `L` as `1`, and reports the number of
+/// the first line whose checksum does not match.  [`unlock`](crate::unlock)
+/// accepts the text of a paper backup directly.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{gen_shares, unlock, PaperShare, SsssConfig};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = "correct horse battery staple".as_bytes();
+/// let config = SsssConfig::default();
+/// let shares = gen_shares(&config, &secret)?;
+///
+/// let papers = PaperShare::set(&config, &shares)?;
+/// let transcribed: Vec<String> = papers.iter().map(PaperShare::to_text).collect();
+/// let parsed: PaperShare = transcribed[0].parse()?;
+/// assert_eq!(parsed.set_id(), papers[0].set_id());
+/// assert_eq!(parsed.index(), 1);
+///
+/// assert_eq!(unlock(&transcribed)?, secret);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PaperShare {
+    set_id: String,
+    index: u8,
+    threshold: u8,
+    created: String,
+    share: Vec<u8>,
+}
+
+impl PaperShare {
+    /// Lay out a set of shares generated with the given configuration, with a
+    /// new random share-set id and today's date.
+    ///
+    /// # Errors
+    /// * This function will generate an error if any of the shares cannot be
+    ///   decoded.  Only shares split over GF(2^8) can be laid out.
+    pub fn set(config: &SsssConfig, shares: &[String]) -> Result<Vec<Self>> {
+        let mut id = [0; 5];
+        rand::rng().fill_bytes(&mut id);
+        let set_id = group(&base32::encode(&id), '-');
+        let created = today();
+        shares
+            .iter()
+            .map(|share| {
+                let (index, share) = decode_share(share.clone())?;
+                Ok(Self {
+                    set_id: set_id.clone(),
+                    index,
+                    threshold: u8::try_from(config.threshold())?,
+                    created: created.clone(),
+                    share,
+                })
+            })
+            .collect()
+    }
+
+    /// The id shared by all of the shares in the set
+    #[must_use]
+    pub fn set_id(&self) -> &str {
+        &self.set_id
+    }
+
+    /// The share index
+    #[must_use]
+    pub fn index(&self) -> u8 {
+        self.index
+    }
+
+    /// The number of shares required to unlock the secret
+    #[must_use]
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    /// The date the backup was created, as `YYYY-MM-DD`
+    #[must_use]
+    pub fn created(&self) -> &str {
+        &self.created
+    }
+
+    /// The share, in the [`Base32`] encoding
+    #[must_use]
+    pub fn share(&self) -> String {
+        Base32.encode(self.index, &self.share)
+    }
+
+    /// The printable text layout
+    #[must_use]
+    pub fn to_text(&self) -> String {
+        self.lines().join("\n") + "\n"
+    }
+
+    /// The printable layout as a PDF document, in a monospaced font on A4
+    /// pages
+    #[must_use]
+    pub fn to_pdf(&self) -> Vec<u8> {
+        pdf::render(&self.lines())
+    }
+
+    fn lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            TITLE.to_string(),
+            format!("Set:       {}", self.set_id),
+            format!("Share:     {}", self.index),
+            format!("Threshold: {}", self.threshold),
+            format!("Created:   {}", self.created),
+            format!("Length:    {}", self.share.len()),
+            String::new(),
+        ];
+        let chunks = self.share.chunks(LINE_BYTES);
+        let width = chunks.len().to_string().len();
+        let data_width = group(&base32::encode(&[0; LINE_BYTES]), ' ').len();
+        for (number, chunk) in (1..).zip(chunks) {
+            lines.push(format!(
+                "{number:>width$}: {:<data_width$}  {}",
+                group(&base32::encode(chunk), ' '),
+                base32::encode(&line_checksum(number, chunk).to_be_bytes()),
+            ));
+        }
+        lines
+    }
+}
+
+impl FromStr for PaperShare {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        let mut fields = BTreeMap::new();
+        let mut numbered: BTreeMap<u16, String> = BTreeMap::new();
+        let mut current = None;
+        for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            if line.eq_ignore_ascii_case(TITLE) {
+                continue;
+            }
+            let (label, rest) = line.split_once(':').unwrap_or(("", line));
+            let label = label.trim().to_ascii_lowercase();
+            if FIELDS.contains(&label.as_str()) {
+                let _old = fields.insert(label, rest.trim().to_string());
+            } else if let Some(number) = line_number(&label) {
+                current = Some(number);
+                numbered.entry(number).or_default().push_str(rest);
+            } else if let Some(number) = current {
+                // A line wrapped while transcribing
+                numbered.entry(number).or_default().push_str(line);
+            }
+        }
+
+        let field = |field: &'static str| {
+            fields
+                .get(field)
+                .map(String::as_str)
+                .ok_or(PaperMissingField { field })
+        };
+        let mut share = vec![];
+        for (expected, (number, line)) in (1..).zip(&numbered) {
+            if *number != expected {
+                return Err(PaperLine { line: expected }.into());
+            }
+            share.extend(parse_line(*number, line)?);
+        }
+        let length: usize = field("length")?.parse()?;
+        if share.len() != length {
+            return Err(PaperLength {
+                expected: length,
+                found: share.len(),
+            }
+            .into());
+        }
+
+        Ok(Self {
+            set_id: field("set")?.chars().map(normalize_id).collect(),
+            index: field("share")?.parse()?,
+            threshold: field("threshold")?.parse()?,
+            created: field("created")?.to_string(),
+            share,
+        })
+    }
+}
+
+/// The header fields of a paper backup
+const FIELDS: [&str; 5] = ["set", "share", "threshold", "created", "length"];
+
+/// Unwrap the share from the text of a paper backup, or return the share
+/// unchanged
+pub(crate) fn strip(share: String) -> Result<String> {
+    if share.trim_start().to_ascii_uppercase().starts_with(TITLE) {
+        Ok(share.parse::<PaperShare>()?.share())
+    } else {
+        Ok(share)
+    }
+}
+
+/// Normalize a character of the share-set id, which is written in Crockford
+/// base32
+fn normalize_id(c: char) -> char {
+    match c.to_ascii_uppercase() {
+        'O' => '0',
+        'I' | 'L' => '1',
+        c => c,
+    }
+}
+
+/// Parse a line number, reading `O` as `0` and `I` and `L` as `1`
+fn line_number(label: &str) -> Option<u16> {
+    let digits: String = label
+        .chars()
+        .map(|c| match c {
+            'o' => '0',
+            'i' | 'l' => '1',
+            c => c,
+        })
+        .collect();
+    if !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()) {
+        digits.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Decode the bytes of a numbered line, and check them against the checksum
+/// at the end of the line
+fn parse_line(number: u16, line: &str) -> Result<Vec<u8>> {
+    let chars: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    let split = chars
+        .char_indices()
+        .rev()
+        .nth(CHECKSUM_CHARS - 1)
+        .map(|(i, _)| i)
+        .ok_or(PaperChecksum { line: number })?;
+    let (data, checksum) = chars.split_at(split);
+    match (base32::decode(data), base32::decode(checksum)) {
+        (Ok(bytes), Ok(checksum)) if checksum == line_checksum(number, &bytes).to_be_bytes() => {
+            Ok(bytes)
+        }
+        _ => Err(PaperChecksum { line: number }.into()),
+    }
+}
+
+/// CRC-16/CCITT of the line number and the line bytes, so that a line
+/// copied in the wrong place is also caught
+fn line_checksum(number: u16, bytes: &[u8]) -> u16 {
+    number
+        .to_be_bytes()
+        .iter()
+        .chain(bytes)
+        .fold(0xffff, |crc, byte| {
+            (0..8).fold(crc ^ u16::from(*byte) << 8, |crc, _| {
+                if crc & 0x8000 == 0 {
+                    crc << 1
+                } else {
+                    crc << 1 ^ 0x1021
+                }
+            })
+        })
+}
+
+/// Split the text into groups of `GROUP_CHARS` characters
+fn group(text: &str, separator: char) -> String {
+    text.chars()
+        .collect::<Vec<char>>()
+        .chunks(GROUP_CHARS)
+        .map(|chunk| chunk.iter().collect::<String>())
+        .collect::<Vec<String>>()
+        .join(&separator.to_string())
+}
+
+/// Today's date in UTC, as `YYYY-MM-DD`
+fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs() / 86_400);
+    civil_date(i64::try_from(days).unwrap_or_default())
+}
+
+/// Convert days since 1970-01-01 to a calendar date
+fn civil_date(days: i64) -> String {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{PaperShare, civil_date, line_checksum};
+    use crate::{SsssConfig, gen_shares, unlock, utils::check_err_result};
+    use anyhow::Result;
+
+    fn paper() -> PaperShare {
+        PaperShare {
+            set_id: "K3QF-9D2A".to_string(),
+            index: 2,
+            threshold: 3,
+            created: "2026-10-18".to_string(),
+            share: (0..28).collect(),
+        }
+    }
+
+    #[test]
+    fn line_checksum_works() {
+        // CRC-16/CCITT-FALSE check value, with the line number as the first
+        // two bytes
+        assert_eq!(line_checksum(0x3132, b"3456789"), 0x29b1);
+    }
+
+    #[test]
+    fn civil_date_works() {
+        assert_eq!(civil_date(0), "1970-01-01");
+        assert_eq!(civil_date(11_016), "2000-02-29");
+        assert_eq!(civil_date(20_744), "2026-10-18");
+    }
+
+    #[test]
+    fn text_layout() {
+        let text = paper().to_text();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], "SSSS PAPER BACKUP");
+        assert_eq!(lines[1], "Set:       K3QF-9D2A");
+        assert_eq!(lines[2], "Share:     2");
+        assert_eq!(lines[3], "Threshold: 3");
+        assert_eq!(lines[4], "Created:   2026-10-18");
+        assert_eq!(lines[5], "Length:    28");
+        assert_eq!(lines.len(), 10);
+        assert!(lines[7].starts_with("1: 000G 40R4 0M30 E209  "));
+        assert_eq!(lines[7].len(), lines[9].len());
+    }
+
+    #[test]
+    fn text_roundtrip() -> Result<()> {
+        assert_eq!(paper().to_text().parse::<PaperShare>()?, paper());
+        Ok(())
+    }
+
+    #[test]
+    fn sloppy_transcriptions_are_accepted() -> Result<()> {
+        let text = paper().to_text();
+        let sloppy: String = text
+            .lines()
+            .map(|line| {
+                if line.starts_with("2:") {
+                    // Wrap the line in the middle
+                    line.replacen(' ', "\n", 3)
+                } else if line.starts_with(|c: char| c.is_ascii_digit()) {
+                    line.replace('0', "o").replace('1', "l").to_lowercase()
+                } else {
+                    line.to_lowercase()
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n\n");
+        assert_eq!(sloppy.parse::<PaperShare>()?, paper());
+        Ok(())
+    }
+
+    #[test]
+    fn bad_line_is_located() -> Result<()> {
+        let text = paper().to_text();
+        let mut lines: Vec<String> = text.lines().map(ToString::to_string).collect();
+        let typo = if &lines[8][4..5] == "7" { "8" } else { "7" };
+        lines[8].replace_range(4..5, typo);
+        check_err_result(
+            lines.join("\n").parse::<PaperShare>(),
+            "Line 2 of the paper backup does not match its checksum",
+        )?;
+
+        let mut lines: Vec<String> = text.lines().map(ToString::to_string).collect();
+        lines.swap(7, 8);
+        lines[7].replace_range(0..1, "1");
+        lines[8].replace_range(0..1, "2");
+        check_err_result(
+            lines.join("\n").parse::<PaperShare>(),
+            "Line 1 of the paper backup does not match its checksum",
+        )
+    }
+
+    #[test]
+    fn missing_lines_error() -> Result<()> {
+        let text = paper().to_text();
+        let lines: Vec<&str> = text.lines().collect();
+        check_err_result(
+            [&lines[..8], &lines[9..]]
+                .concat()
+                .join("\n")
+                .parse::<PaperShare>(),
+            "Line 2 of the paper backup is missing",
+        )?;
+        check_err_result(
+            lines[..9].join("\n").parse::<PaperShare>(),
+            "The paper backup holds 20 bytes, but its header says 28",
+        )?;
+        check_err_result(
+            [&lines[..1], &lines[2..]]
+                .concat()
+                .join("\n")
+                .parse::<PaperShare>(),
+            "The paper backup is missing the 'set' field",
+        )
+    }
+
+    #[test]
+    fn unlock_accepts_paper() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        let config = SsssConfig::default();
+        let shares = gen_shares(&config, secret)?;
+        let papers = PaperShare::set(&config, &shares)?;
+        assert!(
+            papers
+                .iter()
+                .all(|paper| paper.set_id() == papers[0].set_id())
+        );
+        assert_eq!(papers[4].threshold(), 3);
+        let transcribed = vec![
+            papers[0].to_text(),
+            papers[3].to_text().to_lowercase(),
+            shares[4].clone(),
+        ];
+        assert_eq!(unlock(&transcribed)?, secret);
+        Ok(())
+    }
+
+    #[test]
+    fn pdf_works() {
+        let pdf = paper().to_pdf();
+        assert!(pdf.starts_with(b"%PDF-1.4"));
+        assert!(pdf.ends_with(b"%%EOF\n"));
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(text.contains("(Set:       K3QF-9D2A) Tj"));
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! A minimal PDF writer for paper backups
+//!
+//! The text is set in the built-in Courier font, so the document embeds no
+//! font data and every reader renders the columns aligned.
+
+/// The A4 page size in points
+const PAGE_WIDTH: usize = 595;
+const PAGE_HEIGHT: usize = 842;
+/// The page margin in points
+const MARGIN: usize = 56;
+/// The font size and line height in points
+const FONT_SIZE: usize = 11;
+const LEADING: usize = 15;
+/// The number of lines that fit on a page
+const LINES_PER_PAGE: usize = (PAGE_HEIGHT - 2 * MARGIN) / LEADING;
+
+/// Render the lines of text as a PDF document
+pub(crate) fn render(lines: &[String]) -> Vec<u8> {
+    let pages: Vec<&[String]> = lines.chunks(LINES_PER_PAGE).collect();
+    // The catalog, the page tree and the font come first, followed by a page
+    // object and a content stream for every page
+    let page_ids: Vec<usize> = (0..pages.len()).map(|page| 4 + 2 * page).collect();
+    let kids: Vec<String> = page_ids.iter().map(|id| format!("{id} 0 R")).collect();
+
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            kids.join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier >>".to_string(),
+    ];
+    for (page, id) in pages.iter().zip(&page_ids) {
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {PAGE_WIDTH} {PAGE_HEIGHT}] \
+             /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            id + 1
+        ));
+        let mut text = String::new();
+        for line in *page {
+            text.push('(');
+            text.push_str(&escape(line));
+            text.push_str(") Tj T*\n");
+        }
+        let content = format!(
+            "BT\n/F1 {FONT_SIZE} Tf\n{LEADING} TL\n{MARGIN} {} Td\n{text}ET",
+            PAGE_HEIGHT - MARGIN
+        );
+        objects.push(format!(
+            "<< /Length {} >>\nstream\n{content}\nendstream",
+            content.len()
+        ));
+    }
+
+    let mut pdf = b"%PDF-1.4\n".to_vec();
+    let mut offsets = vec![];
+    for (id, object) in (1..).zip(&objects) {
+        offsets.push(pdf.len());
+        pdf.extend(format!("{id} 0 obj\n{object}\nendobj\n").bytes());
+    }
+    let xref = pdf.len();
+    pdf.extend(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).bytes());
+    for offset in offsets {
+        pdf.extend(format!("{offset:010} 00000 n \n").bytes());
+    }
+    pdf.extend(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{xref}\n%%EOF\n",
+            objects.len() + 1
+        )
+        .bytes(),
+    );
+    pdf
+}
+
+/// Escape the characters that are special in a PDF string
+fn escape(line: &str) -> String {
+    line.chars()
+        .flat_map(|c| match c {
+            '(' | ')' | '\\' => vec!['\\', c],
+            c => vec![c],
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{LINES_PER_PAGE, escape, render};
+
+    #[test]
+    fn escape_works() {
+        assert_eq!(escape(r"a(b)c\d"), r"a\(b\)c\\d");
+    }
+
+    #[test]
+    fn long_text_is_paginated() {
+        let lines: Vec<String> = (0..=LINES_PER_PAGE).map(|i| i.to_string()).collect();
+        let pdf = String::from_utf8_lossy(&render(&lines)).to_string();
+        assert!(pdf.contains("/Kids [4 0 R 6 0 R] /Count 2"));
+
+        // Every object offset in the cross-reference table points at its object
+        let xref = &pdf[pdf.find("xref\n").unwrap_or_default()..];
+        for (id, entry) in xref.lines().skip(3).take(7).enumerate() {
+            let offset: usize = entry[..10].parse().unwrap_or_default();
+            assert!(pdf[offset..].starts_with(&format!("{} 0 obj", id + 1)));
+        }
+    }
+}
@@ -0,0 +1,271 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Shares compatible with the
+//! [`Shamir`](https://pycryptodome.readthedocs.io/en/latest/src/protocol/ss.html)
+//! module of `PyCryptodome`.
+//!
+//! `PyCryptodome` splits 16-byte secrets, such as AES-128 keys, over GF(2^128)
+//! reduced by `x^128 + x^7 + x^2 + x + 1`, the field of [`Gf2_128`].  Each
+//! share is a pair of its index, from 1 to `n`, and 16 bytes, the big-endian
+//! integer whose bit `i` is the coefficient of `x^i`.
+//!
+//! With the `ssss` option, the polynomial gains a leading coefficient of 1,
+//! as in [`legacy_ssss`](crate::legacy_ssss), so the shares can be combined
+//! by `ssss-combine -D` at a security level of 128 bits.
+//!
+//! [`split`] and [`combine`] match `Shamir.split` and `Shamir.combine`.
+//!
+//! # Example
+//! ```
+//! # use anyhow::Result;
+//! # use ssss::pycryptodome::{combine, Config};
+//! #
+//! # pub fn main() -> Result<()> {
+//! // Shares in the layout `Shamir.split(3, 5, bytes(range(16)))` returns
+//! // them, as `(index, bytes)` pairs
+//! let shares = [
+//!     (1, 0x5cfc_7139_747c_20df_f5e8_a967_88f7_934a_u128.to_be_bytes()),
+//!     (3, 0x636b_6c64_bbea_ea8d_49e3_657b_dcf3_cc63_u128.to_be_bytes()),
+//!     (5, 0x3a65_a726_56c3_abf7_92dd_1a4a_573a_ba9a_u128.to_be_bytes()),
+//! ];
+//! let key = combine(&Config::default(), &shares)?;
+//! assert_eq!(key, *b"\x00\x01\x02\x03\x04\x05\x06\x07\x08\x09\x0a\x0b\x0c\x0d\x0e\x0f");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    field::{Field, Gf2_128},
+    shamir::{gen_shares_over, unlock_over},
+};
+use anyhow::Result;
+use bon::Builder;
+use serde::{Deserialize, Serialize};
+
+/// The length of a secret, and of a share
+pub const SECRET_LEN: usize = 16;
+
+/// A share, its index and its 16 bytes, as `Shamir.split` returns it
+pub type Share = (u16, [u8; SECRET_LEN]);
+
+/// Configuration used to drive the [`split`] and [`combine`] functions.
+///
+/// # Notes
+/// The default configuration will specify 5 shares with a threshold of 3,
+/// without the `ssss` option.
+#[derive(Builder, Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// The number of shares required to recover the secret, `k`
+    #[builder(default = 3)]
+    threshold: u16,
+    /// The number of shares to generate, `n`
+    #[builder(default = 5)]
+    num_shares: u16,
+    /// Make shares compatible with `ssss`, like the `ssss` argument
+    #[builder(default = false)]
+    ssss: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::builder().build()
+    }
+}
+
+/// Split a 16-byte secret into shares, like `Shamir.split`.
+///
+/// # Errors
+/// * This function will generate an error if the threshold is 0 or greater
+///   than the number of shares.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::pycryptodome::{combine, split, Config};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let key = *b"YELLOW SUBMARINE";
+/// let config = Config::builder().threshold(2).num_shares(4).ssss(true).build();
+/// let shares = split(&config, &key)?;
+/// assert_eq!(shares.iter().map(|(index, _)| *index).collect::<Vec<u16>>(), [1, 2, 3, 4]);
+/// assert_eq!(combine(&config, &shares[2..])?, key);
+/// # Ok(())
+/// # }
+/// ```
+pub fn split(config: &Config, secret: &[u8; SECRET_LEN]) -> Result<Vec<Share>> {
+    let secret = [Gf2_128(u128::from_be_bytes(*secret))];
+    let indices: Vec<Gf2_128> = (1..=config.num_shares)
+        .map(|index| Gf2_128(index.into()))
+        .collect();
+    let shares = gen_shares_over(config.threshold, &secret, &indices)?;
+    Ok((1..=config.num_shares)
+        .zip(indices.iter().zip(shares))
+        .map(|(index, (x, share))| {
+            let mut y = share[0];
+            if config.ssss {
+                y = y.add(&power(x, config.threshold));
+            }
+            (index, y.0.to_be_bytes())
+        })
+        .collect())
+}
+
+/// Combine shares into the 16-byte secret, like `Shamir.combine`.  Only the
+/// `ssss` option of the configuration is used.
+///
+/// As in `PyCryptodome`, every share given is used, so with the `ssss` option
+/// exactly as many shares as the threshold must be given.  Too few shares
+/// give a wrong secret rather than an error.
+///
+/// # Errors
+/// * This function will generate an error if no shares are given, or an index
+///   is 0 or given more than once.
+pub fn combine(config: &Config, shares: &[Share]) -> Result<[u8; SECRET_LEN]> {
+    let count = u16::try_from(shares.len()).unwrap_or(u16::MAX);
+    let points: Vec<(Gf2_128, Vec<Gf2_128>)> = shares
+        .iter()
+        .map(|(index, share)| {
+            let x = Gf2_128((*index).into());
+            let mut y = Gf2_128(u128::from_be_bytes(*share));
+            if config.ssss {
+                y = y.add(&power(&x, count));
+            }
+            (x, vec![y])
+        })
+        .collect();
+    Ok(unlock_over(&points)?[0].0.to_be_bytes())
+}
+
+/// `x^exponent`, the leading term `ssss` adds to every share
+fn power(x: &Gf2_128, exponent: u16) -> Gf2_128 {
+    (0..exponent).fold(Gf2_128::one(), |power, _| power.mul(x))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Config, SECRET_LEN, Share, combine, split};
+    use crate::{legacy_ssss, utils::check_err_result};
+    use anyhow::Result;
+
+    /// 3-of-5 splits of the bytes `0..16`, without and with the `ssss`
+    /// option, computed with a transcription of `PyCryptodome`'s `Shamir.split`
+    const PYCRYPTODOME_SHARES: [[u128; 5]; 2] = [
+        [
+            0x5cfc_7139_747c_20df_f5e8_a967_88f7_934a,
+            0x3f96_1f5e_cb93_cc55_b402_c617_5809_5126,
+            0x636b_6c64_bbea_ea8d_49e3_657b_dcf3_cc63,
+            0x6698_d41c_26ba_8d2f_6f3c_b926_d3c0_27df,
+            0x3a65_a726_56c3_abf7_92dd_1a4a_573a_ba9a,
+        ],
+        [
+            0xdf67_2530_3d72_cee9_4088_3810_ac90_589f,
+            0x04b3_fa44_f017_5cdc_1104_9099_f521_2118,
+            0xdbd5_dd77_c960_9432_5985_a282_55bc_778e,
+            0xe09e_2a0a_f7d6_9faf_1a2d_c5b8_1e0f_5b97,
+            0x3ff8_0d39_cea1_5741_52ac_f7a3_be92_0d13,
+        ],
+    ];
+
+    fn key() -> [u8; SECRET_LEN] {
+        std::array::from_fn(|i| u8::try_from(i).unwrap_or(0))
+    }
+
+    fn shares(ssss: bool) -> Vec<Share> {
+        PYCRYPTODOME_SHARES[usize::from(ssss)]
+            .iter()
+            .zip(1..)
+            .map(|(share, index)| (index, share.to_be_bytes()))
+            .collect()
+    }
+
+    #[test]
+    fn combine_pycryptodome_shares() -> Result<()> {
+        for ssss in [false, true] {
+            let config = Config::builder().ssss(ssss).build();
+            let shares = shares(ssss);
+            assert_eq!(combine(&config, &shares[..3])?, key());
+            assert_eq!(combine(&config, &shares[2..])?, key());
+            assert_eq!(combine(&config, &[shares[4], shares[0], shares[2]])?, key());
+        }
+        // `Shamir.combine` gives these for the first two shares
+        let config = Config::default();
+        assert_eq!(
+            u128::from_be_bytes(combine(&config, &shares(false)[..2])?),
+            0x8225_ab1b_e126_84a6_354e_8c48_38a2_2d13
+        );
+        let config = Config::builder().ssss(true).build();
+        assert_eq!(
+            u128::from_be_bytes(combine(&config, &shares(true)[..2])?),
+            0x962b_6fe3_8651_bf05_8ff3_a068_6400_8fe0
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn ssss_shares_combine_with_ssss() -> Result<()> {
+        let config = legacy_ssss::Config::builder()
+            .threshold(3)
+            .level(128)
+            .diffusion(false)
+            .build();
+        let shares: Vec<String> = shares(true)
+            .iter()
+            .map(|(index, share)| format!("{index}-{:032x}", u128::from_be_bytes(*share)))
+            .collect();
+        assert_eq!(legacy_ssss::combine(&config, &shares[1..4])?, key());
+        Ok(())
+    }
+
+    #[test]
+    fn split_and_combine() -> Result<()> {
+        for ssss in [false, true] {
+            for (threshold, num_shares) in [(1, 1), (1, 4), (2, 2), (3, 5), (7, 300)] {
+                let config = Config::builder()
+                    .threshold(threshold)
+                    .num_shares(num_shares)
+                    .ssss(ssss)
+                    .build();
+                let shares = split(&config, &key())?;
+                assert_eq!(shares.len(), usize::from(num_shares));
+                let threshold = usize::from(threshold);
+                assert_eq!(combine(&config, &shares[..threshold])?, key());
+                assert_eq!(
+                    combine(&config, &shares[shares.len() - threshold..])?,
+                    key()
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bad_args_error() -> Result<()> {
+        check_err_result(
+            split(&Config::builder().threshold(0).build(), &key()),
+            "The threshold must be greater than 0",
+        )?;
+        check_err_result(
+            split(&Config::builder().threshold(6).build(), &key()),
+            "You have specified an invalid threshold.  It must be less than or equal to the number of shares. (6 is not <= 5)",
+        )?;
+        let shares = shares(false);
+        check_err_result(
+            combine(&Config::default(), &[]),
+            "The shares map cannot be empty",
+        )?;
+        check_err_result(
+            combine(&Config::default(), &[shares[0], shares[1], shares[0]]),
+            "A share index is used more than once",
+        )?;
+        check_err_result(
+            combine(&Config::default(), &[(0, [0; SECRET_LEN]), shares[1]]),
+            "A share index cannot be 0",
+        )
+    }
+}
@@ -0,0 +1,559 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Shares compatible with [secrets.js](https://github.com/grempe/secrets.js).
+//!
+//! secrets.js splits a hex secret over GF(2^bits), where `bits` is from 3 to
+//! 20 and by default 8.  Each field uses its own primitive polynomial, with
+//! `x^8 + x^4 + x^3 + x^2 + 1` (`0x11d`) for 8 bits, so its shares cannot be
+//! combined with [`unlock`](crate::unlock), which works over `0x11b`, and
+//! ours cannot be combined by secrets.js.  [`split`] and [`combine`] speak the
+//! secrets.js format instead.
+//!
+//! The secret is read as bits, with a `1` bit in front to keep its leading
+//! zeros, padded on the left with zeros to a multiple of the padding length,
+//! 128 by default, and split `bits` bits at a time.  Each share is written
+//! as
+//!
+//! | Characters          | Field                                                  |
+//! |---------------------|--------------------------------------------------------|
+//! | 1                   | `bits` in base 36, `3` to `9` or `A` to `K`            |
+//! | hex digits of `2^bits - 1` | the share index in hex, padded with zeros       |
+//! | the rest            | the share in hex, `bits` bits for each part of the secret |
+//!
+//! e.g. `801836170f10c1d677f03c02b06251d5e32b680ccb1aa84bc7d463cd1ec5e700095`.
+//! The secret given to `secrets.share` in hex is split here as the bytes the
+//! hex spells.  A secret of an odd number of hex digits is recovered with a
+//! leading zero digit.
+//!
+//! # Example
+//! ```
+//! # use anyhow::Result;
+//! # use ssss::secretsjs::combine;
+//! #
+//! # pub fn main() -> Result<()> {
+//! // Shares in the format of `secrets.share(secrets.str2hex("hi", 1), 4, 3,
+//! // 64)` with `secrets.init(12)`
+//! let shares = vec![
+//!     "C00130d191bd97be3c6033".to_string(),
+//!     "C00393d2ddbc8cf544e356".to_string(),
+//!     "C00446e720fb164afa60ab".to_string(),
+//! ];
+//! assert_eq!(combine(&shares)?, b"hi");
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::error::SsssError::{
+    DuplicateShareIndex, EmptySecret, EmptySharesMap, InvalidShareFormat, SecretsJsBits,
+    SecretsJsBitsMismatch, SecretsJsPadLength, SecretsJsShares, SecretsJsThreshold,
+    ShareLengthMismatch,
+};
+use anyhow::Result;
+use bon::Builder;
+use rand::RngExt;
+use serde::{Deserialize, Serialize};
+
+/// The fewest bits secrets.js supports
+const MIN_BITS: u8 = 3;
+/// The most bits secrets.js supports
+const MAX_BITS: u8 = 20;
+/// The longest padding secrets.js supports
+const MAX_PAD_LENGTH: usize = 1024;
+/// The padding secrets.js uses for a padding length of 0
+const DEFAULT_PAD_LENGTH: usize = 128;
+/// The low terms of the primitive polynomial secrets.js reduces by, by the
+/// number of bits
+const PRIMITIVE: [u32; MAX_BITS as usize + 1] = [
+    0, 0, 0, 3, 3, 5, 3, 3, 29, 17, 9, 5, 83, 27, 43, 3, 45, 9, 39, 39, 9,
+];
+
+/// Configuration used to drive the [`split`] function.
+///
+/// # Notes
+/// The default configuration will specify 5 shares with a threshold of 3,
+/// over 8 bits, with the secret padded to a multiple of 128 bits.
+#[derive(Builder, Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// The number of bits of the field, from 3 to 20, as given to
+    /// `secrets.init`
+    #[builder(default = 8)]
+    bits: u8,
+    /// The number of shares required to recover the secret, at least 2
+    #[builder(default = 3)]
+    threshold: u32,
+    /// The number of shares to generate, from 2 to `2^bits - 1`
+    #[builder(default = 5)]
+    num_shares: u32,
+    /// Pad the secret, in bits, to a multiple of this length, at most 1024.
+    /// As in secrets.js, 0 pads to 128 bits and 1 does not pad
+    #[builder(default = DEFAULT_PAD_LENGTH)]
+    pad_length: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::builder().build()
+    }
+}
+
+impl Config {
+    fn validate(&self, secret: &[u8]) -> Result<()> {
+        validate_bits(self.bits)?;
+        let max = max_index(self.bits);
+        if self.num_shares < 2 || self.num_shares > max {
+            return Err(SecretsJsShares {
+                shares: self.num_shares,
+                max,
+            }
+            .into());
+        }
+        if self.threshold < 2 || self.threshold > self.num_shares {
+            return Err(SecretsJsThreshold {
+                threshold: self.threshold,
+                shares: self.num_shares,
+            }
+            .into());
+        }
+        if self.pad_length > MAX_PAD_LENGTH {
+            return Err(SecretsJsPadLength {
+                length: self.pad_length,
+            }
+            .into());
+        }
+        if secret.is_empty() {
+            return Err(EmptySecret.into());
+        }
+        Ok(())
+    }
+}
+
+/// GF(2^bits), reduced by the primitive polynomial secrets.js uses for that
+/// number of bits
+#[derive(Clone, Copy, Debug)]
+struct Field {
+    bits: u8,
+    polynomial: u32,
+}
+
+impl Field {
+    fn new(bits: u8) -> Self {
+        Field {
+            bits,
+            polynomial: (1 << bits) | PRIMITIVE[usize::from(bits)],
+        }
+    }
+
+    fn mul(self, mut a: u32, b: u32) -> u32 {
+        let mut product = 0;
+        for i in 0..self.bits {
+            if (b >> i) & 1 == 1 {
+                product ^= a;
+            }
+            a <<= 1;
+            if a >> self.bits != 0 {
+                a ^= self.polynomial;
+            }
+        }
+        product
+    }
+
+    /// The inverse of a non-zero element, `a^(2^bits - 2)`
+    fn inv(self, a: u32) -> u32 {
+        let mut result = 1;
+        let mut power = a;
+        let mut exponent = max_index(self.bits) - 1;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = self.mul(result, power);
+            }
+            power = self.mul(power, power);
+            exponent >>= 1;
+        }
+        result
+    }
+}
+
+/// Split the secret into shares in the format of `secrets.share`.
+///
+/// # Errors
+/// * This function will generate an error if the number of bits is not from
+///   3 to 20, or the padding length is larger than 1024.
+/// * This function will generate an error if there are fewer than 2 or more
+///   than `2^bits - 1` shares, or the threshold is less than 2 or greater
+///   than the number of shares.
+/// * This function will generate an error if the secret is empty.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::secretsjs::{combine, split, Config};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let config = Config::builder().bits(10).threshold(2).num_shares(600).build();
+/// let shares = split(&config, b"correct horse battery staple")?;
+/// assert!(shares[0].starts_with("A001"));
+/// assert!(shares[599].starts_with("A258"));
+///
+/// let secret = combine(&shares[42..44])?;
+/// assert_eq!(secret, b"correct horse battery staple");
+/// # Ok(())
+/// # }
+/// ```
+pub fn split(config: &Config, secret: &[u8]) -> Result<Vec<String>> {
+    config.validate(secret)?;
+    let field = Field::new(config.bits);
+    let pad_length = if config.pad_length == 0 {
+        DEFAULT_PAD_LENGTH
+    } else {
+        config.pad_length
+    };
+
+    let mut bits = vec![true];
+    bits.extend(to_bits(secret));
+    let parts = to_parts(&pad_left(bits, pad_length), config.bits);
+
+    let mut rng = rand::rng();
+    let xs: Vec<u32> = (1..=config.num_shares).collect();
+    let mut ys = vec![Vec::with_capacity(parts.len()); xs.len()];
+    for part in parts {
+        let mut coeffs = vec![part];
+        for _ in 1..config.threshold {
+            coeffs.push(rng.random_range(0..=max_index(config.bits)));
+        }
+        for (x, y) in xs.iter().zip(&mut ys) {
+            y.push(
+                coeffs
+                    .iter()
+                    .rev()
+                    .fold(0, |value, coeff| field.mul(value, *x) ^ coeff),
+            );
+        }
+    }
+
+    Ok(xs
+        .iter()
+        .zip(ys)
+        .map(|(x, y)| {
+            let bits: Vec<bool> = y
+                .iter()
+                .rev()
+                .flat_map(|value| (0..config.bits).rev().map(move |i| (value >> i) & 1 == 1))
+                .collect();
+            format!(
+                "{}{x:0width$x}{}",
+                bits_char(config.bits),
+                to_hex(&pad_left(bits, 4)),
+                width = id_len(config.bits)
+            )
+        })
+        .collect())
+}
+
+/// Combine shares in the format of `secrets.share` into the secret, like
+/// `secrets.combine`.  Shares given more than once are used once.
+///
+/// Like [`unlock`](crate::unlock), too few shares give the wrong secret
+/// rather than an error.
+///
+/// # Errors
+/// * This function will generate an error if no shares are given, a share
+///   cannot be read, or the shares were split over different numbers of bits
+///   or are of different lengths.
+pub fn combine(shares: &[String]) -> Result<Vec<u8>> {
+    let mut points: Vec<(u32, Vec<u32>)> = Vec::with_capacity(shares.len());
+    let mut bits = None;
+    for share in shares {
+        let (share_bits, x, parts) = parse_share(share.trim())?;
+        if *bits.get_or_insert(share_bits) != share_bits {
+            return Err(SecretsJsBitsMismatch.into());
+        }
+        if points.iter().all(|(other, _)| *other != x) {
+            points.push((x, parts));
+        }
+    }
+    let (bits, (_, first)) = bits.zip(points.first()).ok_or(EmptySharesMap)?;
+    if points.iter().any(|(_, parts)| parts.len() != first.len()) {
+        return Err(ShareLengthMismatch.into());
+    }
+
+    let field = Field::new(bits);
+    let mut basis = Vec::with_capacity(points.len());
+    for (i, (x_i, _)) in points.iter().enumerate() {
+        let mut numerator = 1;
+        let mut denominator = 1;
+        for (j, (x_j, _)) in points.iter().enumerate() {
+            if i != j {
+                numerator = field.mul(numerator, *x_j);
+                denominator = field.mul(denominator, x_i ^ x_j);
+            }
+        }
+        if denominator == 0 {
+            return Err(DuplicateShareIndex.into());
+        }
+        basis.push(field.mul(numerator, field.inv(denominator)));
+    }
+
+    let secret: Vec<bool> = (0..first.len())
+        .rev()
+        .flat_map(|part| {
+            let value = points
+                .iter()
+                .zip(&basis)
+                .fold(0, |sum, ((_, parts), basis)| {
+                    sum ^ field.mul(parts[part], *basis)
+                });
+            (0..bits).rev().map(move |i| (value >> i) & 1 == 1)
+        })
+        .collect();
+    // Drop the padding and the `1` bit in front of the secret
+    let start = secret.iter().position(|bit| *bit).map_or(0, |i| i + 1);
+    Ok(to_bytes(&pad_left(secret[start..].to_vec(), 8)))
+}
+
+/// Read a share into its number of bits, its index and its parts, lowest
+/// first
+fn parse_share(share: &str) -> Result<(u8, u32, Vec<u32>)> {
+    let mut chars = share.chars();
+    let bits = chars
+        .next()
+        .and_then(|c| c.to_digit(36))
+        .and_then(|bits| u8::try_from(bits).ok())
+        .ok_or(InvalidShareFormat)?;
+    validate_bits(bits)?;
+    let id_len = id_len(bits);
+    let rest = chars.as_str();
+    if rest.len() <= id_len || !rest.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(InvalidShareFormat.into());
+    }
+    let (id, data) = rest.split_at(id_len);
+    let id = u32::from_str_radix(id, 16).map_err(|_| InvalidShareFormat)?;
+    if id == 0 || id > max_index(bits) {
+        return Err(InvalidShareFormat.into());
+    }
+    let data: Vec<bool> = data
+        .chars()
+        .filter_map(|c| c.to_digit(16))
+        .flat_map(|digit| (0..4).rev().map(move |i| (digit >> i) & 1 == 1))
+        .collect();
+    Ok((bits, id, to_parts(&data, bits)))
+}
+
+fn validate_bits(bits: u8) -> Result<()> {
+    if (MIN_BITS..=MAX_BITS).contains(&bits) {
+        Ok(())
+    } else {
+        Err(SecretsJsBits { bits }.into())
+    }
+}
+
+/// The largest share index, `2^bits - 1`
+fn max_index(bits: u8) -> u32 {
+    (1 << bits) - 1
+}
+
+/// The number of hex digits of a share index
+fn id_len(bits: u8) -> usize {
+    usize::from(bits).div_ceil(4)
+}
+
+/// The number of bits in base 36, as secrets.js writes it
+fn bits_char(bits: u8) -> char {
+    char::from_digit(u32::from(bits), 36).map_or('8', |c| c.to_ascii_uppercase())
+}
+
+fn to_bits(bytes: &[u8]) -> impl Iterator<Item = bool> + '_ {
+    bytes
+        .iter()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+}
+
+/// Pad the bits on the left with zeros to a multiple of the length
+fn pad_left(bits: Vec<bool>, multiple: usize) -> Vec<bool> {
+    let missing = match bits.len() % multiple {
+        0 => 0,
+        rest => multiple - rest,
+    };
+    let mut padded = vec![false; missing];
+    padded.extend(bits);
+    padded
+}
+
+/// Split the bits into values of `size` bits from the right, lowest first.
+/// The highest value may be shorter
+fn to_parts(bits: &[bool], size: u8) -> Vec<u32> {
+    bits.rchunks(usize::from(size))
+        .map(|chunk| {
+            chunk
+                .iter()
+                .fold(0, |value, bit| value << 1 | u32::from(*bit))
+        })
+        .collect()
+}
+
+fn to_hex(bits: &[bool]) -> String {
+    to_parts(bits, 4)
+        .iter()
+        .rev()
+        .filter_map(|digit| char::from_digit(*digit, 16))
+        .collect()
+}
+
+fn to_bytes(bits: &[bool]) -> Vec<u8> {
+    bits.chunks(8)
+        .map(|chunk| chunk.iter().fold(0, |byte, bit| byte << 1 | u8::from(*bit)))
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Config, Field, MAX_BITS, MIN_BITS, combine, max_index, split};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+
+    /// A 3-of-5 split of `correct horse battery staple` over 8 bits, computed
+    /// with a transcription of secrets.js
+    const SECRETSJS_SHARES: [&str; 5] = [
+        "801836170f10c1d677f03c02b06251d5e32b680ccb1aa84bc7d463cd1ec5e700095",
+        "8026c1075c754f23be38afac7b96dc89a623bda2ccefb68407e9309feb26f2388f2",
+        "803ef7105373b802eeeec59989f20bab623e87a821e25989971ac155c2a5023e402",
+        "804194f847c8eb1715b2a633b7c242d45276a6fc26acfef3d22da36036ea08749e3",
+        "8059a2ef48ce1c364564cc0645a695f6966b9cf6cba111fe42de52aa1f69f872513",
+    ];
+
+    fn shares(shares: &[&str]) -> Vec<String> {
+        shares.iter().map(ToString::to_string).collect()
+    }
+
+    #[test]
+    fn polynomials_are_primitive() {
+        for bits in MIN_BITS..=MAX_BITS {
+            let field = Field::new(bits);
+            let mut x = 1;
+            for _ in 1..max_index(bits) {
+                x = field.mul(x, 2);
+                assert_ne!(x, 1, "{bits} bits");
+            }
+            assert_eq!(field.mul(x, 2), 1, "{bits} bits");
+        }
+    }
+
+    #[test]
+    fn combine_secretsjs_shares() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        let all = shares(&SECRETSJS_SHARES);
+        assert_eq!(combine(&all)?, secret);
+        assert_eq!(combine(&all[2..])?, secret);
+        assert_eq!(combine(&[&all[..1], &all[3..]].concat())?, secret);
+        assert_ne!(combine(&all[3..])?, secret);
+        // Shares given twice count once
+        assert_ne!(combine(&[&all[3..], &all[3..4]].concat())?, secret);
+
+        let three = shares(&[
+            "31085b342a42d1d7d86d03b43fe859dbce0",
+            "3309b31d9d770a6d701c56a8988ff1b4585",
+            "341c661db4a57124ae8093816425fff09a2",
+            "370eda354b070a79b98bf6b23347a4f5b02",
+        ]);
+        assert_eq!(combine(&three)?, [0x12, 0x34, 0xab, 0xcd]);
+
+        let twenty = shares(&["K000043cd42", "k00006aab98"]);
+        assert_eq!(combine(&twenty)?, [0x00, 0xff]);
+
+        // Leading zero digits are kept, and an odd number of digits gains one
+        let zeros = shares(&[
+            "8025f72f93e3dbb1ed8072e7a22d7de26ae",
+            "803fe4b0b21ad6811b48a394733bcb130a7",
+        ]);
+        assert_eq!(combine(&zeros)?, [0x00, 0x0a, 0xbc]);
+        let odd = shares(&[
+            "802d69444ab011670ef18ca373b1ef92de1",
+            "803bdde66708f1d481614afa2a8110bb841",
+        ]);
+        assert_eq!(combine(&odd)?, [0x0a, 0xbc]);
+        Ok(())
+    }
+
+    #[test]
+    fn split_and_combine() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        for bits in [3, 7, 8, 13, 16, 20] {
+            for pad_length in [0, 1, 64, 1024] {
+                let config = Config::builder()
+                    .bits(bits)
+                    .threshold(3)
+                    .num_shares(7)
+                    .pad_length(pad_length)
+                    .build();
+                let shares = split(&config, secret)?;
+                assert_eq!(shares.len(), 7);
+                assert!(shares.iter().all(|share| share.len() == shares[0].len()));
+                assert_eq!(combine(&shares[..3])?, secret);
+                assert_eq!(combine(&shares[4..])?, secret);
+            }
+        }
+
+        let shares = split(&Config::default(), b"\0\0hi")?;
+        assert!(shares[0].starts_with("801"));
+        assert!(shares[4].starts_with("805"));
+        // 128 bits of secret in 8 bit parts
+        assert_eq!(shares[0].len(), 3 + 32);
+        assert_eq!(combine(&shares[1..4])?, b"\0\0hi");
+
+        let config = Config::builder().bits(12).build();
+        let shares = split(&config, b"hi")?;
+        assert!(shares[0].starts_with("C001"));
+        Ok(())
+    }
+
+    #[test]
+    fn bad_config_errors() -> Result<()> {
+        let secret = b"hunter2";
+        check_err_result(
+            split(&Config::builder().bits(2).build(), secret),
+            "The number of bits must be from 3 to 20, but was 2",
+        )?;
+        check_err_result(
+            split(&Config::builder().bits(21).build(), secret),
+            "The number of bits must be from 3 to 20, but was 21",
+        )?;
+        check_err_result(
+            split(&Config::builder().bits(3).num_shares(8).build(), secret),
+            "The number of shares must be from 2 to 7, but was 8",
+        )?;
+        check_err_result(
+            split(&Config::builder().threshold(1).build(), secret),
+            "The threshold must be from 2 to the number of shares (5), but was 1",
+        )?;
+        check_err_result(
+            split(&Config::builder().threshold(6).build(), secret),
+            "The threshold must be from 2 to the number of shares (5), but was 6",
+        )?;
+        check_err_result(
+            split(&Config::builder().pad_length(1025).build(), secret),
+            "The padding length must be at most 1024, but was 1025",
+        )?;
+        check_err_result(split(&Config::default(), b""), "The secret cannot be empty")
+    }
+
+    #[test]
+    fn bad_shares_error() -> Result<()> {
+        check_err_result(combine(&[]), "The shares map cannot be empty")?;
+        for share in ["", "8", "801", "80g12", "2012", "L012", "80012"] {
+            assert!(combine(&shares(&[share])).is_err(), "{share}");
+        }
+        check_err_result(
+            combine(&shares(&["801ab", "3312"])),
+            "The shares were split with different numbers of bits",
+        )?;
+        check_err_result(
+            combine(&shares(&["801ab", "802abcd"])),
+            "The shares must be the same length",
+        )
+    }
+}
@@ -0,0 +1,315 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` share fields
+//!
+//! Shares split over GF(2^8) are written in one of the [share
+//! encodings](crate::ShareEncoding) as they are.  Shares split over GF(2^16)
+//! are written with a `gf16:` prefix, e.g.
+//! `gf16:hex:2c:0100abcd1234:000003e400001f1b`, and the share bytes handed to
+//! the encoding are
+//!
+//! | Bytes | Field                                               |
+//! |-------|-----------------------------------------------------|
+//! | 1     | the high byte of the share index                    |
+//! | 1     | 1 if the secret has an odd number of bytes, else 0  |
+//! | 2 * n | the share, as `n` big-endian 16-bit field elements  |
+//!
+//! with the low byte of the share index as the encoded index.
+
+use super::{
+    generic::{gen_shares_over, unlock_over},
+    robust::unlock_robust_over,
+};
+use crate::{
+    encoding::ShareEncoding,
+    error::SsssError::{EmptyShare, IndexTooLarge, InvalidShareFormat},
+    field::{Gf256, Gf65536},
+};
+use anyhow::Result;
+#[cfg(feature = "fuzz")]
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+
+/// The prefix of a share split over GF(2^16)
+const GF65536_PREFIX: &str = "gf16:";
+
+/// The finite field a secret is split over.  See
+/// [`SsssConfig`](crate::SsssConfig).
+///
+/// Each share index is a distinct non-zero element of the field, so the field
+/// limits the number of shares.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+pub enum FieldKind {
+    /// GF(2^8), for up to 255 shares of the same length as the secret
+    #[default]
+    Gf256,
+    /// GF(2^16), for up to 65535 shares.  The secret is split two bytes at a
+    /// time, and the shares carry two extra bytes.
+    Gf65536,
+}
+
+impl FieldKind {
+    /// The largest number of shares, and the largest share index
+    pub(crate) fn max_shares(self) -> u16 {
+        match self {
+            FieldKind::Gf256 => u16::from(u8::MAX),
+            FieldKind::Gf65536 => u16::MAX,
+        }
+    }
+
+    /// The prefix of the text of a share split over this field
+    pub(crate) fn prefix(self) -> &'static str {
+        match self {
+            FieldKind::Gf256 => "",
+            FieldKind::Gf65536 => GF65536_PREFIX,
+        }
+    }
+
+    /// Split the text of a share into its field and the encoded share
+    pub(crate) fn split(share: &str) -> (Self, &str) {
+        match share.get(..GF65536_PREFIX.len()) {
+            Some(prefix) if prefix.eq_ignore_ascii_case(GF65536_PREFIX) => {
+                (FieldKind::Gf65536, &share[GF65536_PREFIX.len()..])
+            }
+            _ => (FieldKind::Gf256, share),
+        }
+    }
+
+    /// Split the secret into the shares at the given indices, which have
+    /// already been checked against the field
+    pub(crate) fn split_secret(
+        self,
+        threshold: u16,
+        secret: &[u8],
+        indices: &[u16],
+    ) -> Result<Vec<Vec<u8>>> {
+        match self {
+            FieldKind::Gf256 => {
+                let secret: Vec<Gf256> = secret.iter().copied().map(Gf256).collect();
+                let xs = indices
+                    .iter()
+                    .map(|i| gf256_index(*i))
+                    .collect::<Result<Vec<Gf256>>>()?;
+                Ok(gen_shares_over(threshold, &secret, &xs)?
+                    .into_iter()
+                    .map(|share| share.into_iter().map(|y| y.0).collect())
+                    .collect())
+            }
+            FieldKind::Gf65536 => {
+                let odd = u8::from(secret.len() % 2 == 1);
+                let secret: Vec<Gf65536> = secret
+                    .chunks(2)
+                    .map(|pair| {
+                        Gf65536(u16::from_be_bytes([
+                            pair[0],
+                            pair.get(1).copied().unwrap_or(0),
+                        ]))
+                    })
+                    .collect();
+                let xs: Vec<Gf65536> = indices.iter().copied().map(Gf65536).collect();
+                Ok(gen_shares_over(threshold, &secret, &xs)?
+                    .into_iter()
+                    .map(|share| {
+                        let mut bytes = vec![odd];
+                        bytes.extend(share.into_iter().flat_map(|y| y.0.to_be_bytes()));
+                        bytes
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Combine the shares, all of the same length, into the secret
+    pub(crate) fn combine(self, shares: &[&(u16, Vec<u8>)]) -> Result<Vec<u8>> {
+        match self {
+            FieldKind::Gf256 => Ok(gf256_bytes(unlock_over(&gf256_points(shares)?)?)),
+            FieldKind::Gf65536 => {
+                let odd = shares.first().is_some_and(|(_, share)| share[0] == 1);
+                let points: Vec<(Gf65536, Vec<Gf65536>)> = gf65536_points(shares)
+                    .into_iter()
+                    .map(|(x, mut words)| (x, words.split_off(1)))
+                    .collect();
+                Ok(gf65536_bytes(odd, unlock_over(&points)?))
+            }
+        }
+    }
+
+    /// Combine the shares, all of the same length, into the secret, correcting
+    /// the shares that are wrong.  Also returns the indices of those shares.
+    pub(crate) fn combine_robust(
+        self,
+        threshold: u16,
+        shares: &[&(u16, Vec<u8>)],
+    ) -> Result<(Vec<u8>, Vec<u16>)> {
+        match self {
+            FieldKind::Gf256 => {
+                let (secret, bad) = unlock_robust_over(threshold, &gf256_points(shares)?)?;
+                let bad = bad.into_iter().map(|x| u16::from(x.0)).collect();
+                Ok((gf256_bytes(secret), bad))
+            }
+            FieldKind::Gf65536 => {
+                // The odd length flag is the same in every share, a constant
+                // polynomial, so it is corrected along with the secret
+                let (mut secret, bad) = unlock_robust_over(threshold, &gf65536_points(shares))?;
+                let words = secret.split_off(1);
+                let bad = bad.into_iter().map(|x| x.0).collect();
+                Ok((gf65536_bytes(secret[0].0 == 1, words), bad))
+            }
+        }
+    }
+
+    /// Encode a share with the given encoding
+    pub(crate) fn encode(self, encoder: &dyn ShareEncoding, index: u16, share: &[u8]) -> String {
+        let [hi, lo] = index.to_be_bytes();
+        match self {
+            FieldKind::Gf256 => encoder.encode(lo, share),
+            FieldKind::Gf65536 => {
+                let mut bytes = Vec::with_capacity(share.len() + 1);
+                bytes.push(hi);
+                bytes.extend(share);
+                format!("{GF65536_PREFIX}{}", encoder.encode(lo, &bytes))
+            }
+        }
+    }
+
+    /// Recover the share index and the share from the decoded index and
+    /// bytes of an encoded share
+    pub(crate) fn decode(self, index: u8, bytes: Vec<u8>) -> Result<(u16, Vec<u8>)> {
+        match self {
+            FieldKind::Gf256 => Ok((u16::from(index), bytes)),
+            FieldKind::Gf65536 => match bytes.split_first() {
+                Some((hi, share)) if share.len() % 2 == 1 && share[0] <= 1 => {
+                    if share.len() == 1 {
+                        Err(EmptyShare.into())
+                    } else {
+                        Ok((u16::from_be_bytes([*hi, index]), share.to_vec()))
+                    }
+                }
+                _ => Err(InvalidShareFormat.into()),
+            },
+        }
+    }
+}
+
+/// The GF(2^8) element of a share index
+fn gf256_index(index: u16) -> Result<Gf256> {
+    u8::try_from(index).map(Gf256).map_err(|_| {
+        IndexTooLarge {
+            index,
+            max: u16::from(u8::MAX),
+        }
+        .into()
+    })
+}
+
+/// The points of shares split over GF(2^8)
+fn gf256_points(shares: &[&(u16, Vec<u8>)]) -> Result<Vec<(Gf256, Vec<Gf256>)>> {
+    shares
+        .iter()
+        .map(|(x, share)| Ok((gf256_index(*x)?, share.iter().copied().map(Gf256).collect())))
+        .collect()
+}
+
+fn gf256_bytes(secret: Vec<Gf256>) -> Vec<u8> {
+    secret.into_iter().map(|y| y.0).collect()
+}
+
+/// The points of shares split over GF(2^16), with the odd length flag as the
+/// first element
+fn gf65536_points(shares: &[&(u16, Vec<u8>)]) -> Vec<(Gf65536, Vec<Gf65536>)> {
+    shares
+        .iter()
+        .map(|(x, share)| {
+            let words = [Gf65536(share[0].into())]
+                .into_iter()
+                .chain(
+                    share[1..]
+                        .chunks_exact(2)
+                        .map(|pair| Gf65536(u16::from_be_bytes([pair[0], pair[1]]))),
+                )
+                .collect();
+            (Gf65536(*x), words)
+        })
+        .collect()
+}
+
+fn gf65536_bytes(odd: bool, secret: Vec<Gf65536>) -> Vec<u8> {
+    let mut secret: Vec<u8> = secret.into_iter().flat_map(|y| y.0.to_be_bytes()).collect();
+    if odd {
+        let _pad = secret.pop();
+    }
+    secret
+}
+
+#[cfg(test)]
+mod test {
+    use super::FieldKind;
+    use crate::{encoding::Hex, utils::check_err_result};
+
+    #[test]
+    fn split_works() {
+        assert_eq!(
+            FieldKind::split("gf16:hex:01"),
+            (FieldKind::Gf65536, "hex:01")
+        );
+        assert_eq!(
+            FieldKind::split("GF16:hex:01"),
+            (FieldKind::Gf65536, "hex:01")
+        );
+        assert_eq!(FieldKind::split("hex:01"), (FieldKind::Gf256, "hex:01"));
+        assert_eq!(FieldKind::split("gf1"), (FieldKind::Gf256, "gf1"));
+    }
+
+    #[test]
+    fn fields_roundtrip() -> anyhow::Result<()> {
+        for field in [FieldKind::Gf256, FieldKind::Gf65536] {
+            for secret in [&b"correct horse battery staple"[..], b"odd"] {
+                let indices = [1, 77, u16::from(u8::MAX)];
+                let shares = field.split_secret(2, secret, &indices)?;
+                let shares: Vec<(u16, Vec<u8>)> = indices.into_iter().zip(shares).collect();
+                assert_eq!(field.combine(&[&shares[0], &shares[2]])?, secret);
+                assert_eq!(field.combine(&[&shares[1], &shares[2]])?, secret);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn large_gf256_indices_error() -> anyhow::Result<()> {
+        let field = FieldKind::Gf256;
+        check_err_result(
+            field.split_secret(2, b"secret", &[1, 256]),
+            "The share index 256 is larger than the field allows (255)",
+        )?;
+        let shares = [(1, b"secret".to_vec()), (300, b"secret".to_vec())];
+        check_err_result(
+            field.combine(&[&shares[0], &shares[1]]),
+            "The share index 300 is larger than the field allows (255)",
+        )?;
+        check_err_result(
+            field.combine_robust(1, &[&shares[0], &shares[1]]),
+            "The share index 300 is larger than the field allows (255)",
+        )
+    }
+
+    #[test]
+    fn gf65536_shares_roundtrip() -> anyhow::Result<()> {
+        let field = FieldKind::Gf65536;
+        let share = field.encode(&Hex, 0x1234, &[1, 0xab, 0xcd, 0xef, 0x01]);
+        assert!(share.starts_with("gf16:hex:34:12"));
+        let (field, inner) = FieldKind::split(&share);
+        let (index, bytes) = crate::encoding::detect(inner)?.decode(inner)?;
+        assert_eq!(
+            field.decode(index, bytes)?,
+            (0x1234, vec![1, 0xab, 0xcd, 0xef, 0x01])
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` secret sharing over any field
+
+use crate::{
+    error::SsssError::{
+        DuplicateShareIndex, EmptySecret, EmptyShare, EmptySharesMap, IndexZero,
+        ShareLengthMismatch, ThresholdToLow, ThresholdZero,
+    },
+    field::{Field, Polynomial},
+};
+use anyhow::Result;
+use rand::rng;
+
+/// Split a secret of field elements into a share for each of the given
+/// indices, any `threshold` of which can [unlock](unlock_over) it.
+///
+/// Each element of the secret is the constant term of its own random
+/// polynomial, so every share has as many elements as the secret.
+///
+/// # Errors
+/// * This function will generate an error if the secret is empty, the
+///   threshold is 0 or larger than the number of indices, or an index is 0 or
+///   given more than once.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{gen_shares_over, unlock_over, Field, Gf2_128};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = [Gf2_128(0x0123_4567_89ab_cdef)];
+/// let indices: Vec<Gf2_128> = (1..=5).map(Gf2_128).collect();
+///
+/// let shares = gen_shares_over(3, &secret, &indices)?;
+/// let points: Vec<(Gf2_128, Vec<Gf2_128>)> = indices.into_iter().zip(shares).collect();
+/// assert_eq!(unlock_over(&points[2..])?, secret);
+/// # Ok(())
+/// # }
+/// ```
+pub fn gen_shares_over<F: Field>(
+    threshold: u16,
+    secret: &[F],
+    indices: &[F],
+) -> Result<Vec<Vec<F>>> {
+    if secret.is_empty() {
+        return Err(EmptySecret.into());
+    } else if threshold == 0 {
+        return Err(ThresholdZero.into());
+    } else if usize::from(threshold) > indices.len() {
+        return Err(ThresholdToLow {
+            threshold,
+            shares: u16::try_from(indices.len()).unwrap_or(u16::MAX),
+        }
+        .into());
+    }
+    validate_indices(indices.iter())?;
+
+    let mut rng = rng();
+    let mut shares = vec![Vec::with_capacity(secret.len()); indices.len()];
+    for element in secret {
+        let p = Polynomial::random(&mut rng, usize::from(threshold) - 1, element.clone());
+        for (share, x) in shares.iter_mut().zip(indices) {
+            share.push(p.eval(x));
+        }
+    }
+    Ok(shares)
+}
+
+/// Combine `(index, share)` pairs made by [`gen_shares_over`] into the secret.
+///
+/// As with [`unlock`](crate::unlock), fewer shares than the threshold give a
+/// wrong secret rather than an error.
+///
+/// # Errors
+/// * This function will generate an error if there are no shares, the shares
+///   are empty or of different lengths, or an index is 0 or given more than
+///   once.
+pub fn unlock_over<F: Field>(shares: &[(F, Vec<F>)]) -> Result<Vec<F>> {
+    let len = validate_shares(shares)?;
+    (0..len)
+        .map(|i| {
+            let points: Vec<(F, F)> = shares
+                .iter()
+                .map(|(x, share)| (x.clone(), share[i].clone()))
+                .collect();
+            Polynomial::interpolate(&points, &F::zero()).ok_or_else(|| DuplicateShareIndex.into())
+        })
+        .collect()
+}
+
+/// Check that there are shares, all of the same non-zero length, at distinct
+/// non-zero indices, and return their length
+fn validate_shares<F: Field>(shares: &[(F, Vec<F>)]) -> Result<usize> {
+    let len = shares.first().ok_or(EmptySharesMap)?.1.len();
+    if len == 0 {
+        return Err(EmptyShare.into());
+    } else if shares.iter().any(|(_, share)| share.len() != len) {
+        return Err(ShareLengthMismatch.into());
+    }
+    validate_indices(shares.iter().map(|(x, _)| x))?;
+    Ok(len)
+}
+
+/// Check that the indices are distinct and non-zero.  The share at index 0
+/// would be the secret itself.
+fn validate_indices<'a, F: Field + 'a>(indices: impl Iterator<Item = &'a F>) -> Result<()> {
+    let mut seen: Vec<&F> = Vec::new();
+    for index in indices {
+        if index.is_zero() {
+            return Err(IndexZero.into());
+        } else if seen.contains(&index) {
+            return Err(DuplicateShareIndex.into());
+        }
+        seen.push(index);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{gen_shares_over, unlock_over};
+    use crate::{
+        field::{Field, Fp, Gf256, Gf65536, Mersenne127},
+        utils::check_err_result,
+    };
+    use anyhow::Result;
+    use num_bigint::BigUint;
+
+    fn roundtrip<F: Field>(secret: &[F], indices: &[F]) -> Result<()> {
+        let shares = gen_shares_over(3, secret, indices)?;
+        let points: Vec<(F, Vec<F>)> = indices.iter().cloned().zip(shares).collect();
+        assert_eq!(unlock_over(&points)?, secret);
+        assert_eq!(unlock_over(&points[1..4])?, secret);
+        assert_ne!(unlock_over(&points[..2])?, secret);
+        Ok(())
+    }
+
+    #[test]
+    fn split_and_unlock_over_fields() -> Result<()> {
+        roundtrip(
+            &[Gf256(1), Gf256(2), Gf256(3), Gf256(4)],
+            &[Gf256(1), Gf256(2), Gf256(3), Gf256(4), Gf256(5)],
+        )?;
+        roundtrip(
+            &[Gf65536(0xbeef), Gf65536(0xcafe)],
+            &[Gf65536(1), Gf65536(300), Gf65536(4000), Gf65536(50000)],
+        )?;
+        let secret =
+            BigUint::parse_bytes(b"123456789012345678901234567890", 10).unwrap_or_default();
+        let indices: Vec<Fp<Mersenne127>> = (1..=4_u32).map(|i| Fp::new(i.into())).collect();
+        roundtrip(&[Fp::new(secret)], &indices)
+    }
+
+    #[test]
+    fn bad_args_error() -> Result<()> {
+        let indices = [Gf256(1), Gf256(2), Gf256(3)];
+        check_err_result(
+            gen_shares_over::<Gf256>(2, &[], &indices),
+            "The secret cannot be empty",
+        )?;
+        check_err_result(
+            gen_shares_over(0, &[Gf256(7)], &indices),
+            "The threshold must be greater than 0",
+        )?;
+        check_err_result(
+            gen_shares_over(4, &[Gf256(7)], &indices),
+            "You have specified an invalid threshold.  It must be less than or equal to the number of shares. (4 is not <= 3)",
+        )?;
+        check_err_result(
+            gen_shares_over(2, &[Gf256(7)], &[Gf256(1), Gf256(0)]),
+            "A share index cannot be 0",
+        )?;
+        check_err_result(
+            gen_shares_over(2, &[Gf256(7)], &[Gf256(1), Gf256(1)]),
+            "A share index is used more than once",
+        )?;
+        check_err_result(unlock_over::<Gf256>(&[]), "The shares map cannot be empty")?;
+        check_err_result(
+            unlock_over(&[(Gf256(1), vec![])]),
+            "A share cannot be empty",
+        )?;
+        check_err_result(
+            unlock_over(&[(Gf256(1), vec![Gf256(1)]), (Gf256(2), vec![])]),
+            "The shares must be the same length",
+        )?;
+        check_err_result(
+            unlock_over(&[(Gf256(1), vec![Gf256(1)]), (Gf256(1), vec![Gf256(2)])]),
+            "A share index is used more than once",
+        )
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` share indices
+
+use super::FieldKind;
+use crate::error::SsssError::{DuplicateIndex, IndexCount, IndexTooLarge, IndexZero};
+use anyhow::Result;
+#[cfg(feature = "fuzz")]
+use arbitrary::Arbitrary;
+use rand::{rng, seq::IteratorRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// How the share indices, the x-coordinates at which the secret polynomial
+/// is evaluated, are chosen.  See [`SsssConfig`](crate::SsssConfig).
+///
+/// Explicit indices can be given to [`gen_shares_at`](crate::gen_shares_at).
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+pub enum Indices {
+    /// The shares are numbered `1..=num_shares`, so a holder of share 4 knows
+    /// there are at least 4 shares
+    #[default]
+    Sequential,
+    /// The shares have distinct random indices, non-zero elements of the
+    /// [field](FieldKind), which say nothing about the number of shares
+    Random,
+}
+
+impl Indices {
+    /// Choose the indices of the given number of shares
+    pub(crate) fn choose(self, num_shares: u16, field: FieldKind) -> Vec<u16> {
+        match self {
+            Indices::Sequential => (1..=num_shares).collect(),
+            Indices::Random => (1..=field.max_shares()).sample(&mut rng(), usize::from(num_shares)),
+        }
+    }
+}
+
+/// Check that there is one index for each share, and that the indices are
+/// distinct, non-zero elements of the field.  The share at index 0 would be
+/// the secret itself.
+pub(crate) fn validate(indices: &[u16], num_shares: u16, field: FieldKind) -> Result<()> {
+    if indices.len() != usize::from(num_shares) {
+        return Err(IndexCount {
+            expected: num_shares,
+            found: indices.len(),
+        }
+        .into());
+    }
+    let mut seen = HashSet::new();
+    for index in indices {
+        if *index == 0 {
+            return Err(IndexZero.into());
+        } else if *index > field.max_shares() {
+            return Err(IndexTooLarge {
+                index: *index,
+                max: field.max_shares(),
+            }
+            .into());
+        } else if !seen.insert(*index) {
+            return Err(DuplicateIndex { index: *index }.into());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FieldKind, Indices, validate};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+    use std::collections::HashSet;
+
+    #[test]
+    fn sequential_indices() {
+        assert_eq!(
+            Indices::Sequential.choose(5, FieldKind::Gf256),
+            [1, 2, 3, 4, 5]
+        );
+        assert!(Indices::Sequential.choose(0, FieldKind::Gf256).is_empty());
+    }
+
+    #[test]
+    fn random_indices_are_distinct() -> Result<()> {
+        for (num_shares, field) in [
+            (1, FieldKind::Gf256),
+            (5, FieldKind::Gf256),
+            (255, FieldKind::Gf256),
+            (5, FieldKind::Gf65536),
+            (1000, FieldKind::Gf65536),
+        ] {
+            let indices = Indices::Random.choose(num_shares, field);
+            validate(&indices, num_shares, field)?;
+            assert_eq!(indices.iter().collect::<HashSet<_>>().len(), indices.len());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bad_indices_error() -> Result<()> {
+        check_err_result(
+            validate(&[1, 2], 3, FieldKind::Gf256),
+            "Expected 3 share indices, but 2 were given",
+        )?;
+        check_err_result(
+            validate(&[1, 0, 2], 3, FieldKind::Gf256),
+            "A share index cannot be 0",
+        )?;
+        check_err_result(
+            validate(&[7, 3, 7], 3, FieldKind::Gf256),
+            "The share index 7 is used more than once",
+        )?;
+        check_err_result(
+            validate(&[7, 256], 2, FieldKind::Gf256),
+            "The share index 256 is larger than the field allows (255)",
+        )?;
+        validate(&[7, 256], 2, FieldKind::Gf65536)
+    }
+}
@@ -8,15 +8,31 @@
 
 //! `ssss` Shamir's Secret Sharing Scheme
 
+mod field;
+mod generic;
+mod indices;
+mod padding;
+mod robust;
 mod utils;
 
-use self::utils::{decode_share, encode_share, transpose};
+pub use self::field::FieldKind;
+pub use self::generic::{gen_shares_over, unlock_over};
+pub use self::indices::Indices;
+pub use self::padding::Padding;
+pub use self::robust::unlock_robust_over;
+use self::utils::unwrap_share;
+pub(crate) use self::utils::{decode_point, decode_share};
 use crate::{
+    base62::Base62,
+    encoding::{
+        Encoding, ShareEncoding, detect,
+        parity::{MAX_PARITY, ReedSolomon},
+    },
     error::SsssError::{
-        EmptySecret, EmptyShare, EmptySharesMap, SecretLength, ShareLengthMismatch, SharesZero,
-        ThresholdToLow, ThresholdZero,
+        EmptySecret, EmptyShare, EmptySharesMap, FieldMismatch, ParityTooLarge, SecretLength,
+        ShareLengthMismatch, SharesZero, ThresholdToLow, ThresholdZero, TooManyShares,
+        VerificationFailed, VerifyTooManyShares,
     },
-    gf256,
 };
 use anyhow::Result;
 #[cfg(feature = "fuzz")]
@@ -25,24 +41,69 @@ use bon::Builder;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+/// The maximum number of shares that can be checked when `verify` is enabled
+const MAX_VERIFY_SHARES: u16 = 16;
+
 /// Configuration used to drive the [`gen_shares`] function.
 ///
 /// # Notes
 /// The default configuration will specify 5 shares with a
-/// threshold of 3.  The maximum secret size is [`u16::MAX`] (65536)
+/// threshold of 3.  The maximum secret size is [`u16::MAX`] (65536).  At most
+/// 255 shares can be generated over the default [field](FieldKind).
 #[derive(Builder, Clone, Copy, Debug, Deserialize, Serialize)]
 #[cfg_attr(feature = "fuzz", derive(Arbitrary))]
 pub struct SsssConfig {
     /// The total shares to be generate from a secret
     #[builder(default = 5)]
-    num_shares: u8,
+    num_shares: u16,
     /// The threshold of valid shares required to unlock a secret
     /// This must be less than or equal to the number of shares
     #[builder(default = 3)]
-    threshold: u8,
-    /// The maximum secret size in bytes
+    threshold: u16,
+    /// The maximum secret size in bytes.  The checksum of a share covers at
+    /// most 65530 characters, so the text encodings give a lower limit, e.g.
+    /// about 32 KiB for hex.
     #[builder(default = usize::from(u16::MAX))]
     max_secret_size: usize,
+    /// Verify the generated shares before returning them.
+    ///
+    /// Every subset of `threshold` shares must reconstruct the secret, and
+    /// every smaller subset must fail the integrity tag of the
+    /// [padding](Padding), so that [`unlock`] cannot mistake what it
+    /// reconstructs for the secret.  The tag is always added when verifying,
+    /// so with `Padding::None` the secret gets 10 bytes of padding, just the
+    /// tag and its length.  The number of subsets grows as `2^n`, so this is
+    /// limited to configurations of at most 16 shares.
+    #[builder(default)]
+    verify: bool,
+    /// Encode the shares without a random nonce, so that the same share
+    /// always has the same text.  See [`canonicalize`].
+    #[builder(default)]
+    canonical: bool,
+    /// The text encoding used for the generated shares
+    #[builder(default)]
+    encoding: Encoding,
+    /// The number of Reed–Solomon parity bytes added to each share, so that a
+    /// damaged share can be [repaired](repair).
+    ///
+    /// Each parity byte lets one erased byte be restored, and each two let one
+    /// wrong byte be corrected.  Shares longer than `255 - parity` bytes carry
+    /// this many parity bytes for every such block.  The default of 0 adds no
+    /// parity.
+    #[builder(default)]
+    parity: u8,
+    /// How the secret is [padded](Padding) before it is split, so that the
+    /// shares do not give away its length.  The default adds no padding.
+    #[builder(default)]
+    padding: Padding,
+    /// How the share [indices](Indices) are chosen.  The default numbers the
+    /// shares `1..=num_shares`.
+    #[builder(default)]
+    indices: Indices,
+    /// The finite [field](FieldKind) the secret is split over.  The default
+    /// of GF(2^8) allows up to 255 shares, GF(2^16) up to 65535.
+    #[builder(default)]
+    field: FieldKind,
 }
 
 impl Default for SsssConfig {
@@ -52,6 +113,14 @@ impl Default for SsssConfig {
 }
 
 impl SsssConfig {
+    pub(crate) fn num_shares(&self) -> u16 {
+        self.num_shares
+    }
+
+    pub(crate) fn threshold(&self) -> u16 {
+        self.threshold
+    }
+
     fn validate(&self) -> Result<()> {
         if self.num_shares == 0 {
             Err(SharesZero.into())
@@ -63,6 +132,24 @@ impl SsssConfig {
                 shares: self.num_shares,
             }
             .into())
+        } else if self.num_shares > self.field.max_shares() {
+            Err(TooManyShares {
+                shares: self.num_shares,
+                max: self.field.max_shares(),
+            }
+            .into())
+        } else if self.parity > MAX_PARITY {
+            Err(ParityTooLarge {
+                parity: self.parity,
+                max: MAX_PARITY,
+            }
+            .into())
+        } else if self.verify && self.num_shares > MAX_VERIFY_SHARES {
+            Err(VerifyTooManyShares {
+                shares: self.num_shares,
+                max: MAX_VERIFY_SHARES,
+            }
+            .into())
         } else {
             Ok(())
         }
@@ -77,6 +164,16 @@ impl SsssConfig {
 /// * This function will generate an error if `secret` is empty or larger than `max_secret_size` in the configuration.
 /// * This function will generate an error if either `num_shares` or `threshold` are 0.
 /// * This function will generate an error if `threshold` is greater than `num_shares`
+/// * This function will generate an error if `num_shares` is greater than the
+///   [field](FieldKind) allows.
+/// * This function will generate an error if `parity` is greater than 64.
+/// * This function will generate an error if the secret does not fit the
+///   `padding`, or the padded secret would be larger than 131072 bytes.
+/// * This function will generate an error if `verify` is enabled and the generated
+///   shares fail verification, or if `num_shares` is too large to verify.
+/// * This function will generate an error if a share would be longer than its
+///   checksum covers, 65530 checksummed characters, e.g. a secret of more
+///   than about 32 KiB in the hex encoding.
 ///
 /// # Example
 /// ```
@@ -96,26 +193,126 @@ impl SsssConfig {
 /// # Ok(())
 /// # }
 pub fn gen_shares(config: &SsssConfig, secret: &[u8]) -> Result<Vec<String>> {
+    let indices = config.indices.choose(config.num_shares, config.field);
+    gen_shares_at(config, secret, &indices)
+}
+
+/// Generate shares at the given indices, the x-coordinates at which the
+/// secret polynomial is evaluated, instead of the indices chosen by the
+/// configuration.
+///
+/// The indices could be derived from employee IDs, for example, so that each
+/// share is tied to its holder.  There must be one index for each share, and
+/// the indices must be distinct, non-zero elements of the [field](FieldKind).
+/// The shares are returned in the order the indices are given.
+///
+/// # Errors
+/// * This function will generate an error in the same cases as [`gen_shares`].
+/// * This function will generate an error if the number of indices is not
+///   `num_shares`, or an index is 0, too large for the field or given more
+///   than once.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{gen_shares_at, unlock, SsssConfig};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = "correct horse battery staple".as_bytes();
+/// let config = SsssConfig::builder().num_shares(3).threshold(2).build();
+///
+/// let shares = gen_shares_at(&config, &secret, &[17, 42, 230])?;
+/// assert_eq!(unlock(&shares[1..])?, secret);
+/// # Ok(())
+/// # }
+/// ```
+pub fn gen_shares_at(config: &SsssConfig, secret: &[u8], indices: &[u16]) -> Result<Vec<String>> {
     validate_split_args(config, secret)?;
+    indices::validate(indices, config.num_shares, config.field)?;
+    let padding = if config.verify {
+        config.padding.tagged(secret.len())
+    } else {
+        config.padding
+    };
+    let secret = &padding.pad(secret)?;
+
+    let shares = split(config, secret, indices)?;
+    // A share longer than its checksum covers is refused when it is decoded,
+    // so it is refused here rather than handed out
+    for share in &shares {
+        let _point = decode_point(share.clone())?;
+    }
+    if config.verify {
+        // The shares are never split again when a check fails.  Keeping only
+        // the splits that pass a check on the secret itself would tell every
+        // holder something about it.
+        verify_shares(config, secret, &shares)?;
+    }
+    Ok(shares)
+}
+
+fn split(config: &SsssConfig, secret: &[u8], indices: &[u16]) -> Result<Vec<String>> {
     let SsssConfig {
-        num_shares,
         threshold,
-        max_secret_size: _,
+        canonical,
+        encoding,
+        parity,
+        field,
+        ..
     } = config;
+    let mut encoder = encoding.encoder(*canonical);
+    if *parity > 0 {
+        encoder = Box::new(ReedSolomon::new(*parity, encoder));
+    }
 
-    let coeff_fn =
-        |secret_byte: &u8| -> Vec<u8> { gf256::generate_coeffs(*threshold, *secret_byte) };
-    let gf_add_fn =
-        |p: Vec<u8>| -> Vec<u8> { (1..=*num_shares).map(|i| gf256::eval(&p, i)).collect() };
+    Ok(indices
+        .iter()
+        .zip(field.split_secret(*threshold, secret, indices)?)
+        .map(|(index, share)| field.encode(encoder.as_ref(), *index, &share))
+        .collect())
+}
 
-    let secret: Vec<Vec<u8>> = secret.iter().map(coeff_fn).map(gf_add_fn).collect();
-    Ok(transpose(&secret)
+/// Decode the given shares and check that every threshold subset of them
+/// reconstructs the padded secret, and that no smaller subset passes the
+/// integrity tag of the padding.
+fn verify_shares(config: &SsssConfig, secret: &[u8], shares: &[String]) -> Result<()> {
+    let decoded = shares
         .iter()
         .cloned()
-        .enumerate()
-        .map(encode_share)
-        .filter_map(Result::ok)
-        .collect())
+        .map(decode_point)
+        .map(|point| point.map(|(_, index, share)| (index, share)))
+        .collect::<Result<Vec<(u16, Vec<u8>)>>>()
+        .map_err(|_| VerificationFailed)?;
+
+    if decoded.len() != usize::from(config.num_shares)
+        || decoded
+            .iter()
+            .any(|(_, share)| share.len() != decoded[0].1.len())
+    {
+        return Err(VerificationFailed.into());
+    }
+
+    let threshold = config.threshold.into();
+    for mask in 1_u32..(1 << decoded.len()) {
+        let size = usize::try_from(mask.count_ones())?;
+        if size > threshold {
+            continue;
+        }
+        let subset: Vec<&(u16, Vec<u8>)> = decoded
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| mask & (1 << i) != 0)
+            .map(|(_, share)| share)
+            .collect();
+        let reconstructed = config.field.combine(&subset)?;
+
+        if (size == threshold && reconstructed != secret)
+            || (size < threshold && padding::is_padded(&reconstructed))
+        {
+            return Err(VerificationFailed.into());
+        }
+    }
+    Ok(())
 }
 
 fn validate_split_args(config: &SsssConfig, secret: &[u8]) -> Result<()> {
@@ -128,22 +325,31 @@ fn validate_split_args(config: &SsssConfig, secret: &[u8]) -> Result<()> {
         }
         .into())
     } else {
-        config.validate()
+        config.validate()?;
+        config.padding.validate(secret.len())
     }
 }
 
 /// Attempt to unlock the secret given some [`shares`](gen_shares).
 ///
 /// # Notes
+/// * The encoding of each share is detected from its text, so shares
+///   in different [encodings](ShareEncoding) can be combined.
 /// * If there aren't enough shares to meet the threshold defined when
 ///   the shares were created the resulting vector of bytes will be gibberish.
 /// * If there are more shares supplied than were defined when the shares
 ///   were created the resulting vector of bytes will be gibberish.
+/// * Damaged shares generated with [parity](SsssConfig) are
+///   [repaired](repair) first.
+/// * The [padding](Padding) of a padded secret is removed.
 ///
 /// # Errors
 /// * This function will generate an error if the `shares` map is empty.
 /// * This function will generate an error if the `shares` within the map are not
-///   all the same length.
+///   all the same length, or were not all split over the same [field](FieldKind).
+/// * This function will generate an error if a share cannot be decoded, such
+///   as a mistyped share whose checksum does not match, which is reported
+///   with the likely position of the mistake.
 ///
 /// # Example
 /// ```
@@ -185,28 +391,214 @@ fn validate_split_args(config: &SsssConfig, secret: &[u8]) -> Result<()> {
 /// # Ok(())
 /// # }
 pub fn unlock(shares: &[String]) -> Result<Vec<u8>> {
-    let decoded = shares
+    let points = decode_points(shares)?;
+    let field = points
+        .first()
+        .map_or(FieldKind::Gf256, |(field, _, _)| *field);
+    if points.iter().any(|(other, _, _)| *other != field) {
+        return Err(FieldMismatch.into());
+    }
+    let decoded: HashMap<u16, Vec<u8>> = points
+        .into_iter()
+        .map(|(_, index, share)| (index, share))
+        .collect();
+    validate_join_args(&decoded)?;
+    let shares: Vec<(u16, Vec<u8>)> = decoded.into_iter().collect();
+    let secret = field.combine(&shares.iter().collect::<Vec<_>>())?;
+    Ok(padding::unpad(secret))
+}
+
+/// Unlock a secret from shares of which some may be wrong, given the
+/// threshold it was split with.
+///
+/// Where [`unlock`] interpolates through every share, so that a single wrong
+/// share gives a wrong secret, this decodes the shares as an error-correcting
+/// code.  With `m` shares, up to `⌊(m - threshold) / 2⌋` of them can be
+/// wrong, whether damaged or forged, and the secret is still recovered.  See
+/// [`unlock_robust_over`] for the details.
+///
+/// Returns the secret, and the indices of the shares that are wrong, in the
+/// order they were given.  As with [`unlock`], shares that cannot be decoded
+/// are skipped, and a share given twice is used once.  A forged share need
+/// not look like the others, so shares split over another [field](FieldKind)
+/// than most of them, shares of another length than most of them, and
+/// different shares with the same index are left out and reported as wrong,
+/// rather than stopping the unlock.
+///
+/// # Errors
+/// * This function will generate an error if there are fewer usable shares
+///   than the threshold, or too many of them are wrong to recover the secret.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{gen_shares, unlock, unlock_robust, Hex, ShareEncoding, SsssConfig};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = "correct horse battery staple".as_bytes();
+/// let config = SsssConfig::builder().num_shares(7).threshold(3).build();
+/// let mut shares = gen_shares(&config, &secret)?;
+///
+/// // Two shareholders hand in forged shares
+/// shares[2] = Hex.encode(3, &[0; 28]);
+/// shares[6] = Hex.encode(7, b"Tr0ub4dor&3 Tr0ub4dor&3 Tr0u");
+/// assert_ne!(unlock(&shares)?, secret);
+///
+/// let (unlocked, bad) = unlock_robust(3, &shares)?;
+/// assert_eq!(unlocked, secret);
+/// assert_eq!(bad, [3, 7]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn unlock_robust(threshold: u16, shares: &[String]) -> Result<(Vec<u8>, Vec<u16>)> {
+    // A forged share need not decode, so shares that cannot be decoded are
+    // skipped rather than stopping the unlock
+    let points: Vec<(FieldKind, u16, Vec<u8>)> = shares
         .iter()
         .cloned()
-        .map(decode_share)
+        .map(decode_point)
         .filter_map(Result::ok)
         .collect();
-    let secret_len = validate_join_args(&decoded)?;
-    let mut secret = vec![];
-
-    for i in 0..secret_len {
-        let mut points = vec![vec![0; 2]; decoded.len()];
-        for (idx, (k, v)) in decoded.iter().enumerate() {
-            points[idx][0] = *k;
-            points[idx][1] = v[i];
+    let field = [FieldKind::Gf65536, FieldKind::Gf256]
+        .into_iter()
+        .max_by_key(|field| points.iter().filter(|(other, _, _)| other == field).count())
+        .unwrap_or_default();
+    let used: Vec<(u16, Vec<u8>)> = points
+        .iter()
+        .filter(|(other, _, _)| *other == field)
+        .map(|(_, index, share)| (*index, share.clone()))
+        .collect();
+    let (secret, wrong) = field.combine_robust(threshold, &used.iter().collect::<Vec<_>>())?;
+
+    let mut bad = Vec::new();
+    for (other, index, _) in &points {
+        if (*other != field || wrong.contains(index)) && !bad.contains(index) {
+            bad.push(*index);
         }
-        secret.push(gf256::interpolate(&points));
     }
+    Ok((padding::unpad(secret), bad))
+}
+
+/// Decode the shares, stopping at the first that cannot be decoded
+fn decode_points(shares: &[String]) -> Result<Vec<(FieldKind, u16, Vec<u8>)>> {
+    shares.iter().cloned().map(decode_point).collect()
+}
+
+/// Re-encode a share in its canonical form.
+///
+/// Shares are normally encoded with a random nonce, so the same share has a
+/// different text every time it is encoded.  The canonical form has no nonce,
+/// so two shares with the same contents always have the same canonical text,
+/// regardless of how they were originally encoded.
+///
+/// # Errors
+/// * This function will generate an error if the share cannot be decoded.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{canonicalize, gen_shares, unlock, SsssConfig};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = "correct horse battery staple".as_bytes();
+/// let shares = gen_shares(&SsssConfig::default(), &secret)?;
+///
+/// let canonical = shares
+///     .iter()
+///     .map(|share| canonicalize(share))
+///     .collect::<Result<Vec<String>>>()?;
+/// assert_eq!(canonicalize(&canonical[0])?, canonical[0]);
+/// assert_eq!(unlock(&canonical)?, secret);
+/// # Ok(())
+/// # }
+pub fn canonicalize(share: &str) -> Result<String> {
+    reencode(share, &Base62::new(true))
+}
+
+/// Re-encode a share in the given [encoding](ShareEncoding).
+///
+/// The encoding of the given share is detected from its text.
+///
+/// # Errors
+/// * This function will generate an error if the share cannot be decoded.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{gen_shares, reencode, unlock, Bech32m, Hex, SsssConfig};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = "correct horse battery staple".as_bytes();
+/// let mut shares = gen_shares(&SsssConfig::default(), &secret)?;
+///
+/// // Shares in different encodings can be unlocked together
+/// shares[0] = reencode(&shares[0], &Hex)?;
+/// shares[1] = reencode(&shares[1], &Bech32m::new("vault"))?;
+/// assert!(shares[0].starts_with("hex:"));
+/// assert!(shares[1].starts_with("vault1"));
+/// assert_eq!(unlock(&shares)?, secret);
+/// # Ok(())
+/// # }
+pub fn reencode(share: &str, encoding: &dyn ShareEncoding) -> Result<String> {
+    let share = unwrap_share(share.to_string())?;
+    let (field, share) = FieldKind::split(&share);
+    let (idx, share) = detect(share)?.decode(share)?;
+    Ok(format!(
+        "{}{}",
+        field.prefix(),
+        encoding.encode(idx, &share)
+    ))
+}
 
-    Ok(secret)
+/// Repair a damaged share generated with [parity](SsssConfig).
+///
+/// The share is re-encoded with its parity, so the result can be written down
+/// again in place of the damaged copy.  A character that cannot be read at all
+/// should be replaced with a `?`, keeping the length of the share unchanged;
+/// the parity then only has to restore the bytes it held, rather than find
+/// them first.  Mnemonic shares can only be repaired when every word can still
+/// be read.
+///
+/// # Errors
+/// * This function will generate an error if the share is too damaged to be
+///   repaired, or cannot be decoded.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{gen_shares, repair, unlock, Encoding, SsssConfig};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = "correct horse battery staple".as_bytes();
+/// let config = SsssConfig::builder()
+///     .encoding(Encoding::Base32)
+///     .parity(8)
+///     .build();
+/// let mut shares = gen_shares(&config, &secret)?;
+/// assert!(shares[0].starts_with("rs8:b32:"));
+///
+/// // Smudge a few characters of the first share
+/// let damaged = format!("{}???{}", &shares[0][..20], &shares[0][23..]);
+/// assert_eq!(repair(&damaged)?, shares[0]);
+///
+/// // Damaged shares are also repaired when unlocking
+/// shares[0] = damaged;
+/// assert_eq!(unlock(&shares)?, secret);
+/// # Ok(())
+/// # }
+pub fn repair(share: &str) -> Result<String> {
+    let share = unwrap_share(share.to_string())?;
+    let (field, share) = FieldKind::split(&share);
+    let encoding = detect(share)?;
+    let (idx, bytes) = encoding.decode(share)?;
+    Ok(format!(
+        "{}{}",
+        field.prefix(),
+        encoding.encode(idx, &bytes)
+    ))
 }
 
-fn validate_join_args(shares: &HashMap<u8, Vec<u8>>) -> Result<usize> {
+fn validate_join_args(shares: &HashMap<u16, Vec<u8>>) -> Result<()> {
     if shares.is_empty() {
         Err(EmptySharesMap.into())
     } else {
@@ -215,7 +607,7 @@ fn validate_join_args(shares: &HashMap<u8, Vec<u8>>) -> Result<usize> {
         if len == 0 {
             Err(EmptyShare.into())
         } else if lengths.iter().all(|x| *x == len) {
-            Ok(len)
+            Ok(())
         } else {
             for (k, v) in shares {
                 eprintln!("{k}: {v:?} => {}", v.len());
@@ -227,8 +619,16 @@ fn validate_join_args(shares: &HashMap<u8, Vec<u8>>) -> Result<usize> {
 
 #[cfg(test)]
 mod test {
-    use super::{SsssConfig, gen_shares, unlock, utils::encode_share};
+    use super::{
+        FieldKind, Indices, Padding, SsssConfig, canonicalize, gen_shares, gen_shares_at, reencode,
+        repair, unlock, unlock_robust,
+        utils::{decode_point, decode_share},
+    };
     use crate::utils::{check_err_result, remove_random_entry};
+    use crate::{
+        base62::Base62,
+        encoding::{Base32, Base64Url, Bech32m, Bip39, Encoding, Hex, PgpWords, ShareEncoding},
+    };
     use anyhow::Result;
     use rand::rng;
 
@@ -282,8 +682,8 @@ mod test {
     #[test]
     fn shares_of_differing_lengths() -> Result<()> {
         let bad_shares = vec![
-            encode_share((1, "abc".as_bytes().to_vec()))?,
-            encode_share((2, "abcdef".as_bytes().to_vec()))?,
+            Base62::default().encode(2, "abc".as_bytes()),
+            Base62::default().encode(3, "abcdef".as_bytes()),
         ];
         let result = unlock(&bad_shares);
         check_err_result(result, "The shares must be the same length")
@@ -291,7 +691,7 @@ mod test {
 
     #[test]
     fn empty_shares() -> Result<()> {
-        let bad_shares = vec![encode_share((1, vec![]))?];
+        let bad_shares = vec![Base62::default().encode(2, &[])];
         let result = unlock(&bad_shares);
         check_err_result(result, "A share cannot be empty")
     }
@@ -301,15 +701,453 @@ mod test {
         let config = SsssConfig::default();
         let secret = "abc".as_bytes();
         let mut shares = gen_shares(&config, secret)?;
-        shares.push(encode_share((6, "abc".as_bytes().to_vec()))?);
-        shares.push(encode_share((7, "def".as_bytes().to_vec()))?);
-        shares.push(encode_share((8, "ghi".as_bytes().to_vec()))?);
+        shares.push(Base62::default().encode(6, "abc".as_bytes()));
+        shares.push(Base62::default().encode(7, "def".as_bytes()));
+        shares.push(Base62::default().encode(8, "ghi".as_bytes()));
         assert_eq!(shares.len(), 8);
         let unlocked = unlock(&shares)?;
         assert_ne!(unlocked, secret);
         Ok(())
     }
 
+    #[test]
+    fn verify_too_many_shares() -> Result<()> {
+        let config = SsssConfig::builder()
+            .num_shares(17)
+            .threshold(3)
+            .verify(true)
+            .build();
+        let result = gen_shares(&config, "a".as_bytes());
+        check_err_result(
+            result,
+            "Verification is limited to 16 shares, but 17 were requested",
+        )
+    }
+
+    #[test]
+    fn verify_split_and_join() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        for (num_shares, threshold) in [(1, 1), (5, 1), (5, 3), (5, 5), (8, 4)] {
+            let config = SsssConfig::builder()
+                .num_shares(num_shares)
+                .threshold(threshold)
+                .verify(true)
+                .build();
+            let shares = gen_shares(&config, secret)?;
+            assert_eq!(shares.len(), usize::from(num_shares));
+            assert_eq!(unlock(&shares)?, secret);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn verify_short_secret() -> Result<()> {
+        let config = SsssConfig::builder().verify(true).build();
+        for _ in 0..32 {
+            let shares = gen_shares(&config, "a".as_bytes())?;
+            assert_eq!(unlock(&shares)?, "a".as_bytes());
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn verify_short_padded_secret() -> Result<()> {
+        let config = SsssConfig::builder()
+            .num_shares(12)
+            .threshold(9)
+            .padding(Padding::Fixed(16))
+            .verify(true)
+            .build();
+        let shares = gen_shares(&config, "a".as_bytes())?;
+        assert_eq!(unlock(&shares[3..])?, "a".as_bytes());
+        assert_eq!(unlock(&shares[4..])?.len(), 16);
+        Ok(())
+    }
+
+    #[test]
+    fn verify_unpadded_secret_adds_the_tag() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        let config = SsssConfig::builder()
+            .num_shares(6)
+            .threshold(4)
+            .verify(true)
+            .build();
+        let shares = gen_shares(&config, secret)?;
+        for share in &shares {
+            assert_eq!(decode_share(share.clone())?.1.len(), secret.len() + 10);
+        }
+        assert_eq!(unlock(&shares)?, secret);
+        assert_eq!(unlock(&shares[2..])?, secret);
+        assert_eq!(unlock(&shares[3..])?.len(), secret.len() + 10);
+        Ok(())
+    }
+
+    #[test]
+    fn unlock_legacy_shares() -> Result<()> {
+        let shares = vec![
+            "33jCjZe3IiGKzB:2n9WMPdcfwwXOTyAspUoHORa1JbGOZGv9mrB4CoNZi3y2naqzN".to_string(),
+            "dA0Ae4PlUzJsQB:qjRFCjVjSQUplJN04XPkcycRALl7lTwFVJJQfbw4OWxQMv6d5W".to_string(),
+            "N7ZIELkfewuwz8:KHHOHSMxEPFPUA0uiGsdwpHGc9TjYTioxsuTixcUUf5Wg5ICaW".to_string(),
+        ];
+        assert_eq!(unlock(&shares)?, "correct horse battery staple".as_bytes());
+        Ok(())
+    }
+
+    #[test]
+    fn canonical_shares_are_deterministic() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        let config = SsssConfig::builder().canonical(true).build();
+        let shares = gen_shares(&config, secret)?;
+        assert_eq!(unlock(&shares)?, secret);
+        for share in &shares {
+            assert_eq!(&canonicalize(share)?, share);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn canonicalize_normalizes_all_forms() -> Result<()> {
+        let share = "abc".as_bytes().to_vec();
+        let first = Base62::default().encode(1, &share);
+        let second = Base62::default().encode(1, &share);
+        assert_ne!(first, second);
+        assert_eq!(canonicalize(&first)?, canonicalize(&second)?);
+        assert_eq!(canonicalize(&first)?, Base62::new(true).encode(1, &share));
+        let legacy = "33jCjZe3IiGKzB:2n9WMPdcfwwXOTyAspUoHORa1JbGOZGv9mrB4CoNZi3y2naqzN";
+        let canonical = canonicalize(legacy)?;
+        assert_eq!(canonicalize(&canonical)?, canonical);
+        Ok(())
+    }
+
+    #[test]
+    fn split_and_join_all_encodings() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        for encoding in [
+            Encoding::Base62,
+            Encoding::Hex,
+            Encoding::Base64Url,
+            Encoding::Base32,
+            Encoding::Bech32m,
+            Encoding::Bip39,
+            Encoding::PgpWords,
+        ] {
+            let config = SsssConfig::builder().encoding(encoding).build();
+            let shares = gen_shares(&config, secret)?;
+            assert_eq!(unlock(&shares)?, secret);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn unlock_mixed_encodings() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        let shares = gen_shares(&SsssConfig::default(), secret)?;
+        let mixed = vec![
+            reencode(&shares[0], &Hex)?,
+            reencode(&shares[1], &Base64Url)?,
+            reencode(&shares[2], &Base32)?.to_lowercase(),
+            reencode(&shares[3], &Bech32m::new("test"))?,
+            reencode(&shares[4], &Base62::new(true))?,
+        ];
+        assert_eq!(unlock(&mixed)?, secret);
+        assert_eq!(unlock(&mixed[1..4])?, secret);
+        let words = vec![
+            reencode(&shares[0], &Bip39)?,
+            reencode(&shares[2], &PgpWords)?,
+            shares[4].clone(),
+        ];
+        assert_eq!(unlock(&words)?, secret);
+        Ok(())
+    }
+
+    #[test]
+    fn parity_too_large() -> Result<()> {
+        let config = SsssConfig::builder().parity(65).build();
+        let result = gen_shares(&config, "a".as_bytes());
+        check_err_result(
+            result,
+            "The number of parity bytes '65' is larger than the maximum allowed '64'",
+        )
+    }
+
+    #[test]
+    fn damaged_shares_unlock() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        let config = SsssConfig::builder().parity(16).verify(true).build();
+        let shares = gen_shares(&config, secret)?;
+        let damaged: Vec<String> = shares
+            .iter()
+            .map(|share| format!("{}?{}", &share[..30], &share[31..]))
+            .collect();
+        assert_eq!(unlock(&damaged[..3])?, secret);
+        for (share, damaged) in shares.iter().zip(&damaged) {
+            assert_eq!(canonicalize(&repair(damaged)?)?, canonicalize(share)?);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn repair_without_parity() -> Result<()> {
+        let shares = gen_shares(&SsssConfig::default(), "abc".as_bytes())?;
+        assert_eq!(
+            canonicalize(&repair(&shares[0])?)?,
+            canonicalize(&shares[0])?
+        );
+        check_err_result(
+            repair(&shares[0].replacen(':', "?", 1)),
+            "Invalid share checksum, check the share near character 18",
+        )
+    }
+
+    #[test]
+    fn padding_hides_the_length() -> Result<()> {
+        let config = SsssConfig::builder()
+            .padding(Padding::Fixed(64))
+            .verify(true)
+            .build();
+        for secret in [&b"hunter2"[..], b"correct horse battery staple"] {
+            let mut shares = gen_shares(&config, secret)?;
+            for share in &shares {
+                assert_eq!(decode_share(share.clone())?.1.len(), 64);
+            }
+            assert_eq!(unlock(&shares)?, secret);
+            shares.truncate(3);
+            assert_eq!(unlock(&shares)?, secret);
+            shares.truncate(2);
+            assert_eq!(unlock(&shares)?.len(), 64);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn padding_too_small() -> Result<()> {
+        let config = SsssConfig::builder().padding(Padding::Fixed(16)).build();
+        check_err_result(
+            gen_shares(&config, b"correct horse battery staple"),
+            "The secret length '28' and its padding do not fit the padded size '16'",
+        )
+    }
+
+    #[test]
+    fn random_indices() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        let config = SsssConfig::builder()
+            .indices(Indices::Random)
+            .verify(true)
+            .build();
+        let mut shares = gen_shares(&config, secret)?;
+        let mut rng = rng();
+        remove_random_entry(&mut rng, &mut shares);
+        remove_random_entry(&mut rng, &mut shares);
+        assert_eq!(unlock(&shares)?, secret);
+        Ok(())
+    }
+
+    #[test]
+    fn custom_indices() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        let indices = [255, 1, 128, 77];
+        for encoding in [
+            Encoding::Base62,
+            Encoding::Hex,
+            Encoding::Bech32m,
+            Encoding::Bip39,
+        ] {
+            let config = SsssConfig::builder()
+                .num_shares(4)
+                .threshold(2)
+                .encoding(encoding)
+                .build();
+            let shares = gen_shares_at(&config, secret, &indices)?;
+            for (share, index) in shares.iter().zip(indices) {
+                assert_eq!(decode_point(share.clone())?.1, index);
+            }
+            assert_eq!(unlock(&[shares[0].clone(), shares[2].clone()])?, secret);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bad_custom_indices() -> Result<()> {
+        let config = SsssConfig::builder().num_shares(3).threshold(2).build();
+        check_err_result(
+            gen_shares_at(&config, b"correct horse", &[3, 0, 1]),
+            "A share index cannot be 0",
+        )?;
+        check_err_result(
+            gen_shares_at(&config, b"correct horse", &[3, 1, 3]),
+            "The share index 3 is used more than once",
+        )
+    }
+
+    #[test]
+    fn gf65536_split_and_join() -> Result<()> {
+        let config = SsssConfig::builder()
+            .num_shares(1000)
+            .threshold(3)
+            .field(FieldKind::Gf65536)
+            .build();
+        for secret in [&b"correct horse battery staple"[..], b"odd secret"] {
+            let mut shares = gen_shares(&config, secret)?;
+            assert_eq!(shares.len(), 1000);
+            assert!(shares.iter().all(|share| share.starts_with("gf16:")));
+            assert_eq!(decode_point(shares[999].clone())?.1, 1000);
+            assert_eq!(unlock(&shares[997..])?, secret);
+
+            shares[998] = canonicalize(&shares[998])?;
+            shares[999] = reencode(&shares[999], &Bip39)?;
+            assert!(shares[999].starts_with("gf16:"));
+            assert_eq!(unlock(&shares[997..])?, secret);
+            assert_ne!(unlock(&shares[998..])?, secret);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn gf65536_with_everything() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        let config = SsssConfig::builder()
+            .num_shares(4)
+            .threshold(2)
+            .field(FieldKind::Gf65536)
+            .encoding(Encoding::Base32)
+            .parity(8)
+            .padding(Padding::PowerOfTwo)
+            .indices(Indices::Random)
+            .verify(true)
+            .build();
+        let shares = gen_shares(&config, secret)?;
+        assert!(shares[0].starts_with("gf16:rs8:b32:"));
+        let damaged = format!("{}??{}", &shares[0][..30], &shares[0][32..]);
+        assert_eq!(repair(&damaged)?, shares[0]);
+        assert_eq!(unlock(&[damaged, shares[3].clone()])?, secret);
+        Ok(())
+    }
+
+    #[test]
+    fn too_many_shares_for_the_field() -> Result<()> {
+        let config = SsssConfig::builder().num_shares(256).build();
+        check_err_result(
+            gen_shares(&config, b"correct horse battery staple"),
+            "The field supports at most 255 shares, but 256 were requested",
+        )
+    }
+
+    #[test]
+    fn mixed_fields_error() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        let mut shares = gen_shares(&SsssConfig::default(), secret)?;
+        let config = SsssConfig::builder().field(FieldKind::Gf65536).build();
+        shares.extend(gen_shares(&config, secret)?);
+        check_err_result(
+            unlock(&shares),
+            "The shares were not all split over the same field",
+        )
+    }
+
+    fn corrupt(share: &str) -> Result<String> {
+        let (field, share) = FieldKind::split(share);
+        let (index, mut bytes) = decode_share(share.to_string())?;
+        for byte in bytes.iter_mut().rev().take(4) {
+            *byte ^= 0x5a;
+        }
+        Ok(format!("{}{}", field.prefix(), Hex.encode(index, &bytes)))
+    }
+
+    #[test]
+    fn unlock_robust_corrects_bad_shares() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        for field in [FieldKind::Gf256, FieldKind::Gf65536] {
+            let config = SsssConfig::builder()
+                .num_shares(7)
+                .threshold(3)
+                .field(field)
+                .indices(Indices::Random)
+                .build();
+            let mut shares = gen_shares(&config, secret)?;
+            let bad: Vec<u16> = [1, 4]
+                .into_iter()
+                .map(|i| Ok(decode_point(shares[i].clone())?.1))
+                .collect::<Result<_>>()?;
+            shares[1] = corrupt(&shares[1])?;
+            shares[4] = corrupt(&shares[4])?;
+            shares.push(shares[0].clone());
+            assert_ne!(unlock(&shares)?, secret);
+            assert_eq!(unlock_robust(3, &shares)?, (secret.to_vec(), bad));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn shares_longer_than_the_checksum_error() -> Result<()> {
+        let secret = vec![0x5a; 40_000];
+        let config = SsssConfig::builder().encoding(Encoding::Hex).build();
+        check_err_result(
+            gen_shares(&config, &secret),
+            "A share with 80003 checksummed characters is too long for its checksum, which covers at most 65530",
+        )?;
+        let config = SsssConfig::builder().encoding(Encoding::Base62).build();
+        assert_eq!(unlock(&gen_shares(&config, &secret)?)?, secret);
+        Ok(())
+    }
+
+    #[test]
+    fn unlock_reports_a_mistyped_share() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        let config = SsssConfig::builder().encoding(Encoding::Hex).build();
+        let mut shares = gen_shares(&config, secret)?;
+        shares.truncate(3);
+        let mut typo: Vec<char> = shares[1].chars().collect();
+        typo[10] = if typo[10] == '0' { '1' } else { '0' };
+        shares[1] = typo.into_iter().collect();
+        check_err_result(
+            unlock(&shares),
+            "Invalid share checksum, check the share near character 11",
+        )
+    }
+
+    #[test]
+    fn unlock_robust_errors() -> Result<()> {
+        let config = SsssConfig::default();
+        let secret = "abc".as_bytes();
+        let mut shares = gen_shares(&config, secret)?;
+        assert_eq!(unlock_robust(3, &shares)?, (secret.to_vec(), vec![]));
+        shares.push(Base62::default().encode(6, "abc".as_bytes()));
+        shares.push(Base62::default().encode(7, "def".as_bytes()));
+        shares.push(Base62::default().encode(8, "ghi".as_bytes()));
+        check_err_result(
+            unlock_robust(3, &shares),
+            "Too many shares are wrong to recover the secret, at most 2 can be corrected",
+        )?;
+        check_err_result(
+            unlock_robust(6, &shares[..5]),
+            "6 shares are required, but 5 were given",
+        )
+    }
+
+    #[test]
+    fn unlock_robust_leaves_out_odd_shares() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        let config = SsssConfig::builder().num_shares(7).threshold(3).build();
+        let mut shares = gen_shares(&config, secret)?;
+        let config = SsssConfig::builder()
+            .num_shares(9)
+            .threshold(3)
+            .field(FieldKind::Gf65536)
+            .build();
+        let other_field = gen_shares(&config, secret)?;
+
+        // A different share at an index already given, a share of another
+        // length and a share split over another field
+        shares.push(Hex.encode(1, &[0; 28]));
+        shares.push(Hex.encode(8, b"correct horse"));
+        shares.push(other_field[8].clone());
+        let (unlocked, bad) = unlock_robust(3, &shares)?;
+        assert_eq!(unlocked, secret);
+        assert_eq!(bad, [1, 8, 9]);
+        Ok(())
+    }
+
     #[test]
     fn split_and_join() -> Result<()> {
         let secret = "correct horse battery staple".as_bytes();
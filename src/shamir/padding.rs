@@ -0,0 +1,212 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` secret padding
+//!
+//! A padded secret is laid out as
+//!
+//! | Bytes             | Field                                                       |
+//! |-------------------|-------------------------------------------------------------|
+//! | `len`             | the secret                                                  |
+//! | `size - len - 10` | zero bytes                                                  |
+//! | 8                 | the first 8 bytes of a SHA-256 of the length and the secret |
+//! | 2                 | the secret length, big-endian                               |
+//!
+//! The tag authenticates the length, so that padding is only removed from a
+//! secret that was padded, and only when enough shares were combined to
+//! reconstruct it.
+
+use crate::error::SsssError::{PaddingTooLarge, PaddingTooSmall, SecretLength};
+use anyhow::Result;
+#[cfg(feature = "fuzz")]
+use arbitrary::Arbitrary;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// The domain separation prefix of the tag
+const DOMAIN: &[u8] = b"ssss padding v1\0";
+/// The number of tag bytes
+const TAG_BYTES: usize = 8;
+/// The number of bytes added after the padding, the tag and the length
+const TRAILER_BYTES: usize = TAG_BYTES + 2;
+/// The largest padded size, enough for a power-of-two bucket of the longest
+/// secret whose length fits the length field
+const MAX_PADDED_SIZE: usize = 2 << 16;
+
+/// How a secret is padded before it is split, so that its shares do not give
+/// away its length.  See [`SsssConfig`](crate::SsssConfig).
+///
+/// Every share is as long as the secret it was split from.  Padding the secret
+/// to a fixed size, or to one of a few sizes, means a share only tells how
+/// long the padded secret is.  [`unlock`](crate::unlock) removes the padding
+/// again.  A padded secret carries 10 extra bytes: its length and a tag that
+/// authenticates it.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(feature = "fuzz", derive(Arbitrary))]
+pub enum Padding {
+    /// The secret is not padded
+    #[default]
+    None,
+    /// Every secret is padded to the same number of bytes, which must be at
+    /// least 10 more than the length of the secret
+    Fixed(usize),
+    /// The secret is padded to the next power of two bytes, so that its
+    /// length is only known to within a factor of two
+    PowerOfTwo,
+}
+
+impl Padding {
+    /// The size of the padded secret
+    fn size(self, length: usize) -> Result<Option<usize>> {
+        let size = match self {
+            Padding::None => return Ok(None),
+            Padding::Fixed(size) => size,
+            Padding::PowerOfTwo => (length + TRAILER_BYTES).next_power_of_two(),
+        };
+        if size > MAX_PADDED_SIZE {
+            Err(PaddingTooLarge {
+                size,
+                max: MAX_PADDED_SIZE,
+            }
+            .into())
+        } else if length + TRAILER_BYTES > size {
+            Err(PaddingTooSmall { length, size }.into())
+        } else {
+            Ok(Some(size))
+        }
+    }
+
+    /// Check that a secret of the given length can be padded
+    pub(crate) fn validate(self, length: usize) -> Result<()> {
+        if self != Padding::None && length > usize::from(u16::MAX) {
+            return Err(SecretLength {
+                length,
+                max: usize::from(u16::MAX),
+            }
+            .into());
+        }
+        self.size(length).map(|_| ())
+    }
+
+    /// The padding of a secret whose shares are verified, which must carry
+    /// the tag.  A secret that would not be padded gets the least padding,
+    /// just the tag and the length.
+    pub(crate) fn tagged(self, length: usize) -> Self {
+        match self {
+            Padding::None => Padding::Fixed(length + TRAILER_BYTES),
+            padding => padding,
+        }
+    }
+
+    /// Pad the secret
+    pub(crate) fn pad(self, secret: &[u8]) -> Result<Vec<u8>> {
+        self.validate(secret.len())?;
+        let Some(size) = self.size(secret.len())? else {
+            return Ok(secret.to_vec());
+        };
+        let length = u16::try_from(secret.len())?.to_be_bytes();
+        let mut padded = secret.to_vec();
+        padded.resize(size - TRAILER_BYTES, 0);
+        padded.extend(&tag(length, secret));
+        padded.extend(&length);
+        Ok(padded)
+    }
+}
+
+/// Remove the padding from a reconstructed secret.
+///
+/// A secret without a valid length and tag is returned unchanged, whether it
+/// was never padded or was reconstructed from too few shares.
+pub(crate) fn unpad(mut secret: Vec<u8>) -> Vec<u8> {
+    if let Some(len) = padded_len(&secret) {
+        secret.truncate(len);
+    }
+    secret
+}
+
+/// Check whether a reconstructed secret carries a valid length and tag
+pub(crate) fn is_padded(secret: &[u8]) -> bool {
+    padded_len(secret).is_some()
+}
+
+/// The length of the secret inside a padded secret, if its length and tag
+/// are valid
+fn padded_len(secret: &[u8]) -> Option<usize> {
+    let body = secret.len().checked_sub(TRAILER_BYTES)?;
+    let length = [secret[secret.len() - 2], secret[secret.len() - 1]];
+    let len = usize::from(u16::from_be_bytes(length));
+    (len <= body
+        && secret[len..body].iter().all(|byte| *byte == 0)
+        && tag(length, &secret[..len]) == secret[body..body + TAG_BYTES])
+        .then_some(len)
+}
+
+fn tag(length: [u8; 2], secret: &[u8]) -> [u8; TAG_BYTES] {
+    let mut hasher = Sha256::new();
+    hasher.update(DOMAIN);
+    hasher.update(length);
+    hasher.update(secret);
+    let mut tag = [0; TAG_BYTES];
+    tag.copy_from_slice(&hasher.finalize()[..TAG_BYTES]);
+    tag
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Padding, is_padded, unpad};
+    use crate::utils::check_err_result;
+    use anyhow::Result;
+
+    #[test]
+    fn padding_roundtrip() -> Result<()> {
+        let secret = b"correct horse battery staple";
+        for (padding, size) in [
+            (Padding::None, 28),
+            (Padding::Fixed(38), 38),
+            (Padding::Fixed(64), 64),
+            (Padding::PowerOfTwo, 64),
+        ] {
+            let padded = padding.pad(secret)?;
+            assert_eq!(padded.len(), size);
+            assert_eq!(unpad(padded), secret);
+        }
+        assert_eq!(Padding::PowerOfTwo.pad(b"hunter2")?.len(), 32);
+        assert_eq!(Padding::PowerOfTwo.pad(b"correct")?.len(), 32);
+        Ok(())
+    }
+
+    #[test]
+    fn bad_padding_is_kept() -> Result<()> {
+        let mut padded = Padding::Fixed(48).pad(b"correct horse battery staple")?;
+        padded[10] ^= 1;
+        assert_eq!(unpad(padded.clone()).len(), 48);
+        padded[10] ^= 1;
+        padded[30] = 1;
+        assert_eq!(unpad(padded).len(), 48);
+        assert_eq!(unpad(b"short".to_vec()), b"short");
+        assert!(!is_padded(b"short"));
+        assert!(is_padded(&Padding::Fixed(16).pad(b"a")?));
+        Ok(())
+    }
+
+    #[test]
+    fn padding_too_small() -> Result<()> {
+        check_err_result(
+            Padding::Fixed(37).pad(b"correct horse battery staple"),
+            "The secret length '28' and its padding do not fit the padded size '37'",
+        )?;
+        check_err_result(
+            Padding::Fixed(1 << 20).pad(b"correct horse battery staple"),
+            "The padded size '1048576' is larger than the maximum allowed '131072'",
+        )?;
+        check_err_result(
+            Padding::PowerOfTwo.validate(1 << 16),
+            "The secret length '65536' is longer than the maximum allowed '65535'",
+        )
+    }
+}
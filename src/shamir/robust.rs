@@ -0,0 +1,389 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` robust unlocking
+//!
+//! With `m` shares of a secret split with threshold `t`, each element of the
+//! shares is a word of a Reed–Solomon code of length `m` and dimension `t`,
+//! so Berlekamp–Welch decoding finds the secret polynomial despite up to
+//! `⌊(m - t) / 2⌋` wrong shares, and with it the shares that are wrong.
+
+use crate::{
+    error::SsssError::{
+        EmptyShare, EmptySharesMap, NotEnoughShares, ThresholdZero, TooManyBadShares,
+    },
+    field::{Field, Polynomial},
+};
+use anyhow::Result;
+
+/// Combine `(index, share)` pairs made by
+/// [`gen_shares_over`](crate::gen_shares_over) with the given threshold into
+/// the secret, like [`unlock_over`](crate::unlock_over), correcting up to
+/// `⌊(m - threshold) / 2⌋` of the `m` shares that are wrong.
+///
+/// Returns the secret, and the indices of the shares that disagree with it,
+/// in the order they were given.  Each element of the secret is corrected on
+/// its own, so more shares than that may be reported as long as no element
+/// has too many wrong shares.
+///
+/// A wrong share may not even look like the others, so rather than failing,
+/// the shares that are not the most common length, those at index 0, and
+/// those at an index also given with a different share are left out, and
+/// their indices are reported as wrong too.  A share given twice is used once.
+///
+/// # Errors
+/// * This function will generate an error if the threshold is 0, or there
+///   are fewer shares left than the threshold.
+/// * This function will generate an error if there are no shares, or the most
+///   common share length is 0.
+/// * This function will generate an error if too many shares are wrong to
+///   recover the secret.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{gen_shares_over, unlock_robust_over, Gf256};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = [Gf256(0x2a), Gf256(0x17)];
+/// let indices: Vec<Gf256> = (1..=7).map(Gf256).collect();
+/// let shares = gen_shares_over(3, &secret, &indices)?;
+/// let mut points: Vec<(Gf256, Vec<Gf256>)> = indices.into_iter().zip(shares).collect();
+///
+/// // Two of the seven shares are wrong
+/// points[1].1[0] += Gf256(1);
+/// points[5].1 = vec![Gf256(0), Gf256(0)];
+/// let (unlocked, bad) = unlock_robust_over(3, &points)?;
+/// assert_eq!(unlocked, secret);
+/// assert_eq!(bad, [Gf256(2), Gf256(6)]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn unlock_robust_over<F: Field>(
+    threshold: u16,
+    shares: &[(F, Vec<F>)],
+) -> Result<(Vec<F>, Vec<F>)> {
+    let threshold = usize::from(threshold);
+    if threshold == 0 {
+        return Err(ThresholdZero.into());
+    }
+    let len = shares
+        .iter()
+        .map(|(_, share)| share.len())
+        .max_by_key(|len| {
+            shares
+                .iter()
+                .filter(|(_, share)| share.len() == *len)
+                .count()
+        })
+        .ok_or(EmptySharesMap)?;
+    if len == 0 {
+        return Err(EmptyShare.into());
+    }
+
+    let mut left_out: Vec<&F> = Vec::new();
+    let mut used: Vec<&(F, Vec<F>)> = Vec::with_capacity(shares.len());
+    for point @ (x, share) in shares {
+        let conflicts = || {
+            shares
+                .iter()
+                .any(|(other, ys)| other == x && ys.len() == len && ys != share)
+        };
+        if share.len() != len || x.is_zero() || conflicts() {
+            left_out.push(x);
+        } else if !used.iter().any(|(other, _)| other == x) {
+            used.push(point);
+        }
+    }
+    if used.len() < threshold {
+        return Err(NotEnoughShares {
+            threshold: u16::try_from(threshold).unwrap_or(u16::MAX),
+            found: used.len(),
+        }
+        .into());
+    }
+    let max_errors = (used.len() - threshold) / 2;
+
+    let xs: Vec<F> = used.iter().map(|(x, _)| x.clone()).collect();
+    let mut wrong = vec![false; used.len()];
+    let mut secret = Vec::with_capacity(len);
+    for i in 0..len {
+        let ys: Vec<F> = used.iter().map(|(_, share)| share[i].clone()).collect();
+        let polynomial =
+            decode(&xs, &ys, threshold, max_errors).ok_or(TooManyBadShares { max: max_errors })?;
+        for ((x, y), wrong) in xs.iter().zip(&ys).zip(&mut wrong) {
+            *wrong |= polynomial.eval(x) != *y;
+        }
+        secret.push(polynomial.eval(&F::zero()));
+    }
+
+    let mut bad: Vec<F> = Vec::new();
+    for (x, _) in shares {
+        let wrong = left_out.contains(&x) || xs.iter().zip(&wrong).any(|(y, w)| *w && y == x);
+        if wrong && !bad.contains(x) {
+            bad.push(x.clone());
+        }
+    }
+    Ok((secret, bad))
+}
+
+/// The polynomial of degree less than `threshold` through all but at most
+/// `max_errors` of the points, by Berlekamp–Welch
+fn decode<F: Field>(
+    xs: &[F],
+    ys: &[F],
+    threshold: usize,
+    max_errors: usize,
+) -> Option<Polynomial<F>> {
+    // Most of the time no share is wrong, and the polynomial through the
+    // first `threshold` points goes through all of them
+    let (first, rest) = (
+        xs.iter().cloned().zip(ys.iter().cloned()).take(threshold),
+        xs.iter().zip(ys).skip(threshold),
+    );
+    let first: Vec<(F, F)> = first.collect();
+    if rest
+        .clone()
+        .all(|(x, y)| Polynomial::interpolate(&first, x).as_ref() == Some(y))
+    {
+        let coeffs = solve(
+            first
+                .iter()
+                .map(|(x, y)| powers(x, threshold).chain([y.clone()]).collect())
+                .collect(),
+            threshold,
+        )?;
+        return Some(Polynomial::new(coeffs));
+    }
+
+    // Find `Q` of degree less than `threshold + e` and the error locator `E`,
+    // monic of degree `e`, with `Q(x) = y E(x)` at every point.  Then
+    // `Q = P E` for the secret polynomial `P`.
+    let e = max_errors;
+    let rows = xs
+        .iter()
+        .zip(ys)
+        .map(|(x, y)| {
+            powers(x, threshold + e)
+                .chain(powers(x, e).map(|power| F::zero().sub(&y.mul(&power))))
+                .chain([y.mul(&powers(x, e + 1).last().unwrap_or_else(F::one))])
+                .collect()
+        })
+        .collect();
+    let solution = solve(rows, threshold + 2 * e)?;
+    let (q, locator) = solution.split_at(threshold + e);
+    let mut locator = locator.to_vec();
+    locator.push(F::one());
+    let (quotient, remainder) = divide(q, &locator);
+    if remainder.iter().any(|coeff| !coeff.is_zero()) {
+        return None;
+    }
+    let polynomial = Polynomial::new(quotient);
+    let errors = xs
+        .iter()
+        .zip(ys)
+        .filter(|(x, y)| polynomial.eval(x) != **y)
+        .count();
+    (errors <= max_errors).then_some(polynomial)
+}
+
+/// `1, x, x^2, .., x^(count - 1)`
+fn powers<F: Field>(x: &F, count: usize) -> impl Iterator<Item = F> + '_ {
+    std::iter::successors(Some(F::one()), move |power| Some(power.mul(x))).take(count)
+}
+
+/// Any one solution of the linear system whose rows are the coefficients of
+/// the unknowns followed by the constant, or `None` if it has none
+fn solve<F: Field>(mut rows: Vec<Vec<F>>, unknowns: usize) -> Option<Vec<F>> {
+    let mut pivots = Vec::with_capacity(unknowns);
+    for col in 0..unknowns {
+        let row = pivots.len();
+        let Some(found) = (row..rows.len()).find(|r| !rows[*r][col].is_zero()) else {
+            continue;
+        };
+        rows.swap(row, found);
+        let inverse = rows[row][col].inv()?;
+        let pivot: Vec<F> = rows[row].iter().map(|coeff| coeff.mul(&inverse)).collect();
+        for other in &mut rows {
+            let factor = other[col].clone();
+            if !factor.is_zero() {
+                for (coeff, pivot) in other.iter_mut().zip(&pivot).skip(col) {
+                    *coeff = coeff.sub(&factor.mul(pivot));
+                }
+            }
+        }
+        rows[row] = pivot;
+        pivots.push(col);
+    }
+    if rows[pivots.len()..]
+        .iter()
+        .any(|row| !row[unknowns].is_zero())
+    {
+        return None;
+    }
+    let mut solution = vec![F::zero(); unknowns];
+    for (row, col) in pivots.into_iter().enumerate() {
+        solution[col] = rows[row][unknowns].clone();
+    }
+    Some(solution)
+}
+
+/// Divide by a monic polynomial, both constant term first, into the quotient
+/// and the remainder
+fn divide<F: Field>(dividend: &[F], divisor: &[F]) -> (Vec<F>, Vec<F>) {
+    let degree = divisor.len() - 1;
+    let mut remainder = dividend.to_vec();
+    if remainder.len() <= degree {
+        return (Vec::new(), remainder);
+    }
+    let mut quotient = vec![F::zero(); remainder.len() - degree];
+    for i in (0..quotient.len()).rev() {
+        let coeff = remainder[i + degree].clone();
+        for (j, term) in divisor.iter().enumerate() {
+            remainder[i + j] = remainder[i + j].sub(&coeff.mul(term));
+        }
+        quotient[i] = coeff;
+    }
+    remainder.truncate(degree);
+    (quotient, remainder)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{divide, unlock_robust_over};
+    use crate::{
+        field::{Field, Fp, Gf256, Gf65536, Mersenne127},
+        shamir::gen_shares_over,
+        utils::check_err_result,
+    };
+    use anyhow::Result;
+    use rand::{rng, seq::index::sample};
+
+    fn gf256(coeffs: &[u8]) -> Vec<Gf256> {
+        coeffs.iter().copied().map(Gf256).collect()
+    }
+
+    #[test]
+    fn divide_works() {
+        // (x^2 + 3x + 2) / (x + 1) = x + 2 over GF(2^8)
+        assert_eq!(
+            divide(&gf256(&[2, 3, 1]), &gf256(&[1, 1])),
+            (gf256(&[2, 1]), gf256(&[0]))
+        );
+        assert_eq!(
+            divide(&gf256(&[3, 3, 1]), &gf256(&[1, 1])),
+            (gf256(&[2, 1]), gf256(&[1]))
+        );
+        assert_eq!(divide(&gf256(&[5]), &gf256(&[1, 1])), (vec![], gf256(&[5])));
+    }
+
+    fn corrects<F: Field>(secret: &[F], indices: &[F], threshold: u16) -> Result<()> {
+        let mut rng = rng();
+        let shares = gen_shares_over(threshold, secret, indices)?;
+        let points: Vec<(F, Vec<F>)> = indices.iter().cloned().zip(shares).collect();
+        let max_errors = (points.len() - usize::from(threshold)) / 2;
+        for errors in 0..=max_errors {
+            let mut damaged = points.clone();
+            let mut wrong = sample(&mut rng, points.len(), errors).into_vec();
+            wrong.sort_unstable();
+            for i in &wrong {
+                let element = &mut damaged[*i].1[*i % secret.len()];
+                *element = element.add(&F::one());
+            }
+            let (unlocked, bad) = unlock_robust_over(threshold, &damaged)?;
+            assert_eq!(unlocked, secret);
+            let expected: Vec<F> = wrong.iter().map(|i| indices[*i].clone()).collect();
+            assert_eq!(bad, expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn bad_shares_are_corrected() -> Result<()> {
+        let indices: Vec<Gf256> = (1..=12).map(Gf256).collect();
+        for threshold in [1, 2, 3, 5, 12] {
+            corrects(&gf256(b"hunter2"), &indices, threshold)?;
+        }
+        let indices: Vec<Gf65536> = [1, 300, 4000, 50000, 65535].map(Gf65536).to_vec();
+        corrects(&[Gf65536(0xbeef), Gf65536(0xcafe)], &indices, 2)?;
+        let indices: Vec<Fp<Mersenne127>> = (1..=6_u32).map(|i| Fp::new(i.into())).collect();
+        corrects(&[Fp::new(42_u32.into())], &indices, 3)
+    }
+
+    #[test]
+    fn bad_shares_in_different_elements_are_corrected() -> Result<()> {
+        // Each element has one wrong share, but three shares are wrong
+        let indices: Vec<Gf256> = (1..=5).map(Gf256).collect();
+        let shares = gen_shares_over(3, &gf256(b"abc"), &indices)?;
+        let mut points: Vec<(Gf256, Vec<Gf256>)> = indices.into_iter().zip(shares).collect();
+        for (i, point) in points.iter_mut().take(3).enumerate() {
+            point.1[i] += Gf256(0x80);
+        }
+        let (unlocked, bad) = unlock_robust_over(3, &points)?;
+        assert_eq!(unlocked, gf256(b"abc"));
+        assert_eq!(bad, gf256(&[1, 2, 3]));
+        Ok(())
+    }
+
+    #[test]
+    fn odd_shares_are_left_out() -> Result<()> {
+        let indices: Vec<Gf256> = (1..=7).map(Gf256).collect();
+        let shares = gen_shares_over(3, &gf256(b"abc"), &indices)?;
+        let mut points: Vec<(Gf256, Vec<Gf256>)> = indices.into_iter().zip(shares).collect();
+        // A share of another length, a different share at an index already
+        // given, a share at index 0 and a share given twice
+        points[3].1.push(Gf256(0));
+        points.push((Gf256(2), gf256(b"abc")));
+        points.push((Gf256(0), gf256(b"abc")));
+        points.push(points[5].clone());
+        let (unlocked, bad) = unlock_robust_over(3, &points)?;
+        assert_eq!(unlocked, gf256(b"abc"));
+        assert_eq!(bad, gf256(&[2, 4, 0]));
+
+        points[0].1[0] += Gf256(1);
+        points[6].1[0] += Gf256(2);
+        check_err_result(
+            unlock_robust_over(3, &points),
+            "Too many shares are wrong to recover the secret, at most 1 can be corrected",
+        )?;
+        check_err_result(
+            unlock_robust_over(4, &points[..4]),
+            "4 shares are required, but 3 were given",
+        )
+    }
+
+    #[test]
+    fn too_many_bad_shares_error() -> Result<()> {
+        let indices: Vec<Gf256> = (1..=6).map(Gf256).collect();
+        let shares = gen_shares_over(2, &gf256(b"secret"), &indices)?;
+        let mut points: Vec<(Gf256, Vec<Gf256>)> = indices.into_iter().zip(shares).collect();
+        for point in points.iter_mut().take(3) {
+            point.1[0] += Gf256(1);
+        }
+        check_err_result(
+            unlock_robust_over(2, &points),
+            "Too many shares are wrong to recover the secret, at most 2 can be corrected",
+        )?;
+        check_err_result(
+            unlock_robust_over(0, &points),
+            "The threshold must be greater than 0",
+        )?;
+        check_err_result(
+            unlock_robust_over(7, &points),
+            "7 shares are required, but 6 were given",
+        )?;
+        check_err_result(
+            unlock_robust_over::<Gf256>(2, &[]),
+            "The shares map cannot be empty",
+        )?;
+        check_err_result(
+            unlock_robust_over(1, &[(Gf256(1), vec![])]),
+            "A share cannot be empty",
+        )
+    }
+}
@@ -10,52 +10,27 @@
 
 use anyhow::Result;
 
-use crate::{
-    base62::{decode, encode},
-    error::SsssError::InvalidShareFormat,
-};
-
-#[allow(clippy::needless_pass_by_value)]
-pub(crate) fn encode_share(tuple: (usize, Vec<u8>)) -> Result<String> {
-    let idx = u8::try_from(tuple.0)? + 1;
-    let idx_enc = encode(&idx.to_be_bytes());
-    let share_enc = encode(&tuple.1);
-    Ok(format!("{idx_enc}:{share_enc}"))
-}
+use super::FieldKind;
+use crate::{binary, encoding::detect, paper, uri};
 
 #[allow(clippy::needless_pass_by_value)]
 pub(crate) fn decode_share(share: String) -> Result<(u8, Vec<u8>)> {
-    let split_str = share.split(':').collect::<Vec<&str>>();
-    if split_str.len() == 2 {
-        let idx_bytes = decode(split_str[0])?;
-        let idx = u8::from_be_bytes((&idx_bytes[..]).try_into()?);
-        let share = decode(split_str[1])?;
-        Ok((idx, share))
-    } else {
-        Err(InvalidShareFormat.into())
-    }
+    let share = unwrap_share(share)?;
+    detect(&share)?.decode(&share)
 }
 
-pub(crate) fn transpose<T>(v: &[Vec<T>]) -> Vec<Vec<T>>
-where
-    T: Clone,
-{
-    if let Some(first) = v.first() {
-        (0..first.len())
-            .map(|i| v.iter().map(|inner| inner[i].clone()).collect::<Vec<T>>())
-            .collect()
-    } else {
-        vec![]
-    }
+/// Decode a share split over any [field](FieldKind) into the field, the share
+/// index and the share
+pub(crate) fn decode_point(share: String) -> Result<(FieldKind, u16, Vec<u8>)> {
+    let share = unwrap_share(share)?;
+    let (field, share) = FieldKind::split(&share);
+    let (idx, bytes) = detect(share)?.decode(share)?;
+    let (idx, bytes) = field.decode(idx, bytes)?;
+    Ok((field, idx, bytes))
 }
 
-#[cfg(test)]
-mod test {
-    use super::transpose;
-
-    #[test]
-    fn transpose_empty_works() {
-        let empty_vec: Vec<Vec<u8>> = vec![];
-        assert!(transpose(&empty_vec).is_empty());
-    }
+/// Unwrap the share from a share URI, a paper backup or an armored binary
+/// share
+pub(crate) fn unwrap_share(share: String) -> Result<String> {
+    binary::strip(paper::strip(uri::strip(share)?)?)
 }
@@ -0,0 +1,97 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The SLIP-0039 passphrase encryption
+//!
+//! A four round Feistel network with PBKDF2-HMAC-SHA256 as the round function.
+
+use pbkdf2::pbkdf2_hmac;
+use sha2::Sha256;
+
+/// The total number of PBKDF2 iterations at iteration exponent 0
+const BASE_ITERATION_COUNT: u32 = 10_000;
+/// The number of Feistel rounds
+const ROUND_COUNT: u8 = 4;
+/// The salt prefix used by shares without the extendable flag
+const SALT_PREFIX: &[u8] = b"shamir";
+
+/// The parameters shared by encryption and decryption
+pub(crate) struct Cipher<'a> {
+    pub(crate) passphrase: &'a [u8],
+    pub(crate) iteration_exponent: u8,
+    pub(crate) identifier: u16,
+    pub(crate) extendable: bool,
+}
+
+impl Cipher<'_> {
+    /// Encrypt the master secret
+    pub(crate) fn encrypt(&self, master_secret: &[u8]) -> Vec<u8> {
+        self.feistel(master_secret, 0..ROUND_COUNT)
+    }
+
+    /// Decrypt the encrypted master secret
+    pub(crate) fn decrypt(&self, encrypted: &[u8]) -> Vec<u8> {
+        self.feistel(encrypted, (0..ROUND_COUNT).rev())
+    }
+
+    fn feistel(&self, input: &[u8], rounds: impl Iterator<Item = u8>) -> Vec<u8> {
+        let (left, right) = input.split_at(input.len() / 2);
+        let (mut left, mut right) = (left.to_vec(), right.to_vec());
+        let salt = self.salt();
+        for round in rounds {
+            let f = self.round(round, &salt, &right);
+            let next: Vec<u8> = left.iter().zip(f).map(|(l, f)| l ^ f).collect();
+            left = right;
+            right = next;
+        }
+        right.extend(left);
+        right
+    }
+
+    fn round(&self, round: u8, salt: &[u8], right: &[u8]) -> Vec<u8> {
+        let mut password = vec![round];
+        password.extend_from_slice(self.passphrase);
+        let mut salt = salt.to_vec();
+        salt.extend_from_slice(right);
+        let iterations = (BASE_ITERATION_COUNT << self.iteration_exponent) / u32::from(ROUND_COUNT);
+        let mut output = vec![0; right.len()];
+        pbkdf2_hmac::<Sha256>(&password, &salt, iterations, &mut output);
+        output
+    }
+
+    fn salt(&self) -> Vec<u8> {
+        if self.extendable {
+            vec![]
+        } else {
+            let mut salt = SALT_PREFIX.to_vec();
+            salt.extend(self.identifier.to_be_bytes());
+            salt
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Cipher;
+
+    #[test]
+    fn decrypt_is_inverse_of_encrypt() {
+        let secret: Vec<u8> = (0..16).collect();
+        for extendable in [false, true] {
+            let cipher = Cipher {
+                passphrase: b"TREZOR",
+                iteration_exponent: 0,
+                identifier: 7945,
+                extendable,
+            };
+            let encrypted = cipher.encrypt(&secret);
+            assert_ne!(encrypted, secret);
+            assert_eq!(cipher.decrypt(&encrypted), secret);
+        }
+    }
+}
@@ -0,0 +1,1008 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! [SLIP-0039](https://github.com/satoshilabs/slips/blob/master/slip-0039.md)
+//! compatible share generation and recovery.
+//!
+//! SLIP-0039 shares a master secret in two levels: the secret is split into
+//! groups, and each group secret is split again among the group members.
+//! Recovery needs `group_threshold` groups, each with its own member threshold
+//! of shares.  The master secret is encrypted with a passphrase before it is
+//! split, and every share is written as a mnemonic of words from the SLIP-0039
+//! word list, protected by an RS1024 checksum.
+//!
+//! The mnemonics produced here can be recovered by hardware wallets that
+//! implement SLIP-0039, and the other way around.
+//!
+//! # Example
+//! ```
+//! # use anyhow::Result;
+//! # use ssss::slip39::{Config, Group, combine, split};
+//! #
+//! # pub fn main() -> Result<()> {
+//! let master_secret = b"ABCDEFGHIJKLMNOP";
+//!
+//! // Two of the three groups are required, a 1-of-1, a 2-of-3 and a 3-of-5
+//! let config = Config::builder()
+//!     .group_threshold(2)
+//!     .groups(vec![
+//!         Group::builder().threshold(1).count(1).build(),
+//!         Group::builder().threshold(2).count(3).build(),
+//!         Group::default(),
+//!     ])
+//!     .iteration_exponent(0)
+//!     .build();
+//! let groups = split(&config, master_secret, "TREZOR")?;
+//!
+//! let mnemonics = vec![
+//!     groups[0][0].clone(),
+//!     groups[1][2].clone(),
+//!     groups[1][0].clone(),
+//! ];
+//! assert_eq!(combine(&mnemonics, "TREZOR")?, master_secret);
+//! # Ok(())
+//! # }
+//! ```
+
+mod cipher;
+mod rs1024;
+mod words;
+
+use self::cipher::Cipher;
+use crate::{
+    encoding::mnemonic::lookup,
+    error::SsssError::{
+        EmptySharesMap, InvalidChecksum, ShareLengthMismatch, SharesZero, Slip39Digest,
+        Slip39DuplicateIndex, Slip39GroupCount, Slip39IterationExponent, Slip39MemberCount,
+        Slip39MemberThresholdOne, Slip39Mismatch, Slip39MnemonicLength, Slip39Padding,
+        Slip39Passphrase, Slip39SecretLength, Slip39TooManyShares, ThresholdToLow, ThresholdZero,
+    },
+    gf256,
+};
+use anyhow::Result;
+use bon::Builder;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::collections::BTreeMap;
+
+/// The number of bits carried by each word
+const RADIX_BITS: usize = 10;
+/// The number of words holding the identifier, extendable flag and iteration exponent
+const ID_EXP_WORDS: usize = 2;
+/// The number of words holding the identifier, group and member parameters
+const METADATA_WORDS: usize = ID_EXP_WORDS + 2 + rs1024::CHECKSUM_WORDS;
+/// The minimum length of the master secret in bytes
+const MIN_SECRET_BYTES: usize = 16;
+/// The minimum number of words in a mnemonic
+const MIN_MNEMONIC_WORDS: usize = METADATA_WORDS + (MIN_SECRET_BYTES * 8).div_ceil(RADIX_BITS);
+/// The maximum number of groups, and of members in a group
+const MAX_SHARE_COUNT: usize = 16;
+/// The maximum iteration exponent
+const MAX_ITERATION_EXPONENT: u8 = 15;
+/// The length of the digest of the shared secret
+const DIGEST_BYTES: usize = 4;
+/// The x-coordinate of the digest share
+const DIGEST_INDEX: u8 = 254;
+/// The x-coordinate of the shared secret
+const SECRET_INDEX: u8 = 255;
+
+/// Configuration used to drive the [`split`] function.
+///
+/// # Notes
+/// The default configuration will specify a single group of 5 shares with a
+/// threshold of 3, an iteration exponent of 1 and the extendable flag set.
+#[derive(Builder, Clone, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// The number of groups required to recover the master secret.
+    /// This must be less than or equal to the number of groups
+    #[builder(default = 1)]
+    group_threshold: u8,
+    /// The groups to generate, at most 16
+    #[builder(default = vec![Group::default()])]
+    groups: Vec<Group>,
+    /// The passphrase is stretched with `10000 * 2^iteration_exponent`
+    /// iterations of PBKDF2.  This must be less than 16
+    #[builder(default = 1)]
+    iteration_exponent: u8,
+    /// Generate shares that can be extended with more groups or members later.
+    /// Without the flag, the share identifier is part of the encryption salt.
+    #[builder(default = true)]
+    extendable: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::builder().build()
+    }
+}
+
+/// A group of shares, of which `threshold` are required to recover the group
+/// secret.
+///
+/// # Notes
+/// The default group will specify 5 shares with a threshold of 3.
+#[derive(Builder, Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Group {
+    /// The number of member shares required to recover the group secret
+    #[builder(default = 3)]
+    threshold: u8,
+    /// The number of member shares to generate, at most 16
+    #[builder(default = 5)]
+    count: u8,
+}
+
+impl Default for Group {
+    fn default() -> Self {
+        Group::builder().build()
+    }
+}
+
+impl Config {
+    fn validate(&self, master_secret: &[u8], passphrase: &str) -> Result<()> {
+        if master_secret.len() < MIN_SECRET_BYTES || master_secret.len() % 2 != 0 {
+            return Err(Slip39SecretLength {
+                length: master_secret.len(),
+            }
+            .into());
+        }
+        if !passphrase.bytes().all(|b| (32..=126).contains(&b)) {
+            return Err(Slip39Passphrase.into());
+        }
+        if self.iteration_exponent > MAX_ITERATION_EXPONENT {
+            return Err(Slip39IterationExponent {
+                exponent: self.iteration_exponent,
+            }
+            .into());
+        }
+        validate_threshold(self.group_threshold, self.groups.len())?;
+        for group in &self.groups {
+            validate_threshold(group.threshold, usize::from(group.count))?;
+            if group.threshold == 1 && group.count > 1 {
+                return Err(Slip39MemberThresholdOne.into());
+            }
+        }
+        Ok(())
+    }
+}
+
+fn validate_threshold(threshold: u8, count: usize) -> Result<()> {
+    if count == 0 {
+        Err(SharesZero.into())
+    } else if count > MAX_SHARE_COUNT {
+        Err(Slip39TooManyShares { count }.into())
+    } else if threshold == 0 {
+        Err(ThresholdZero.into())
+    } else if usize::from(threshold) > count {
+        Err(ThresholdToLow {
+            threshold: threshold.into(),
+            shares: u16::try_from(count)?,
+        }
+        .into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Split the master secret into SLIP-0039 mnemonics, one list of mnemonics
+/// for each group in the configuration.
+///
+/// # Errors
+/// * This function will generate an error if the master secret is shorter
+///   than 16 bytes or its length is odd.
+/// * This function will generate an error if the passphrase contains
+///   characters other than printable ASCII.
+/// * This function will generate an error if a threshold is 0 or greater than
+///   the number of groups or members, if there are more than 16 groups or
+///   members, or if a group has a member threshold of 1 and more than one
+///   member.
+pub fn split(config: &Config, master_secret: &[u8], passphrase: &str) -> Result<Vec<Vec<String>>> {
+    config.validate(master_secret, passphrase)?;
+    let mut id = [0; 2];
+    rand::rng().fill_bytes(&mut id);
+    let identifier = u16::from_be_bytes(id) >> 1;
+    let encrypted = Cipher {
+        passphrase: passphrase.as_bytes(),
+        iteration_exponent: config.iteration_exponent,
+        identifier,
+        extendable: config.extendable,
+    }
+    .encrypt(master_secret);
+
+    let group_count = u8::try_from(config.groups.len())?;
+    let group_secrets = split_secret(config.group_threshold, group_count, &encrypted);
+    Ok(config
+        .groups
+        .iter()
+        .zip(group_secrets)
+        .map(|(group, (group_index, group_secret))| {
+            split_secret(group.threshold, group.count, &group_secret)
+                .into_iter()
+                .map(|(member_index, value)| {
+                    Share {
+                        identifier,
+                        extendable: config.extendable,
+                        iteration_exponent: config.iteration_exponent,
+                        group_index,
+                        group_threshold: config.group_threshold,
+                        group_count,
+                        member_index,
+                        member_threshold: group.threshold,
+                        value,
+                    }
+                    .to_mnemonic()
+                })
+                .collect()
+        })
+        .collect())
+}
+
+/// Recover the master secret from SLIP-0039 mnemonics.
+///
+/// The mnemonics must hold exactly `group_threshold` groups, with exactly the
+/// member threshold of mnemonics from each of them.  Words may be shortened to
+/// their first four letters.
+///
+/// # Errors
+/// * This function will generate an error if a mnemonic is invalid, or if its
+///   checksum does not match.
+/// * This function will generate an error if the mnemonics do not belong to the
+///   same set of shares, or there are too few or too many of them.
+/// * This function will generate an error if the recovered secret does not
+///   match its digest.
+pub fn combine(mnemonics: &[String], passphrase: &str) -> Result<Vec<u8>> {
+    let shares = mnemonics
+        .iter()
+        .map(|mnemonic| Share::from_mnemonic(mnemonic))
+        .collect::<Result<Vec<Share>>>()?;
+    let first = shares.first().ok_or(EmptySharesMap)?;
+
+    let mut groups: BTreeMap<u8, Vec<&Share>> = BTreeMap::new();
+    for share in &shares {
+        if !share.same_set(first) {
+            return Err(Slip39Mismatch.into());
+        }
+        let group = groups.entry(share.group_index).or_default();
+        if group
+            .iter()
+            .any(|member| member.member_threshold != share.member_threshold)
+        {
+            return Err(Slip39Mismatch.into());
+        }
+        if !group.contains(&share) {
+            group.push(share);
+        }
+    }
+
+    if groups.len() != usize::from(first.group_threshold) {
+        return Err(Slip39GroupCount {
+            expected: first.group_threshold,
+            found: groups.len(),
+        }
+        .into());
+    }
+    let group_secrets = groups
+        .into_iter()
+        .map(|(group_index, members)| {
+            let member_threshold = members[0].member_threshold;
+            if members.len() != usize::from(member_threshold) {
+                return Err(Slip39MemberCount {
+                    group: group_index,
+                    expected: member_threshold,
+                    found: members.len(),
+                }
+                .into());
+            }
+            let members: Vec<(u8, Vec<u8>)> = members
+                .into_iter()
+                .map(|member| (member.member_index, member.value.clone()))
+                .collect();
+            Ok((group_index, recover_secret(member_threshold, &members)?))
+        })
+        .collect::<Result<Vec<(u8, Vec<u8>)>>>()?;
+    let encrypted = recover_secret(first.group_threshold, &group_secrets)?;
+
+    Ok(Cipher {
+        passphrase: passphrase.as_bytes(),
+        iteration_exponent: first.iteration_exponent,
+        identifier: first.identifier,
+        extendable: first.extendable,
+    }
+    .decrypt(&encrypted))
+}
+
+/// A single decoded SLIP-0039 share
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Share {
+    identifier: u16,
+    extendable: bool,
+    iteration_exponent: u8,
+    group_index: u8,
+    group_threshold: u8,
+    group_count: u8,
+    member_index: u8,
+    member_threshold: u8,
+    value: Vec<u8>,
+}
+
+impl Share {
+    fn same_set(&self, other: &Share) -> bool {
+        self.identifier == other.identifier
+            && self.extendable == other.extendable
+            && self.iteration_exponent == other.iteration_exponent
+            && self.group_threshold == other.group_threshold
+            && self.group_count == other.group_count
+    }
+
+    fn to_mnemonic(&self) -> String {
+        let id_exp = u32::from(self.identifier) << 5
+            | u32::from(self.extendable) << 4
+            | u32::from(self.iteration_exponent);
+        let params = u32::from(self.group_index) << 16
+            | u32::from(self.group_threshold - 1) << 12
+            | u32::from(self.group_count - 1) << 8
+            | u32::from(self.member_index) << 4
+            | u32::from(self.member_threshold - 1);
+
+        let mut values: Vec<u16> = [id_exp >> 10, id_exp, params >> 10, params]
+            .iter()
+            .map(|value| u16::try_from(value & 0x3ff).unwrap_or_default())
+            .collect();
+        values.extend(to_words(&self.value));
+        values.extend(rs1024::create(customization(self.extendable), &values));
+        values
+            .iter()
+            .map(|value| words::WORDS[usize::from(*value)])
+            .collect::<Vec<&str>>()
+            .join(" ")
+    }
+
+    fn from_mnemonic(mnemonic: &str) -> Result<Self> {
+        let values = mnemonic
+            .split_whitespace()
+            .enumerate()
+            .map(|(position, word)| lookup(&words::WORDS, position, word))
+            .collect::<Result<Vec<u16>>>()?;
+        let padding = (RADIX_BITS * values.len().saturating_sub(METADATA_WORDS)) % 16;
+        if values.len() < MIN_MNEMONIC_WORDS || padding > 8 {
+            return Err(Slip39MnemonicLength {
+                words: values.len(),
+            }
+            .into());
+        }
+
+        let id_exp = u32::from(values[0]) << 10 | u32::from(values[1]);
+        let extendable = id_exp >> 4 & 1 == 1;
+        if !rs1024::verify(customization(extendable), &values) {
+            return Err(InvalidChecksum.into());
+        }
+        let params = u32::from(values[2]) << 10 | u32::from(values[3]);
+        let nibble = |shift: u32| u8::try_from(params >> shift & 0xf).unwrap_or_default();
+        let share = Share {
+            identifier: u16::try_from(id_exp >> 5)?,
+            extendable,
+            iteration_exponent: u8::try_from(id_exp & 0xf)?,
+            group_index: nibble(16),
+            group_threshold: nibble(12) + 1,
+            group_count: nibble(8) + 1,
+            member_index: nibble(4),
+            member_threshold: nibble(0) + 1,
+            value: from_words(
+                &values[ID_EXP_WORDS + 2..values.len() - rs1024::CHECKSUM_WORDS],
+                padding,
+            )?,
+        };
+        if share.group_threshold > share.group_count {
+            return Err(ThresholdToLow {
+                threshold: share.group_threshold.into(),
+                shares: share.group_count.into(),
+            }
+            .into());
+        }
+        Ok(share)
+    }
+}
+
+/// The customization string of the RS1024 checksum
+fn customization(extendable: bool) -> &'static [u8] {
+    if extendable {
+        b"shamir_extendable"
+    } else {
+        b"shamir"
+    }
+}
+
+/// Split the share value into words, with the padding bits at the front
+fn to_words(value: &[u8]) -> Vec<u16> {
+    let word_count = (value.len() * 8).div_ceil(RADIX_BITS);
+    let padding = word_count * RADIX_BITS - value.len() * 8;
+    let bits: Vec<bool> = std::iter::repeat_n(false, padding)
+        .chain(
+            value
+                .iter()
+                .flat_map(|byte| (0..8).rev().map(move |i| byte >> i & 1 == 1)),
+        )
+        .collect();
+    bits.chunks(RADIX_BITS)
+        .map(|chunk| chunk.iter().fold(0, |acc, bit| acc << 1 | u16::from(*bit)))
+        .collect()
+}
+
+/// Join the words back into the share value, the inverse of [`to_words`]
+fn from_words(values: &[u16], padding: usize) -> Result<Vec<u8>> {
+    let bits: Vec<bool> = values
+        .iter()
+        .flat_map(|value| (0..RADIX_BITS).rev().map(move |i| value >> i & 1 == 1))
+        .collect();
+    if bits[..padding].iter().any(|bit| *bit) {
+        return Err(Slip39Padding.into());
+    }
+    Ok(bits[padding..]
+        .chunks(8)
+        .map(|chunk| chunk.iter().fold(0, |acc, bit| acc << 1 | u8::from(*bit)))
+        .collect())
+}
+
+/// Split a secret into `count` shares, `threshold` of which recover it.
+///
+/// The polynomial passes through `threshold - 2` random shares, the digest
+/// share at x = 254 and the secret at x = 255.
+fn split_secret(threshold: u8, count: u8, secret: &[u8]) -> Vec<(u8, Vec<u8>)> {
+    if threshold == 1 {
+        return (0..count).map(|index| (index, secret.to_vec())).collect();
+    }
+
+    let mut rng = rand::rng();
+    let mut random = |len| {
+        let mut bytes = vec![0; len];
+        rng.fill_bytes(&mut bytes);
+        bytes
+    };
+    let random_count = threshold - 2;
+    let mut shares: Vec<(u8, Vec<u8>)> = (0..random_count)
+        .map(|index| (index, random(secret.len())))
+        .collect();
+    let random_part = random(secret.len() - DIGEST_BYTES);
+    let mut digest_share = digest(&random_part, secret);
+    digest_share.extend(random_part);
+
+    let mut base = shares.clone();
+    base.push((DIGEST_INDEX, digest_share));
+    base.push((SECRET_INDEX, secret.to_vec()));
+    for index in random_count..count {
+        shares.push((index, interpolate(&base, index)));
+    }
+    shares
+}
+
+/// Recover a secret from exactly `threshold` shares, and check its digest
+fn recover_secret(threshold: u8, shares: &[(u8, Vec<u8>)]) -> Result<Vec<u8>> {
+    let mut indices: Vec<u8> = shares.iter().map(|(index, _)| *index).collect();
+    indices.sort_unstable();
+    indices.dedup();
+    if indices.len() != shares.len() {
+        return Err(Slip39DuplicateIndex.into());
+    }
+    if shares
+        .iter()
+        .any(|(_, value)| value.len() != shares[0].1.len())
+    {
+        return Err(ShareLengthMismatch.into());
+    }
+    if threshold == 1 {
+        return Ok(shares[0].1.clone());
+    }
+
+    let secret = interpolate(shares, SECRET_INDEX);
+    let digest_share = interpolate(shares, DIGEST_INDEX);
+    let (expected, random_part) = digest_share.split_at(DIGEST_BYTES);
+    if digest(random_part, &secret) == expected {
+        Ok(secret)
+    } else {
+        Err(Slip39Digest.into())
+    }
+}
+
+/// Evaluate the polynomial through the given shares at `x`, byte by byte
+fn interpolate(shares: &[(u8, Vec<u8>)], x: u8) -> Vec<u8> {
+    (0..shares[0].1.len())
+        .map(|i| {
+            let points: Vec<Vec<u8>> = shares
+                .iter()
+                .map(|(index, value)| vec![*index, value[i]])
+                .collect();
+            gf256::interpolate_at(&points, x)
+        })
+        .collect()
+}
+
+/// The first four bytes of HMAC-SHA256 of the secret, keyed by the random part
+fn digest(random_part: &[u8], secret: &[u8]) -> Vec<u8> {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(random_part).expect("HMAC accepts keys of any length");
+    mac.update(secret);
+    mac.finalize().into_bytes()[..DIGEST_BYTES].to_vec()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Config, Group, combine, split, words::WORDS};
+    use crate::{encoding::hex, utils::check_err_result};
+    use anyhow::Result;
+
+    /// Test vectors from `vectors.json` of the SLIP-0039 reference
+    /// implementation, numbered as there, all using the passphrase `TREZOR`.
+    /// The expected value is the master secret in hex, or the expected error.
+    const VECTORS: &[(&str, &[&str], Result<&str, &str>)] = &[
+        (
+            "1. Valid mnemonic without sharing (128 bits)",
+            &[
+                "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision keyboard",
+            ],
+            Ok("bb54aac4b89dc868ba37d9cc21b2cece"),
+        ),
+        (
+            "2. Mnemonic with invalid checksum (128 bits)",
+            &[
+                "duckling enlarge academic academic agency result length solution fridge kidney coal piece deal husband erode duke ajar critical decision kidney",
+            ],
+            Err("Invalid share checksum"),
+        ),
+        (
+            "3. Mnemonic with invalid padding (128 bits)",
+            &[
+                "duckling enlarge academic academic email result length solution fridge kidney coal piece deal husband erode duke ajar music cargo fitness",
+            ],
+            Err("Invalid mnemonic padding"),
+        ),
+        (
+            "4. Basic sharing 2-of-3 (128 bits)",
+            &[
+                "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+                "shadow pistol academic acid actress prayer class unknown daughter sweater depict flip twice unkind craft early superior advocate guest smoking",
+            ],
+            Ok("b43ceb7e57a0ea8766221624d01b0864"),
+        ),
+        (
+            "5. Basic sharing 2-of-3 (128 bits)",
+            &[
+                "shadow pistol academic always adequate wildlife fancy gross oasis cylinder mustang wrist rescue view short owner flip making coding armed",
+            ],
+            Err("Wrong number of mnemonics in group 0, expected 2 but 1 were provided"),
+        ),
+        (
+            "6. Mnemonics with different identifiers (128 bits)",
+            &[
+                "adequate smoking academic acid debut wine petition glen cluster slow rhyme slow simple epidemic rumor junk tracks treat olympic tolerate",
+                "adequate stay academic agency agency formal party ting frequent learn upstairs remember smear leaf damage anatomy ladle market hush corner",
+            ],
+            Err("The mnemonics do not all belong to the same set of shares"),
+        ),
+        (
+            "7. Mnemonics with different iteration exponents (128 bits)",
+            &[
+                "peasant leaves academic acid desert exact olympic math alive axle trial tackle drug deny decent smear dominant desert bucket remind",
+                "peasant leader academic agency cultural blessing percent network envelope medal junk primary human pumps jacket fragment payroll ticket evoke voice",
+            ],
+            Err("The mnemonics do not all belong to the same set of shares"),
+        ),
+        (
+            "8. Mnemonics with mismatching group thresholds (128 bits)",
+            &[
+                "liberty category beard echo animal fawn temple briefing math username various wolf aviation fancy visual holy thunder yelp helpful payment",
+                "liberty category beard email beyond should fancy romp founder easel pink holy hairy romp loyalty material victim owner toxic custody",
+                "liberty category academic easy being hazard crush diminish oral lizard reaction cluster force dilemma deploy force club veteran expect photo",
+            ],
+            Err("The mnemonics do not all belong to the same set of shares"),
+        ),
+        (
+            "9. Mnemonics with mismatching group counts (128 bits)",
+            &[
+                "average senior academic leaf broken teacher expect surface hour capture obesity desire negative dynamic dominant pistol mineral mailman iris aide",
+                "average senior academic agency curious pants blimp spew clothes slice script dress wrap firm shaft regular slavery negative theater roster",
+            ],
+            Err("The mnemonics do not all belong to the same set of shares"),
+        ),
+        (
+            "10. Mnemonics with greater group threshold than group counts (128 bits)",
+            &[
+                "music husband acrobat acid artist finance center either graduate swimming object bike medical clothes station aspect spider maiden bulb welcome",
+                "music husband acrobat agency advance hunting bike corner density careful material civil evil tactics remind hawk discuss hobo voice rainbow",
+                "music husband beard academic black tricycle clock mayor estimate level photo episode exclude ecology papa source amazing salt verify divorce",
+            ],
+            Err(
+                "You have specified an invalid threshold.  It must be less than or equal to the number of shares. (2 is not <= 1)",
+            ),
+        ),
+        (
+            "11. Mnemonics with duplicate member indices (128 bits)",
+            &[
+                "device stay academic always dive coal antenna adult black exceed stadium herald advance soldier busy dryer daughter evaluate minister laser",
+                "device stay academic always dwarf afraid robin gravity crunch adjust soul branch walnut coastal dream costume scholar mortgage mountain pumps",
+            ],
+            Err("Share indices must be unique"),
+        ),
+        (
+            "12. Mnemonics with mismatching member thresholds (128 bits)",
+            &[
+                "hour painting academic academic device formal evoke guitar random modern justice filter withdraw trouble identify mailman insect general cover oven",
+                "hour painting academic agency artist again daisy capital beaver fiber much enjoy suitable symbolic identify photo editor romp float echo",
+            ],
+            Err("The mnemonics do not all belong to the same set of shares"),
+        ),
+        (
+            "13. Mnemonics giving an invalid digest (128 bits)",
+            &[
+                "guilt walnut academic acid deliver remove equip listen vampire tactics nylon rhythm failure husband fatigue alive blind enemy teaspoon rebound",
+                "guilt walnut academic agency brave hamster hobo declare herd taste alpha slim criminal mild arcade formal romp branch pink ambition",
+            ],
+            Err("Invalid digest of the shared secret"),
+        ),
+        (
+            "14. Insufficient number of groups (128 bits, case 1)",
+            &[
+                "eraser senior beard romp adorn nuclear spill corner cradle style ancient family general leader ambition exchange unusual garlic promise voice",
+            ],
+            Err("Wrong number of mnemonic groups, expected 2 but 1 were provided"),
+        ),
+        (
+            "15. Insufficient number of groups (128 bits, case 2)",
+            &[
+                "eraser senior ceramic snake clay various huge numb argue hesitate auction category timber browser greatest hanger petition script leaf pickup",
+                "eraser senior ceramic shaft dynamic become junior wrist silver peasant force math alto coal amazing segment yelp velvet image paces",
+            ],
+            Err("Wrong number of mnemonic groups, expected 2 but 1 were provided"),
+        ),
+        (
+            "16. Threshold number of groups, but insufficient number of members in one group (128 bits)",
+            &[
+                "eraser senior decision shadow artist work morning estate greatest pipeline plan ting petition forget hormone flexible general goat admit surface",
+                "eraser senior beard romp adorn nuclear spill corner cradle style ancient family general leader ambition exchange unusual garlic promise voice",
+            ],
+            Err("Wrong number of mnemonics in group 3, expected 2 but 1 were provided"),
+        ),
+        (
+            "17. Threshold number of groups and members in each group (128 bits, case 1)",
+            &[
+                "eraser senior ceramic round column hawk trust auction smug shame alive greatest sheriff living perfect corner chest sled fumes adequate",
+                "eraser senior decision roster beard treat identify grumpy salt index fake aviation theater cubic bike cause research dragon emphasis counter",
+                "eraser senior ceramic snake clay various huge numb argue hesitate auction category timber browser greatest hanger petition script leaf pickup",
+                "eraser senior ceramic shaft dynamic become junior wrist silver peasant force math alto coal amazing segment yelp velvet image paces",
+                "eraser senior decision shadow artist work morning estate greatest pipeline plan ting petition forget hormone flexible general goat admit surface",
+            ],
+            Ok("7c3397a292a5941682d7a4ae2d898d11"),
+        ),
+        (
+            "18. Threshold number of groups and members in each group (128 bits, case 2)",
+            &[
+                "eraser senior decision smug corner ruin rescue cubic angel tackle skin skunk program roster trash rumor slush angel flea amazing",
+                "eraser senior ceramic round column hawk trust auction smug shame alive greatest sheriff living perfect corner chest sled fumes adequate",
+                "eraser senior decision roster beard treat identify grumpy salt index fake aviation theater cubic bike cause research dragon emphasis counter",
+                "eraser senior ceramic shaft dynamic become junior wrist silver peasant force math alto coal amazing segment yelp velvet image paces",
+                "eraser senior ceramic snake clay various huge numb argue hesitate auction category timber browser greatest hanger petition script leaf pickup",
+            ],
+            Ok("7c3397a292a5941682d7a4ae2d898d11"),
+        ),
+        (
+            "19. Threshold number of groups and members in each group (128 bits, case 3)",
+            &[
+                "eraser senior beard romp adorn nuclear spill corner cradle style ancient family general leader ambition exchange unusual garlic promise voice",
+                "eraser senior acrobat romp bishop medical gesture pumps secret alive ultimate quarter priest subject class dictate spew material endless market",
+            ],
+            Ok("7c3397a292a5941682d7a4ae2d898d11"),
+        ),
+        (
+            "20. Valid mnemonic without sharing (256 bits)",
+            &[
+                "theory painting academic academic armed sweater year military elder discuss acne wildlife boring employer fused large satoshi bundle carbon diagnose anatomy hamster leaves tracks paces beyond phantom capital marvel lips brave detect luck",
+            ],
+            Ok("989baf9dcaad5b10ca33dfd8cc75e42477025dce88ae83e75a230086a0e00e92"),
+        ),
+        (
+            "21. Mnemonic with invalid checksum (256 bits)",
+            &[
+                "theory painting academic academic armed sweater year military elder discuss acne wildlife boring employer fused large satoshi bundle carbon diagnose anatomy hamster leaves tracks paces beyond phantom capital marvel lips brave detect lunar",
+            ],
+            Err("Invalid share checksum"),
+        ),
+        (
+            "22. Mnemonic with invalid padding (256 bits)",
+            &[
+                "theory painting academic academic campus sweater year military elder discuss acne wildlife boring employer fused large satoshi bundle carbon diagnose anatomy hamster leaves tracks paces beyond phantom capital marvel lips facility obtain sister",
+            ],
+            Err("Invalid mnemonic padding"),
+        ),
+        (
+            "23. Basic sharing 2-of-3 (256 bits)",
+            &[
+                "humidity disease academic always aluminum jewelry energy woman receiver strategy amuse duckling lying evidence network walnut tactics forget hairy rebound impulse brother survive clothes stadium mailman rival ocean reward venture always armed unwrap",
+                "humidity disease academic agency actress jacket gross physics cylinder solution fake mortgage benefit public busy prepare sharp friar change work slow purchase ruler again tricycle involve viral wireless mixture anatomy desert cargo upgrade",
+            ],
+            Ok("c938b319067687e990e05e0da0ecce1278f75ff58d9853f19dcaeed5de104aae"),
+        ),
+        (
+            "24. Basic sharing 2-of-3 (256 bits)",
+            &[
+                "humidity disease academic always aluminum jewelry energy woman receiver strategy amuse duckling lying evidence network walnut tactics forget hairy rebound impulse brother survive clothes stadium mailman rival ocean reward venture always armed unwrap",
+            ],
+            Err("Wrong number of mnemonics in group 0, expected 2 but 1 were provided"),
+        ),
+        (
+            "25. Mnemonics with different identifiers (256 bits)",
+            &[
+                "smear husband academic acid deadline scene venture distance dive overall parking bracelet elevator justice echo burning oven chest duke nylon",
+                "smear isolate academic agency alpha mandate decorate burden recover guard exercise fatal force syndrome fumes thank guest drift dramatic mule",
+            ],
+            Err("The mnemonics do not all belong to the same set of shares"),
+        ),
+        (
+            "26. Mnemonics with different iteration exponents (256 bits)",
+            &[
+                "finger trash academic acid average priority dish revenue academic hospital spirit western ocean fact calcium syndrome greatest plan losing dictate",
+                "finger traffic academic agency building lilac deny paces subject threaten diploma eclipse window unknown health slim piece dragon focus smirk",
+            ],
+            Err("The mnemonics do not all belong to the same set of shares"),
+        ),
+        (
+            "27. Mnemonics with mismatching group thresholds (256 bits)",
+            &[
+                "flavor pink beard echo depart forbid retreat become frost helpful juice unwrap reunion credit math burning spine black capital lair",
+                "flavor pink beard email diet teaspoon freshman identify document rebound cricket prune headset loyalty smell emission skin often square rebound",
+                "flavor pink academic easy credit cage raisin crazy closet lobe mobile become drink human tactics valuable hand capture sympathy finger",
+            ],
+            Err("The mnemonics do not all belong to the same set of shares"),
+        ),
+        (
+            "28. Mnemonics with mismatching group counts (256 bits)",
+            &[
+                "column flea academic leaf debut extra surface slow timber husky lawsuit game behavior husky swimming already paper episode tricycle scroll",
+                "column flea academic agency blessing garbage party software stadium verify silent umbrella therapy decorate chemical erode dramatic eclipse replace apart",
+            ],
+            Err("The mnemonics do not all belong to the same set of shares"),
+        ),
+        (
+            "29. Mnemonics with greater group threshold than group counts (256 bits)",
+            &[
+                "smirk pink acrobat acid auction wireless impulse spine sprinkle fortune clogs elbow guest hush loyalty crush dictate tracks airport talent",
+                "smirk pink acrobat agency dwarf emperor ajar organize legs slice harvest plastic dynamic style mobile float bulb health coding credit",
+                "smirk pink beard academic alto strategy carve shame language rapids ruin smart location spray training acquire eraser endorse submit peaceful",
+            ],
+            Err(
+                "You have specified an invalid threshold.  It must be less than or equal to the number of shares. (2 is not <= 1)",
+            ),
+        ),
+        (
+            "30. Mnemonics with duplicate member indices (256 bits)",
+            &[
+                "fishing recover academic always device craft trend snapshot gums skin downtown watch device sniff hour clock public maximum garlic born",
+                "fishing recover academic always aircraft view software cradle fangs amazing package plastic evaluate intend penalty epidemic anatomy quarter cage apart",
+            ],
+            Err("Share indices must be unique"),
+        ),
+        (
+            "31. Mnemonics with mismatching member thresholds (256 bits)",
+            &[
+                "evoke garden academic academic answer wolf scandal modern warmth station devote emerald market physics surface formal amazing aquatic gesture medical",
+                "evoke garden academic agency deal revenue knit reunion decrease magazine flexible company goat repair alarm military facility clogs aide mandate",
+            ],
+            Err("The mnemonics do not all belong to the same set of shares"),
+        ),
+        (
+            "32. Mnemonics giving an invalid digest (256 bits)",
+            &[
+                "river deal academic acid average forbid pistol peanut custody bike class aunt hairy merit valid flexible learn ajar very easel",
+                "river deal academic agency camera amuse lungs numb isolate display smear piece traffic worthy year patrol crush fact fancy emission",
+            ],
+            Err("Invalid digest of the shared secret"),
+        ),
+        (
+            "33. Insufficient number of groups (256 bits, case 1)",
+            &[
+                "wildlife deal beard romp alcohol space mild usual clothes union nuclear testify course research heat listen task location thank hospital slice smell failure fawn helpful priest ambition average recover lecture process dough stadium",
+            ],
+            Err("Wrong number of mnemonic groups, expected 2 but 1 were provided"),
+        ),
+        (
+            "34. Insufficient number of groups (256 bits, case 2)",
+            &[
+                "wildlife deal decision scared acne fatal snake paces obtain election dryer dominant romp tactics railroad marvel trust helpful flip peanut theory theater photo luck install entrance taxi step oven network dictate intimate listen",
+                "wildlife deal decision smug ancestor genuine move huge cubic strategy smell game costume extend swimming false desire fake traffic vegan senior twice timber submit leader payroll fraction apart exact forward pulse tidy install",
+            ],
+            Err("Wrong number of mnemonic groups, expected 2 but 1 were provided"),
+        ),
+        (
+            "35. Threshold number of groups, but insufficient number of members in one group (256 bits)",
+            &[
+                "wildlife deal decision shadow analysis adjust bulb skunk muscle mandate obesity total guitar coal gravity carve slim jacket ruin rebuild ancestor numerous hour mortgage require herd maiden public ceiling pecan pickup shadow club",
+                "wildlife deal beard romp alcohol space mild usual clothes union nuclear testify course research heat listen task location thank hospital slice smell failure fawn helpful priest ambition average recover lecture process dough stadium",
+            ],
+            Err("Wrong number of mnemonics in group 3, expected 2 but 1 were provided"),
+        ),
+        (
+            "36. Threshold number of groups and members in each group (256 bits, case 1)",
+            &[
+                "wildlife deal ceramic round aluminum pitch goat racism employer miracle percent math decision episode dramatic editor lily prospect program scene rebuild display sympathy have single mustang junction relate often chemical society wits estate",
+                "wildlife deal decision scared acne fatal snake paces obtain election dryer dominant romp tactics railroad marvel trust helpful flip peanut theory theater photo luck install entrance taxi step oven network dictate intimate listen",
+                "wildlife deal ceramic scatter argue equip vampire together ruin reject literary rival distance aquatic agency teammate rebound false argue miracle stay again blessing peaceful unknown cover beard acid island language debris industry idle",
+                "wildlife deal ceramic snake agree voter main lecture axis kitchen physics arcade velvet spine idea scroll promise platform firm sharp patrol divorce ancestor fantasy forbid goat ajar believe swimming cowboy symbolic plastic spelling",
+                "wildlife deal decision shadow analysis adjust bulb skunk muscle mandate obesity total guitar coal gravity carve slim jacket ruin rebuild ancestor numerous hour mortgage require herd maiden public ceiling pecan pickup shadow club",
+            ],
+            Ok("5385577c8cfc6c1a8aa0f7f10ecde0a3318493262591e78b8c14c6686167123b"),
+        ),
+        (
+            "37. Threshold number of groups and members in each group (256 bits, case 2)",
+            &[
+                "wildlife deal ceramic round aluminum pitch goat racism employer miracle percent math decision episode dramatic editor lily prospect program scene rebuild display sympathy have single mustang junction relate often chemical society wits estate",
+                "wildlife deal ceramic scatter argue equip vampire together ruin reject literary rival distance aquatic agency teammate rebound false argue miracle stay again blessing peaceful unknown cover beard acid island language debris industry idle",
+                "wildlife deal ceramic snake agree voter main lecture axis kitchen physics arcade velvet spine idea scroll promise platform firm sharp patrol divorce ancestor fantasy forbid goat ajar believe swimming cowboy symbolic plastic spelling",
+                "wildlife deal decision shadow analysis adjust bulb skunk muscle mandate obesity total guitar coal gravity carve slim jacket ruin rebuild ancestor numerous hour mortgage require herd maiden public ceiling pecan pickup shadow club",
+                "wildlife deal decision smug ancestor genuine move huge cubic strategy smell game costume extend swimming false desire fake traffic vegan senior twice timber submit leader payroll fraction apart exact forward pulse tidy install",
+            ],
+            Ok("5385577c8cfc6c1a8aa0f7f10ecde0a3318493262591e78b8c14c6686167123b"),
+        ),
+        (
+            "38. Threshold number of groups and members in each group (256 bits, case 3)",
+            &[
+                "wildlife deal beard romp alcohol space mild usual clothes union nuclear testify course research heat listen task location thank hospital slice smell failure fawn helpful priest ambition average recover lecture process dough stadium",
+                "wildlife deal acrobat romp anxiety axis starting require metric flexible geology game drove editor edge screw helpful have huge holy making pitch unknown carve holiday numb glasses survive already tenant adapt goat fangs",
+            ],
+            Ok("5385577c8cfc6c1a8aa0f7f10ecde0a3318493262591e78b8c14c6686167123b"),
+        ),
+        (
+            "39. Mnemonic with insufficient length",
+            &[
+                "junk necklace academic academic acne isolate join hesitate lunar roster dough calcium chemical ladybug amount mobile glasses verify cylinder",
+            ],
+            Err("Invalid mnemonic length: 19 words"),
+        ),
+        (
+            "40. Mnemonic with invalid master secret length",
+            &[
+                "fraction necklace academic academic award teammate mouse regular testify coding building member verdict purchase blind camera duration email prepare spirit quarter",
+            ],
+            Err("Invalid mnemonic length: 21 words"),
+        ),
+        (
+            "41. Valid mnemonics which can detect some errors in modular arithmetic",
+            &[
+                "herald flea academic cage avoid space trend estate dryer hairy evoke eyebrow improve airline artwork garlic premium duration prevent oven",
+                "herald flea academic client blue skunk class goat luxury deny presence impulse graduate clay join blanket bulge survive dish necklace",
+                "herald flea academic acne advance fused brother frozen broken game ranked ajar already believe check install theory angry exercise adult",
+            ],
+            Ok("ad6f2ad8b59bbbaa01369b9006208d9a"),
+        ),
+        (
+            "42. Valid extendable mnemonic without sharing (128 bits)",
+            &[
+                "testify swimming academic academic column loyalty smear include exotic bedroom exotic wrist lobe cover grief golden smart junior estimate learn",
+            ],
+            Ok("1679b4516e0ee5954351d288a838f45e"),
+        ),
+        (
+            "43. Extendable basic sharing 2-of-3 (128 bits)",
+            &[
+                "enemy favorite academic acid cowboy phrase havoc level response walnut budget painting inside trash adjust froth kitchen learn tidy punish",
+                "enemy favorite academic always academic sniff script carpet romp kind promise scatter center unfair training emphasis evening belong fake enforce",
+            ],
+            Ok("48b1a4b80b8c209ad42c33672bdaa428"),
+        ),
+        (
+            "44. Valid extendable mnemonic without sharing (256 bits)",
+            &[
+                "impulse calcium academic academic alcohol sugar lyrics pajamas column facility finance tension extend space birthday rainbow swimming purple syndrome facility trial warn duration snapshot shadow hormone rhyme public spine counter easy hawk album",
+            ],
+            Ok("8340611602fe91af634a5f4608377b5235fa2d757c51d720c0c7656249a3035f"),
+        ),
+        (
+            "45. Extendable basic sharing 2-of-3 (256 bits)",
+            &[
+                "western apart academic always artist resident briefing sugar woman oven coding club ajar merit pecan answer prisoner artist fraction amount desktop mild false necklace muscle photo wealthy alpha category unwrap spew losing making",
+                "western apart academic acid answer ancient auction flip image penalty oasis beaver multiple thunder problem switch alive heat inherit superior teaspoon explain blanket pencil numb lend punish endless aunt garlic humidity kidney observe",
+            ],
+            Ok("8dc652d6d6cd370d8c963141f6d79ba440300f25c467302c1d966bff8f62300d"),
+        ),
+    ];
+
+    #[test]
+    fn word_list_is_complete() {
+        assert_eq!(WORDS[0], "academic");
+        assert_eq!(WORDS[1023], "zero");
+        assert!(WORDS.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+
+    #[test]
+    fn reference_vectors() -> Result<()> {
+        for (description, mnemonics, expected) in VECTORS {
+            let mnemonics: Vec<String> = mnemonics.iter().map(ToString::to_string).collect();
+            let result = combine(&mnemonics, "TREZOR");
+            match expected {
+                Ok(secret) => assert_eq!(hex::encode(&result?), *secret, "{description}"),
+                Err(message) => check_err_result(result, message)?,
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn split_and_combine_groups() -> Result<()> {
+        let secret: Vec<u8> = (0..32).collect();
+        for extendable in [false, true] {
+            let config = Config::builder()
+                .group_threshold(2)
+                .groups(vec![
+                    Group::builder().threshold(1).count(1).build(),
+                    Group::builder().threshold(2).count(3).build(),
+                    Group::builder().threshold(3).count(5).build(),
+                ])
+                .iteration_exponent(0)
+                .extendable(extendable)
+                .build();
+            let groups = split(&config, &secret, "TREZOR")?;
+            assert_eq!(groups.len(), 3);
+            assert_eq!(groups[2].len(), 5);
+
+            let mut mnemonics = vec![groups[2][4].clone(), groups[1][1].clone()];
+            check_err_result(
+                combine(&mnemonics, "TREZOR"),
+                "Wrong number of mnemonics in group 1, expected 2 but 1 were provided",
+            )?;
+            mnemonics.push(groups[1][2].clone());
+            check_err_result(
+                combine(&mnemonics, "TREZOR"),
+                "Wrong number of mnemonics in group 2, expected 3 but 1 were provided",
+            )?;
+            mnemonics[0].clone_from(&groups[0][0]);
+            assert_eq!(combine(&mnemonics, "TREZOR")?, secret);
+            assert_ne!(combine(&mnemonics, "")?, secret);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn prefixes_are_accepted() -> Result<()> {
+        let secret = b"ABCDEFGHIJKLMNOP";
+        let config = Config::builder()
+            .groups(vec![Group::builder().threshold(1).count(1).build()])
+            .iteration_exponent(0)
+            .build();
+        let mnemonic = &split(&config, secret, "")?[0][0];
+        let shortened = mnemonic
+            .split(' ')
+            .map(|word| word.chars().take(4).collect::<String>().to_uppercase())
+            .collect::<Vec<String>>()
+            .join(" ");
+        assert_eq!(combine(&[shortened], "")?, secret);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_configurations_error() -> Result<()> {
+        let config = Config::default();
+        check_err_result(
+            split(&config, b"too short", ""),
+            "The master secret must be an even number of at least 16 bytes, but was 9 bytes",
+        )?;
+        check_err_result(
+            split(&config, b"ABCDEFGHIJKLMNOP", "caf\u{e9}"),
+            "The passphrase must contain only printable ASCII characters",
+        )?;
+        let config = Config::builder()
+            .groups(vec![Group::builder().threshold(1).count(2).build()])
+            .build();
+        check_err_result(
+            split(&config, b"ABCDEFGHIJKLMNOP", ""),
+            "Creating multiple member shares with member threshold 1 is not allowed, use 1-of-1 member sharing instead",
+        )?;
+        let config = Config::builder().group_threshold(2).build();
+        check_err_result(
+            split(&config, b"ABCDEFGHIJKLMNOP", ""),
+            "You have specified an invalid threshold.  It must be less than or equal to the number of shares. (2 is not <= 1)",
+        )
+    }
+}
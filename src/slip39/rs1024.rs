@@ -0,0 +1,74 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! The SLIP-0039 RS1024 checksum
+//!
+//! A Reed-Solomon code over GF(1024) that detects any error affecting at most
+//! three words, keyed by a customization string.
+
+/// The number of checksum words
+pub(crate) const CHECKSUM_WORDS: usize = 3;
+
+const GEN: [u32; 10] = [
+    0x00e0_e040,
+    0x01c1_c080,
+    0x0383_8100,
+    0x0707_0200,
+    0x0e0e_0009,
+    0x1c0c_2412,
+    0x3808_6c24,
+    0x3090_fc48,
+    0x21b1_f890,
+    0x03f3_f120,
+];
+
+fn polymod(customization: &[u8], values: &[u16]) -> u32 {
+    customization
+        .iter()
+        .map(|c| u16::from(*c))
+        .chain(values.iter().copied())
+        .fold(1, |chk, value| {
+            let b = chk >> 20;
+            let chk = (chk & 0x000f_ffff) << 10 ^ u32::from(value);
+            GEN.iter()
+                .enumerate()
+                .filter(|(i, _)| b >> i & 1 == 1)
+                .fold(chk, |chk, (_, g)| chk ^ g)
+        })
+}
+
+/// Create the checksum words for the given data words
+pub(crate) fn create(customization: &[u8], values: &[u16]) -> [u16; CHECKSUM_WORDS] {
+    let mut padded = values.to_vec();
+    padded.extend([0; CHECKSUM_WORDS]);
+    let chk = polymod(customization, &padded) ^ 1;
+    [20, 10, 0].map(|shift| u16::try_from(chk >> shift & 0x3ff).unwrap_or_default())
+}
+
+/// Verify data words followed by their checksum words
+pub(crate) fn verify(customization: &[u8], values: &[u16]) -> bool {
+    polymod(customization, values) == 1
+}
+
+#[cfg(test)]
+mod test {
+    use super::{create, verify};
+
+    #[test]
+    fn checksum_roundtrip() {
+        let mut values = vec![1, 2, 3, 1023, 0, 512];
+        values.extend(create(b"shamir", &values));
+        assert!(verify(b"shamir", &values));
+        assert!(!verify(b"shamir_extendable", &values));
+        for i in 0..values.len() {
+            let mut mistyped = values.clone();
+            mistyped[i] ^= 0x155;
+            assert!(!verify(b"shamir", &mistyped));
+        }
+    }
+}
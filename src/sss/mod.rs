@@ -0,0 +1,357 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! Shares compatible with D. Sprenkels'
+//! [`sss`](https://github.com/dsprenkels/sss) library.
+//!
+//! `sss` splits 32-byte keys over the same GF(2^8) as this crate, reduced by
+//! `0x11b`, into 33-byte key shares: the share index, from 1 to `n`, followed
+//! by the 32 bytes of the share.  These are made by [`split_key`] and
+//! combined by [`combine_keys`], like `sss_create_keyshares` and
+//! `sss_combine_keyshares`.
+//!
+//! Its message mode shares a 64-byte message instead.  The message is
+//! encrypted with `crypto_secretbox`, XSalsa20-Poly1305, under a random key
+//! and an all-zero nonce, and the key is split into key shares.  Each
+//! 113-byte share is
+//!
+//! | Bytes | Field                                          |
+//! |-------|------------------------------------------------|
+//! | 33    | the key share                                  |
+//! | 16    | the Poly1305 tag                               |
+//! | 64    | the encrypted message, the same in every share |
+//!
+//! These are made by [`split`] and combined by [`combine`], like
+//! `sss_create_shares` and `sss_combine_shares`.  Since the message is
+//! authenticated, too few shares or a damaged share give an error rather than
+//! a wrong message.
+//!
+//! # Example
+//! ```
+//! # use anyhow::Result;
+//! # use ssss::sss::{combine, split, Config, MESSAGE_LEN, SHARE_LEN};
+//! #
+//! # pub fn main() -> Result<()> {
+//! let mut message = [0; MESSAGE_LEN];
+//! message[..13].copy_from_slice(b"launch codes!");
+//!
+//! let config = Config::builder().threshold(3).num_shares(5).build();
+//! let shares = split(&config, &message)?;
+//! assert_eq!(shares[4].len(), SHARE_LEN);
+//! assert_eq!(shares[4][0], 5);
+//!
+//! assert_eq!(combine(&shares[1..4])?, message);
+//! assert!(combine(&shares[3..]).is_err());
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    error::SsssError::{SssCiphertextMismatch, SssDecrypt, ThresholdToLow, ThresholdZero},
+    field::Gf256,
+    shamir::{gen_shares_over, unlock_over},
+};
+use anyhow::Result;
+use bon::Builder;
+use crypto_secretbox::{
+    Key, Nonce, XSalsa20Poly1305,
+    aead::{Aead, KeyInit},
+};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// The length of a key
+pub const KEY_LEN: usize = 32;
+/// The length of a key share, the index and the share of the key
+pub const KEYSHARE_LEN: usize = KEY_LEN + 1;
+/// The length of a message
+pub const MESSAGE_LEN: usize = 64;
+/// The length of a Poly1305 tag
+const TAG_LEN: usize = 16;
+/// The length of a share of a message
+pub const SHARE_LEN: usize = KEYSHARE_LEN + TAG_LEN + MESSAGE_LEN;
+
+/// A share of a key, made by [`split_key`]
+pub type Keyshare = [u8; KEYSHARE_LEN];
+/// A share of a message, made by [`split`]
+pub type Share = [u8; SHARE_LEN];
+
+/// Configuration used to drive the [`split`] and [`split_key`] functions.
+///
+/// # Notes
+/// The default configuration will specify 5 shares with a threshold of 3.
+#[derive(Builder, Clone, Copy, Debug, Deserialize, Serialize)]
+pub struct Config {
+    /// The number of shares required to recover the secret, at least 1
+    #[builder(default = 3)]
+    threshold: u8,
+    /// The number of shares to generate
+    #[builder(default = 5)]
+    num_shares: u8,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config::builder().build()
+    }
+}
+
+impl Config {
+    fn validate(self) -> Result<()> {
+        if self.threshold == 0 {
+            Err(ThresholdZero.into())
+        } else if self.threshold > self.num_shares {
+            Err(ThresholdToLow {
+                threshold: self.threshold.into(),
+                shares: self.num_shares.into(),
+            }
+            .into())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Split a key into key shares in the layout of `sss_create_keyshares`.
+///
+/// # Errors
+/// * This function will generate an error if the threshold is 0 or greater
+///   than the number of shares.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::sss::{combine_keys, split_key, Config};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let key = [0x2a; 32];
+/// let config = Config::builder().threshold(2).num_shares(3).build();
+/// let keyshares = split_key(&config, &key)?;
+/// assert_eq!(keyshares.iter().map(|share| share[0]).collect::<Vec<u8>>(), [1, 2, 3]);
+/// assert_eq!(combine_keys(&keyshares[1..])?, key);
+/// # Ok(())
+/// # }
+/// ```
+pub fn split_key(config: &Config, key: &[u8; KEY_LEN]) -> Result<Vec<Keyshare>> {
+    config.validate()?;
+    let secret: Vec<Gf256> = key.iter().copied().map(Gf256).collect();
+    let indices: Vec<Gf256> = (1..=config.num_shares).map(Gf256).collect();
+    let shares = gen_shares_over(config.threshold.into(), &secret, &indices)?;
+    Ok(indices
+        .iter()
+        .zip(shares)
+        .map(|(x, share)| {
+            let mut keyshare = [0; KEYSHARE_LEN];
+            keyshare[0] = x.0;
+            for (byte, y) in keyshare[1..].iter_mut().zip(share) {
+                *byte = y.0;
+            }
+            keyshare
+        })
+        .collect())
+}
+
+/// Combine key shares in the layout of `sss_create_keyshares` into the key,
+/// like `sss_combine_keyshares`.
+///
+/// As with [`unlock`](crate::unlock), fewer shares than the threshold give a
+/// wrong key rather than an error.
+///
+/// # Errors
+/// * This function will generate an error if no key shares are given, or an
+///   index is 0 or given more than once.
+pub fn combine_keys(keyshares: &[Keyshare]) -> Result<[u8; KEY_LEN]> {
+    let points: Vec<(Gf256, Vec<Gf256>)> = keyshares
+        .iter()
+        .map(|keyshare| {
+            let share = keyshare[1..].iter().copied().map(Gf256).collect();
+            (Gf256(keyshare[0]), share)
+        })
+        .collect();
+    let mut key = [0; KEY_LEN];
+    for (byte, y) in key.iter_mut().zip(unlock_over(&points)?) {
+        *byte = y.0;
+    }
+    Ok(key)
+}
+
+/// Split a message into shares in the layout of `sss_create_shares`.
+///
+/// # Errors
+/// * This function will generate an error if the threshold is 0 or greater
+///   than the number of shares.
+pub fn split(config: &Config, message: &[u8; MESSAGE_LEN]) -> Result<Vec<Share>> {
+    config.validate()?;
+    let mut key = [0; KEY_LEN];
+    rand::rng().fill_bytes(&mut key);
+    let ciphertext = seal(&key, message);
+
+    Ok(split_key(config, &key)?
+        .iter()
+        .map(|keyshare| {
+            let mut share = [0; SHARE_LEN];
+            share[..KEYSHARE_LEN].copy_from_slice(keyshare);
+            share[KEYSHARE_LEN..].copy_from_slice(&ciphertext);
+            share
+        })
+        .collect())
+}
+
+/// Combine shares in the layout of `sss_create_shares` into the message,
+/// like `sss_combine_shares`.
+///
+/// # Errors
+/// * This function will generate an error if no shares are given, or an
+///   index is 0 or given more than once.
+/// * This function will generate an error if the shares hold different
+///   encrypted messages, or the message cannot be decrypted, as happens with
+///   too few shares.
+pub fn combine(shares: &[Share]) -> Result<[u8; MESSAGE_LEN]> {
+    let keyshares: Vec<Keyshare> = shares
+        .iter()
+        .map(|share| {
+            let mut keyshare = [0; KEYSHARE_LEN];
+            keyshare.copy_from_slice(&share[..KEYSHARE_LEN]);
+            keyshare
+        })
+        .collect();
+    let key = combine_keys(&keyshares)?;
+    let ciphertext = &shares[0][KEYSHARE_LEN..];
+    if shares
+        .iter()
+        .any(|share| share[KEYSHARE_LEN..] != *ciphertext)
+    {
+        return Err(SssCiphertextMismatch.into());
+    }
+
+    let plaintext = XSalsa20Poly1305::new(Key::from_slice(&key))
+        .decrypt(&Nonce::default(), ciphertext)
+        .map_err(|_| SssDecrypt)?;
+    let mut message = [0; MESSAGE_LEN];
+    message.copy_from_slice(&plaintext);
+    Ok(message)
+}
+
+/// Encrypt the message like `crypto_secretbox`, with the tag in front
+fn seal(key: &[u8; KEY_LEN], message: &[u8; MESSAGE_LEN]) -> Vec<u8> {
+    XSalsa20Poly1305::new(Key::from_slice(key))
+        .encrypt(&Nonce::default(), &message[..])
+        .expect("a 64-byte message can always be encrypted")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Config, KEYSHARE_LEN, MESSAGE_LEN, combine, combine_keys, split, split_key};
+    use crate::{
+        encoding::{Hex, ShareEncoding},
+        unlock,
+        utils::check_err_result,
+    };
+    use anyhow::Result;
+    use crypto_secretbox::{
+        Key, Nonce, XSalsa20Poly1305,
+        aead::{Aead, KeyInit},
+    };
+
+    fn message() -> [u8; MESSAGE_LEN] {
+        let mut message = [0; MESSAGE_LEN];
+        for (byte, i) in message.iter_mut().zip(0..) {
+            *byte = i * 3;
+        }
+        message
+    }
+
+    #[test]
+    fn split_and_combine_keys() -> Result<()> {
+        let key: [u8; 32] = std::array::from_fn(|i| u8::try_from(i).unwrap_or(0) ^ 0xa5);
+        for (threshold, num_shares) in [(1, 1), (1, 3), (2, 2), (3, 5), (255, 255)] {
+            let config = Config::builder()
+                .threshold(threshold)
+                .num_shares(num_shares)
+                .build();
+            let keyshares = split_key(&config, &key)?;
+            assert_eq!(keyshares.len(), usize::from(num_shares));
+            let threshold = usize::from(threshold);
+            assert_eq!(combine_keys(&keyshares[..threshold])?, key);
+            assert_eq!(
+                combine_keys(&keyshares[keyshares.len() - threshold..])?,
+                key
+            );
+            if threshold > 1 {
+                assert_ne!(combine_keys(&keyshares[1..threshold])?, key);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn keyshares_are_shares() -> Result<()> {
+        // A key share is a share over the crate's field, with the index first
+        let key = [0x5c; 32];
+        let keyshares = split_key(&Config::default(), &key)?;
+        let shares = keyshares
+            .iter()
+            .map(|keyshare| Hex.encode(keyshare[0], &keyshare[1..]))
+            .collect::<Vec<String>>();
+        assert_eq!(unlock(&shares[2..])?, key);
+        Ok(())
+    }
+
+    #[test]
+    fn shares_hold_a_secretbox() -> Result<()> {
+        let shares = split(&Config::default(), &message())?;
+        let keyshares: Vec<[u8; KEYSHARE_LEN]> = shares
+            .iter()
+            .map(|share| {
+                share[..KEYSHARE_LEN]
+                    .try_into()
+                    .unwrap_or([0; KEYSHARE_LEN])
+            })
+            .collect();
+        let key = combine_keys(&keyshares[..3])?;
+        let sealed = XSalsa20Poly1305::new(Key::from_slice(&key))
+            .encrypt(&Nonce::from([0; 24]), &message()[..])
+            .unwrap_or_default();
+        for share in &shares {
+            assert_eq!(share[KEYSHARE_LEN..], sealed[..]);
+        }
+        assert_eq!(combine(&shares[..3])?, message());
+        assert_eq!(combine(&shares[2..])?, message());
+        Ok(())
+    }
+
+    #[test]
+    fn bad_shares_error() -> Result<()> {
+        let shares = split(&Config::default(), &message())?;
+        check_err_result(combine(&[]), "The shares map cannot be empty")?;
+        check_err_result(combine(&shares[..2]), "The shares could not be decrypted")?;
+        let mut damaged = shares[..3].to_vec();
+        damaged[2][60] ^= 1;
+        check_err_result(
+            combine(&damaged),
+            "The shares do not all hold the same encrypted message",
+        )?;
+        let byte = damaged[2][60];
+        for share in &mut damaged[..2] {
+            share[60] = byte;
+        }
+        check_err_result(combine(&damaged), "The shares could not be decrypted")?;
+        check_err_result(
+            combine(&[shares[0], shares[1], shares[0]]),
+            "A share index is used more than once",
+        )?;
+        check_err_result(
+            split(&Config::builder().threshold(0).build(), &message()),
+            "The threshold must be greater than 0",
+        )?;
+        check_err_result(
+            split_key(&Config::builder().threshold(6).build(), &[0; 32]),
+            "You have specified an invalid threshold.  It must be less than or equal to the number of shares. (6 is not <= 5)",
+        )
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` share URIs
+
+#[cfg(feature = "qr")]
+mod qr;
+
+use crate::{SsssConfig, error::SsssError::InvalidUri};
+use anyhow::Result;
+use std::{fmt, str::FromStr};
+
+/// The URI scheme of a share
+const SCHEME: &str = "ssss:";
+
+/// A share wrapped in an `ssss:` URI, ready to be rendered as a QR code and
+/// scanned back in, e.g. `ssss:.3:.4F2x...:~9Gm?t=3&n=5`.
+///
+/// The query carries the share header, the threshold `t` and the total number
+/// of shares `n`, so that whoever scans a share knows how many more are
+/// needed.  Characters outside the URI unreserved set, such as the spaces of a
+/// mnemonic share, are percent-encoded.
+///
+/// With the `qr` feature enabled, the URI can be rendered as a QR code in SVG
+/// or PNG format.  [`unlock`](crate::unlock) accepts share URIs directly, so
+/// scanner output can be passed in as-is.
+///
+/// # Example
+/// ```
+/// # use anyhow::Result;
+/// # use ssss::{gen_shares, unlock, ShareUri, SsssConfig};
+/// #
+/// # pub fn main() -> Result<()> {
+/// let secret = "correct horse battery staple".as_bytes();
+/// let config = SsssConfig::default();
+/// let shares = gen_shares(&config, &secret)?;
+///
+/// let uris: Vec<String> = shares
+///     .iter()
+///     .map(|share| ShareUri::new(&config, share).to_string())
+///     .collect();
+/// let scanned: ShareUri = uris[0].parse()?;
+/// assert_eq!(scanned.share(), shares[0]);
+/// assert_eq!(scanned.threshold(), Some(3));
+///
+/// assert_eq!(unlock(&uris)?, secret);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ShareUri {
+    share: String,
+    threshold: Option<u16>,
+    num_shares: Option<u16>,
+}
+
+impl ShareUri {
+    /// Wrap a share generated with the given configuration
+    #[must_use]
+    pub fn new(config: &SsssConfig, share: &str) -> Self {
+        Self {
+            share: share.to_string(),
+            threshold: Some(config.threshold()),
+            num_shares: Some(config.num_shares()),
+        }
+    }
+
+    /// The share text
+    #[must_use]
+    pub fn share(&self) -> &str {
+        &self.share
+    }
+
+    /// The number of shares required to unlock the secret, if known
+    #[must_use]
+    pub fn threshold(&self) -> Option<u16> {
+        self.threshold
+    }
+
+    /// The total number of shares generated, if known
+    #[must_use]
+    pub fn num_shares(&self) -> Option<u16> {
+        self.num_shares
+    }
+}
+
+impl fmt::Display for ShareUri {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{SCHEME}")?;
+        for byte in self.share.bytes() {
+            if byte.is_ascii_alphanumeric() || b"-._~:".contains(&byte) {
+                write!(f, "{}", char::from(byte))?;
+            } else {
+                write!(f, "%{byte:02X}")?;
+            }
+        }
+        let header: Vec<String> = [("t", self.threshold), ("n", self.num_shares)]
+            .iter()
+            .filter_map(|(key, value)| value.map(|value| format!("{key}={value}")))
+            .collect();
+        if !header.is_empty() {
+            write!(f, "?{}", header.join("&"))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ShareUri {
+    type Err = anyhow::Error;
+
+    /// Parse scanned URI text.  Surrounding whitespace is ignored, as are
+    /// unknown header fields.
+    fn from_str(uri: &str) -> Result<Self> {
+        let uri = uri.trim();
+        let rest = uri
+            .get(..SCHEME.len())
+            .filter(|scheme| scheme.eq_ignore_ascii_case(SCHEME))
+            .map(|_| &uri[SCHEME.len()..])
+            .ok_or(InvalidUri)?;
+        let (share, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let mut parsed = Self {
+            share: percent_decode(share)?,
+            threshold: None,
+            num_shares: None,
+        };
+        for (key, value) in query.split('&').filter_map(|field| field.split_once('=')) {
+            match key {
+                "t" => parsed.threshold = Some(value.parse()?),
+                "n" => parsed.num_shares = Some(value.parse()?),
+                _ => {}
+            }
+        }
+        if parsed.share.is_empty() {
+            Err(InvalidUri.into())
+        } else {
+            Ok(parsed)
+        }
+    }
+}
+
+/// Unwrap the share from a share URI, or return the share unchanged
+pub(crate) fn strip(share: String) -> Result<String> {
+    if is_uri(&share) {
+        Ok(share.parse::<ShareUri>()?.share)
+    } else {
+        Ok(share)
+    }
+}
+
+fn is_uri(share: &str) -> bool {
+    share
+        .trim_start()
+        .get(..SCHEME.len())
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case(SCHEME))
+}
+
+fn percent_decode(input: &str) -> Result<String> {
+    let mut bytes = Vec::with_capacity(input.len());
+    let mut iter = input.bytes();
+    while let Some(byte) = iter.next() {
+        if byte == b'%' {
+            let hex = [iter.next(), iter.next()];
+            let hex = hex
+                .iter()
+                .flatten()
+                .filter(|b| b.is_ascii_hexdigit())
+                .map(|b| char::from(*b))
+                .collect::<String>();
+            if hex.len() != 2 {
+                return Err(InvalidUri.into());
+            }
+            bytes.push(u8::from_str_radix(&hex, 16)?);
+        } else {
+            bytes.push(byte);
+        }
+    }
+    Ok(String::from_utf8(bytes)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ShareUri, strip};
+    use crate::{
+        Bip39, ShareEncoding, SsssConfig, gen_shares, reencode, unlock, utils::check_err_result,
+    };
+    use anyhow::Result;
+
+    #[test]
+    fn uri_roundtrip() -> Result<()> {
+        let config = SsssConfig::builder().num_shares(4).threshold(2).build();
+        let shares = gen_shares(&config, b"correct horse battery staple")?;
+        let uri = ShareUri::new(&config, &shares[1]);
+        assert_eq!(uri.to_string(), format!("ssss:{}?t=2&n=4", shares[1]));
+        assert_eq!(uri.to_string().parse::<ShareUri>()?, uri);
+        Ok(())
+    }
+
+    #[test]
+    fn mnemonic_shares_are_percent_encoded() -> Result<()> {
+        let share = Bip39.encode(1, b"correct horse battery staple");
+        let uri = ShareUri::new(&SsssConfig::default(), &share).to_string();
+        assert!(!uri.contains(' '));
+        assert!(uri.contains("%20"));
+        assert_eq!(uri.parse::<ShareUri>()?.share(), share);
+        Ok(())
+    }
+
+    #[test]
+    fn scanner_output_is_accepted() -> Result<()> {
+        let parsed: ShareUri = "  SSSS:hex:03:abcd:0011?n=5&x=y&t=3\r\n".parse()?;
+        assert_eq!(parsed.share(), "hex:03:abcd:0011");
+        assert_eq!(parsed.threshold(), Some(3));
+        assert_eq!(parsed.num_shares(), Some(5));
+        let parsed: ShareUri = "ssss:hex:03:abcd:0011".parse()?;
+        assert_eq!(parsed.threshold(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn invalid_uris_error() -> Result<()> {
+        check_err_result("sss:abc".parse::<ShareUri>(), "Invalid share URI")?;
+        check_err_result("ssss:".parse::<ShareUri>(), "Invalid share URI")?;
+        check_err_result("ssss:ab%2".parse::<ShareUri>(), "Invalid share URI")
+    }
+
+    #[test]
+    fn unlock_accepts_uris() -> Result<()> {
+        let secret = "correct horse battery staple".as_bytes();
+        let config = SsssConfig::default();
+        let shares = gen_shares(&config, secret)?;
+        let scanned = vec![
+            format!("{}\n", ShareUri::new(&config, &shares[0])),
+            ShareUri::new(&config, &reencode(&shares[2], &Bip39)?).to_string(),
+            shares[4].clone(),
+        ];
+        assert_eq!(unlock(&scanned)?, secret);
+        assert_eq!(strip(shares[3].clone())?, shares[3]);
+        Ok(())
+    }
+}
@@ -0,0 +1,132 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! `ssss` QR code rendering
+
+use super::ShareUri;
+use anyhow::Result;
+use png::{BitDepth, ColorType, Encoder};
+use qrcode::{Color, EcLevel, QrCode, render::svg};
+
+/// The width of the light border around the code, in modules
+const QUIET_ZONE: usize = 4;
+/// The size of a single module in PNG output, in pixels
+const MODULE_PIXELS: usize = 8;
+
+impl ShareUri {
+    /// Render the share URI as a QR code in SVG format.
+    ///
+    /// The code uses error correction level Q, so up to a quarter of it can be
+    /// damaged and still scan.
+    ///
+    /// # Errors
+    /// * This function will generate an error if the URI is too long to fit in
+    ///   a QR code.
+    #[cfg_attr(docsrs, doc(cfg(feature = "qr")))]
+    pub fn to_svg(&self) -> Result<String> {
+        Ok(self
+            .qr_code()?
+            .render::<svg::Color<'_>>()
+            .module_dimensions(8, 8)
+            .build())
+    }
+
+    /// Render the share URI as a QR code in PNG format, eight pixels per module,
+    /// with the same error correction as [`ShareUri::to_svg`].
+    ///
+    /// # Errors
+    /// * This function will generate an error if the URI is too long to fit in
+    ///   a QR code.
+    #[cfg_attr(docsrs, doc(cfg(feature = "qr")))]
+    pub fn to_png(&self) -> Result<Vec<u8>> {
+        let code = self.qr_code()?;
+        let modules = code.width();
+        let colors = code.to_colors();
+        let size = (modules + 2 * QUIET_ZONE) * MODULE_PIXELS;
+
+        let mut pixels = vec![u8::MAX; size * size];
+        for (i, color) in colors.iter().enumerate() {
+            if *color == Color::Dark {
+                let x = (i % modules + QUIET_ZONE) * MODULE_PIXELS;
+                let y = (i / modules + QUIET_ZONE) * MODULE_PIXELS;
+                for row in y..y + MODULE_PIXELS {
+                    pixels[row * size + x..row * size + x + MODULE_PIXELS].fill(0);
+                }
+            }
+        }
+
+        let mut png = vec![];
+        let width = u32::try_from(size)?;
+        let mut encoder = Encoder::new(&mut png, width, width);
+        encoder.set_color(ColorType::Grayscale);
+        encoder.set_depth(BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&pixels)?;
+        writer.finish()?;
+        Ok(png)
+    }
+
+    fn qr_code(&self) -> Result<QrCode> {
+        Ok(QrCode::with_error_correction_level(
+            self.to_string(),
+            EcLevel::Q,
+        )?)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{MODULE_PIXELS, QUIET_ZONE};
+    use crate::{SsssConfig, gen_shares, uri::ShareUri};
+    use anyhow::Result;
+    use qrcode::Color;
+
+    fn uri() -> Result<ShareUri> {
+        let config = SsssConfig::default();
+        let shares = gen_shares(&config, b"correct horse battery staple")?;
+        Ok(ShareUri::new(&config, &shares[0]))
+    }
+
+    #[test]
+    fn svg_works() -> Result<()> {
+        let svg = uri()?.to_svg()?;
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.contains("<svg"));
+        Ok(())
+    }
+
+    #[test]
+    fn png_matches_code() -> Result<()> {
+        let uri = uri()?;
+        let png = uri.to_png()?;
+        let mut decoder = png::Decoder::new(std::io::Cursor::new(png)).read_info()?;
+        let mut pixels = vec![0; decoder.output_buffer_size().unwrap_or_default()];
+        let info = decoder.next_frame(&mut pixels)?;
+
+        let code = uri.qr_code()?;
+        let modules = code.width();
+        let size = usize::try_from(info.width)?;
+        assert_eq!(size, (modules + 2 * QUIET_ZONE) * MODULE_PIXELS);
+        for (i, color) in code.to_colors().iter().enumerate() {
+            let x = (i % modules + QUIET_ZONE) * MODULE_PIXELS + MODULE_PIXELS / 2;
+            let y = (i / modules + QUIET_ZONE) * MODULE_PIXELS + MODULE_PIXELS / 2;
+            assert_eq!(pixels[y * size + x] == 0, *color == Color::Dark);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn oversized_uri_errors() {
+        let share = "0".repeat(8192);
+        assert!(
+            ShareUri::new(&SsssConfig::default(), &share)
+                .to_png()
+                .is_err()
+        );
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright (c) 2020 ssss developers
+//
+// Licensed under the Apache License, Version 2.0
+// <LICENSE-APACHE or http://www.apache.org/licenses/LICENSE-2.0> or the MIT
+// license <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. All files in the project carrying such notice may not be copied,
+// modified, or distributed except according to those terms.
+
+//! [HashiCorp Vault](https://developer.hashicorp.com/vault/docs/concepts/seal)
+//! unseal key conversion.
+//!
+//! Vault splits its root key with Shamir's Secret Sharing over the same
+//! GF(2^8) as this crate, reduced by `0x11b`, so its shares only differ from
+//! ours in how they are written.  An unseal key is the share followed by a
+//! byte holding the share index, encoded in base64 or hex.
+//!
+//! [`import`] turns an unseal key into a share that [`unlock`](crate::unlock)
+//! accepts, and [`export`] turns a share into an unseal key that Vault
+//! accepts.  Shares of a [padded](crate::Padding) secret give Vault the padded
+//! secret.
+//!
+//! # Example
+//! ```
+//! # use anyhow::Result;
+//! # use ssss::{unlock, vault, Hex};
+//! #
+//! # pub fn main() -> Result<()> {
+//! let keys = [
+//!     "4vDBx7NKzI5SNvAhjEe0NlgPMpGcv1VAr9fUPLlUPqIv",
+//!     "dbLBGQBGHJZzI6Z9st21rKCK1rdyA6lK9Ctpr7f4B7GR",
+//!     "W/8h7OHwtsrnUtYvaunaumG8zbSXDKlXmSbw1AI9lJsH",
+//! ];
+//! let shares = keys
+//!     .iter()
+//!     .map(|key| vault::import(key, &Hex))
+//!     .collect::<Result<Vec<String>>>()?;
+//! assert!(shares[0].starts_with("hex:2f:e2f0c1c7b34acc8e5236f0218c47b4"));
+//!
+//! let root_key = unlock(&shares)?;
+//! assert_eq!(root_key, (0x10..0x30).collect::<Vec<u8>>());
+//! assert_eq!(vault::export(&shares[0])?, keys[0]);
+//! # Ok(())
+//! # }
+//! ```
+
+use crate::{
+    encoding::{ShareEncoding, base64url, hex},
+    error::SsssError::{IndexZero, InvalidVaultKey},
+    shamir::decode_share,
+};
+use anyhow::Result;
+
+/// Convert a Vault unseal key, in base64 or hex, into a share with the given
+/// encoding.
+///
+/// Like Vault, a key that can be read as hex is read as hex.
+///
+/// # Errors
+/// * This function will generate an error if the key is not valid base64 or
+///   hex, holds no share, or has the share index 0.
+pub fn import(key: &str, encoding: &dyn ShareEncoding) -> Result<String> {
+    let key = key.trim();
+    let bytes = hex::decode(key)
+        .ok()
+        .or_else(|| decode_base64(key))
+        .ok_or(InvalidVaultKey)?;
+    match bytes.split_last() {
+        Some((0, _)) => Err(IndexZero.into()),
+        Some((index, share)) if !share.is_empty() => Ok(encoding.encode(*index, share)),
+        _ => Err(InvalidVaultKey.into()),
+    }
+}
+
+/// Convert a share into a base64 Vault unseal key.
+///
+/// # Errors
+/// * This function will generate an error if the share cannot be decoded, or
+///   was not split over GF(2^8).
+pub fn export(share: &str) -> Result<String> {
+    let (index, mut bytes) = decode_share(share.to_string())?;
+    bytes.push(index);
+    Ok(encode_base64(&bytes))
+}
+
+/// Standard, padded base64 ([RFC 4648 §4](https://www.rfc-editor.org/rfc/rfc4648#section-4))
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = base64url::encode(bytes).replace('-', "+").replace('_', "/");
+    while encoded.len() % 4 != 0 {
+        encoded.push('=');
+    }
+    encoded
+}
+
+fn decode_base64(input: &str) -> Option<Vec<u8>> {
+    if input.contains(['-', '_']) {
+        None
+    } else {
+        base64url::decode(&input.replace('+', "-").replace('/', "_")).ok()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode_base64, export, import};
+    use crate::{
+        Encoding, SsssConfig,
+        encoding::{Base64Url, Hex},
+        gen_shares, unlock,
+        utils::check_err_result,
+    };
+    use anyhow::Result;
+
+    /// A 3-of-5 split of the bytes `0x10..0x30` in Vault's layout, computed
+    /// with a transcription of Vault's `shamir.Split`
+    const VAULT_KEYS: [&str; 5] = [
+        "4vDBx7NKzI5SNvAhjEe0NlgPMpGcv1VAr9fUPLlUPqIv",
+        "dbLBGQBGHJZzI6Z9st21rKCK1rdyA6lK9Ctpr7f4B7GR",
+        "W/8h7OHwtsrnUtYvaunaumG8zbSXDKlXmSbw1AI9lJsH",
+        "ilSv50vXYcbCP801U0ytqBui7NDjY9arpdhYvYLvuGjU",
+        "ObuNjkmP/n7IvCcXPgMUcEjDoS1XVPWTxvIF2yrCE8dc",
+    ];
+
+    fn root_key() -> Vec<u8> {
+        (0x10..0x30).collect()
+    }
+
+    /// Vault's `mult`, multiplying in GF(2^8) bit by bit
+    fn vault_mult(a: u8, b: u8) -> u8 {
+        (0..8).rev().fold(0_u8, |r, i| {
+            (((b >> i) & 1).wrapping_neg() & a)
+                ^ ((r >> 7).wrapping_neg() & 0x1b)
+                ^ r.wrapping_add(r)
+        })
+    }
+
+    /// Vault's `shamir.Combine`, with the inverse as `a^254`
+    fn vault_combine(keys: &[Vec<u8>]) -> Vec<u8> {
+        let inverse = |a: u8| (0..253).fold(a, |power, _| vault_mult(power, a));
+        let xs: Vec<u8> = keys.iter().map(|key| key[key.len() - 1]).collect();
+        (0..keys[0].len() - 1)
+            .map(|idx| {
+                let mut result = 0;
+                for (i, key) in keys.iter().enumerate() {
+                    let mut basis = 1;
+                    for (j, x) in xs.iter().enumerate() {
+                        if i != j {
+                            let term = vault_mult(*x, inverse(xs[i] ^ x));
+                            basis = vault_mult(basis, term);
+                        }
+                    }
+                    result ^= vault_mult(key[idx], basis);
+                }
+                result
+            })
+            .collect()
+    }
+
+    #[test]
+    fn import_vault_keys() -> Result<()> {
+        let shares = VAULT_KEYS
+            .iter()
+            .map(|key| import(key, &Base64Url))
+            .collect::<Result<Vec<String>>>()?;
+        assert_eq!(unlock(&shares)?, root_key());
+        assert_eq!(unlock(&shares[2..])?, root_key());
+        assert_ne!(unlock(&shares[3..])?, root_key());
+
+        // Vault also prints unseal keys in hex
+        let hex_key = "8a54afe74bd761c6c23fcd35534cada81ba2ecd0e363d6aba5d858bd82efb868d4";
+        let mut shares = shares[..2].to_vec();
+        shares.push(import(hex_key, &Hex)?);
+        assert_eq!(unlock(&shares)?, root_key());
+        Ok(())
+    }
+
+    #[test]
+    fn export_to_vault() -> Result<()> {
+        for key in VAULT_KEYS {
+            assert_eq!(export(&import(key, &Hex)?)?, key);
+        }
+
+        let secret = b"correct horse battery staple";
+        let config = SsssConfig::builder().encoding(Encoding::Bech32m).build();
+        let keys = gen_shares(&config, secret)?
+            .iter()
+            .map(|share| export(share))
+            .collect::<Result<Vec<String>>>()?;
+        let bytes = keys
+            .iter()
+            .map(|key| decode_base64(key).unwrap_or_default())
+            .collect::<Vec<Vec<u8>>>();
+        assert_eq!(vault_combine(&bytes[..3]), secret);
+        assert_eq!(vault_combine(&bytes[2..]), secret);
+        Ok(())
+    }
+
+    #[test]
+    fn bad_keys_error() -> Result<()> {
+        check_err_result(import("not a key!", &Hex), "Invalid Vault unseal key")?;
+        check_err_result(import("Lw==", &Hex), "Invalid Vault unseal key")?;
+        check_err_result(import("", &Hex), "Invalid Vault unseal key")?;
+        check_err_result(import("abcd00", &Hex), "A share index cannot be 0")?;
+        check_err_result(
+            import("4vDBx7NKzI5SNvAhjEe0NlgPMpGcv1VAr9fUPLlUPqI-", &Hex),
+            "Invalid Vault unseal key",
+        )
+    }
+}